@@ -3,7 +3,8 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde_json::{json, Map, Value};
+use validator::{ValidationErrors, ValidationErrorsKind};
 
 #[derive(Debug)]
 pub struct AnyhowError(anyhow::Error);
@@ -34,6 +35,8 @@ pub enum AuthError {
     TokenCreation,
     InvalidToken,
     ExpiredSignature,
+    Forbidden,
+    InvalidWorkshop,
 }
 
 impl IntoResponse for AuthError {
@@ -43,6 +46,8 @@ impl IntoResponse for AuthError {
             AuthError::MissingCredentials => (StatusCode::BAD_REQUEST, "Missing credentials"),
             AuthError::TokenCreation => (StatusCode::INTERNAL_SERVER_ERROR, "Token creation error"),
             AuthError::InvalidToken => (StatusCode::BAD_REQUEST, "Invalid token"),
+            AuthError::Forbidden => (StatusCode::FORBIDDEN, "Permission denied"),
+            AuthError::InvalidWorkshop => (StatusCode::BAD_REQUEST, "Invalid workshop"),
         };
         let body = Json(json!({
             "code": status.as_u16(),
@@ -52,6 +57,53 @@ impl IntoResponse for AuthError {
     }
 }
 
+#[derive(Debug)]
+pub struct ValidationError(pub ValidationErrors);
+
+// 把嵌套的校验错误展开成 "items[0].count": ["..."] 的形式
+fn field_errors(prefix: &str, errors: &ValidationErrors, fields: &mut Map<String, Value>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{prefix}.{field}")
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                let messages = errors
+                    .iter()
+                    .map(|e| match &e.message {
+                        Some(message) => message.to_string(),
+                        None => e.code.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                fields.insert(path, json!(messages));
+            }
+            ValidationErrorsKind::Struct(errors) => field_errors(&path, errors, fields),
+            ValidationErrorsKind::List(errors) => {
+                for (index, errors) in errors {
+                    field_errors(&format!("{path}[{index}]"), errors, fields);
+                }
+            }
+        }
+    }
+}
+
+impl IntoResponse for ValidationError {
+    fn into_response(self) -> Response {
+        let mut fields = Map::new();
+        field_errors("", &self.0, &mut fields);
+
+        let status = StatusCode::UNPROCESSABLE_ENTITY;
+        let body = Json(json!({
+            "code": status.as_u16(),
+            "reason": "Validation failed",
+            "errors": fields,
+        }));
+        (status, body).into_response()
+    }
+}
+
 pub enum AppError {
     AuthError(AuthError),
     AnyhowError(AnyhowError),
@@ -0,0 +1,79 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, Query, Request},
+    http::request::Parts,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use service::Sortable;
+use validator::Validate;
+
+use crate::error::ValidationError;
+
+// 和 Json 一样, 反序列化后再校验, 校验失败返回 422
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        value
+            .validate()
+            .map_err(|e| ValidationError(e).into_response())?;
+
+        Ok(ValidatedJson(value))
+    }
+}
+
+// 和 Query 一样, 反序列化后再校验, 校验失败返回 422
+pub struct ValidatedQuery<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        value
+            .validate()
+            .map_err(|e| ValidationError(e).into_response())?;
+
+        Ok(ValidatedQuery(value))
+    }
+}
+
+// 列表查询参数, 在 ValidatedQuery 的基础上再校验 sort_by 是否为允许排序的列
+pub struct ValidatedListQuery<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ValidatedListQuery<T>
+where
+    T: DeserializeOwned + Validate + Sortable,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ValidatedQuery(value) = ValidatedQuery::<T>::from_request_parts(parts, state).await?;
+        value
+            .validate_sort_by()
+            .map_err(|e| ValidationError(e).into_response())?;
+
+        Ok(ValidatedListQuery(value))
+    }
+}
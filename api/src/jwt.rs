@@ -4,20 +4,42 @@ use axum_extra::{
     typed_header::TypedHeader,
 };
 use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use sea_orm::DbErr;
 use serde::{Deserialize, Serialize};
+use service::workshop::{WorkshopRole, WorkshopService};
 
-use crate::{error::AuthError, state::AppState};
+use crate::{
+    error::{AppError, AuthError},
+    state::AppState,
+};
+
+// 指定当前工坊的请求头, 优先于 token 中的工坊
+const WORKSHOP_HEADER: &str = "x-workshop-id";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub user_id: i32,
+    // 选择工坊后签发的 token 会带上工坊 id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workshop_id: Option<i32>,
     pub exp: i64,
 }
 
 impl Claims {
     pub fn new(user_id: i32) -> Self {
         let exp = (chrono::Utc::now() + chrono::Duration::days(15)).timestamp();
-        Self { user_id, exp }
+        Self {
+            user_id,
+            workshop_id: None,
+            exp,
+        }
+    }
+
+    pub fn with_workshop(user_id: i32, workshop_id: i32) -> Self {
+        Self {
+            workshop_id: Some(workshop_id),
+            ..Self::new(user_id)
+        }
     }
 
     // 生成token
@@ -68,3 +90,52 @@ impl FromRequestParts<AppState> for Claims {
         Self::decode(header.token(), &state.jwt_secret)
     }
 }
+
+// 当前请求所在的工坊, 依次取请求头, token 中的工坊和用户最早加入的工坊, 并校验成员身份
+#[derive(Debug, Clone)]
+pub struct CurrentWorkshop {
+    pub user_id: i32,
+    pub workshop_id: i32,
+    pub role: WorkshopRole,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for CurrentWorkshop {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        let header = match parts.headers.get(WORKSHOP_HEADER) {
+            Some(value) => Some(
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|v| v.trim().parse::<i32>().ok())
+                    .ok_or(AuthError::InvalidWorkshop)?,
+            ),
+            None => None,
+        };
+
+        let member = match header.or(claims.workshop_id) {
+            Some(workshop_id) => {
+                WorkshopService::membership(&state.db, workshop_id, claims.user_id).await
+            }
+            None => WorkshopService::default_membership(&state.db, claims.user_id).await,
+        };
+        let member = match member {
+            Ok(member) => member,
+            Err(DbErr::RecordNotFound(_)) => return Err(AuthError::Forbidden.into()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            user_id: member.user_id,
+            workshop_id: member.workshop_id,
+            role: member.role,
+        })
+    }
+}
@@ -1,27 +1,77 @@
 use axum::Router;
 use migration::{Migrator, MigratorTrait};
+use rate_limit::LoginLimitLayer;
 use sea_orm::DatabaseConnection;
+use service::{crypto::Cipher, user::UserService};
 use state::AppState;
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tower_http::{cors::CorsLayer, timeout::TimeoutLayer};
 
 mod error;
+mod extract;
 mod jwt;
+mod rate_limit;
 mod routes;
 mod state;
+pub mod storage;
 
-pub async fn router(db: DatabaseConnection, jwt_secret: String) -> anyhow::Result<Router> {
+pub async fn router(
+    db: DatabaseConnection,
+    jwt_secret: String,
+    staff_encryption_key: String,
+    behind_proxy: bool,
+) -> anyhow::Result<Router> {
     Migrator::up(&db, None).await?;
 
+    // 环境变量 ADMIN_PHONE_NUMBER 指定的用户为管理员, 可以为其他用户生成密码重置码
+    if let Ok(phone_number) = std::env::var("ADMIN_PHONE_NUMBER") {
+        if !UserService::grant_admin(&db, phone_number.clone()).await? {
+            tracing::warn!("admin {phone_number} is not registered, restart after registering");
+        }
+    }
+
+    // 默认把上传的文件保存在本地, 开启 s3 feature 后使用对象存储
+    #[cfg(not(feature = "s3"))]
+    let storage = storage::LocalStorage::new("uploads", "/uploads");
+    #[cfg(feature = "s3")]
+    let storage = storage::S3Storage::from_env(std::env::var("S3_PUBLIC_URL")?)?;
+
+    // 员工敏感信息的加密密钥与 jwt 密钥分开管理
+    let cipher = Cipher::new(&staff_encryption_key)
+        .map_err(|err| anyhow::anyhow!("invalid STAFF_ENCRYPTION_KEY: {err}"))?;
+
+    let login_limit = LoginLimitLayer::from_env(behind_proxy);
+
     let router = Router::new()
-        .merge(routes::user::route())
+        .merge(routes::user::route(login_limit))
         .merge(routes::boss::route())
         .merge(routes::staff::route())
         .merge(routes::clothing::route())
+        .merge(routes::material::route())
         .merge(routes::procurement::route())
+        .merge(routes::supplier::route())
+        .merge(routes::sales_order::route())
         .merge(routes::shipment::route())
         .merge(routes::production::route())
-        .with_state(AppState::new(db, jwt_secret))
+        .merge(routes::inspection::route())
+        .merge(routes::process_step::route())
+        .merge(routes::attendance::route())
+        .merge(routes::payroll::route())
+        .merge(routes::staff_adjustment::route())
+        .merge(routes::analytics::route())
+        .merge(routes::accounting_period::route())
+        .merge(routes::dashboard::route())
+        .merge(routes::search::route())
+        .merge(routes::workshop::route());
+
+    #[cfg(not(feature = "s3"))]
+    let router = router.nest_service(
+        "/uploads",
+        tower_http::services::ServeDir::new(storage.root()),
+    );
+
+    let router = router
+        .with_state(AppState::new(db, jwt_secret, Arc::new(storage), cipher))
         .layer(TimeoutLayer::new(Duration::from_secs(5)))
         .layer(CorsLayer::permissive());
     Ok(router)
@@ -0,0 +1,503 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tower::{Layer, Service};
+
+// 登录请求体最大长度
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+// 超过这个数量时清理过期的记录, 仍然超过时丢弃最早过期的一半
+const MAX_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Policy {
+    // 允许连续失败的次数, 超过后按指数退避
+    free_failures: u32,
+    // 连续失败达到这个次数后锁定
+    lockout_failures: u32,
+    lockout: Duration,
+    max_backoff: Duration,
+}
+
+const ACCOUNT_POLICY: Policy = Policy {
+    free_failures: 3,
+    lockout_failures: 10,
+    lockout: Duration::from_secs(15 * 60),
+    max_backoff: Duration::from_secs(5 * 60),
+};
+
+const IP_POLICY: Policy = Policy {
+    free_failures: 10,
+    lockout_failures: 50,
+    lockout: Duration::from_secs(60 * 60),
+    max_backoff: Duration::from_secs(5 * 60),
+};
+
+impl Policy {
+    fn wait(&self, failures: u32) -> Duration {
+        if failures >= self.lockout_failures {
+            self.lockout
+        } else if failures > self.free_failures {
+            let exp = (failures - self.free_failures - 1).min(16);
+            Duration::from_secs(1 << exp).min(self.max_backoff)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Attempt {
+    failures: u32,
+    last_failure: Instant,
+    blocked_until: Instant,
+    // 之后既不再锁定也会重新计数, 记录可以丢弃
+    expire_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Limiter {
+    attempts: HashMap<String, Attempt>,
+}
+
+impl Limiter {
+    // 还需要等待的时间
+    fn blocked(&self, key: &str, now: Instant) -> Option<Duration> {
+        self.attempts
+            .get(key)
+            .filter(|a| a.blocked_until > now)
+            .map(|a| a.blocked_until - now)
+    }
+
+    fn fail(&mut self, key: String, policy: &Policy, now: Instant) {
+        self.cleanup(now);
+
+        let attempt = self.attempts.entry(key).or_insert(Attempt {
+            failures: 0,
+            last_failure: now,
+            blocked_until: now,
+            expire_at: now,
+        });
+        // 很久没有失败的话重新计数
+        if now.duration_since(attempt.last_failure) >= policy.lockout {
+            attempt.failures = 0;
+        }
+        attempt.failures += 1;
+        attempt.last_failure = now;
+        attempt.blocked_until = now + policy.wait(attempt.failures);
+        attempt.expire_at = attempt.blocked_until.max(now + policy.lockout);
+    }
+
+    // 撤销一次预先记录的失败
+    fn release(&mut self, key: &str, policy: &Policy) {
+        if let Some(attempt) = self.attempts.get_mut(key) {
+            attempt.failures = attempt.failures.saturating_sub(1);
+            attempt.blocked_until = attempt.last_failure + policy.wait(attempt.failures);
+        }
+    }
+
+    fn succeed(&mut self, key: &str) {
+        self.attempts.remove(key);
+    }
+
+    fn cleanup(&mut self, now: Instant) {
+        if self.attempts.len() < MAX_ENTRIES {
+            return;
+        }
+        self.attempts.retain(|_, a| a.expire_at > now);
+
+        // 伪造的账号很多时只保留最晚过期的一半, 避免无限增长
+        if self.attempts.len() >= MAX_ENTRIES {
+            let mut expire_at = self
+                .attempts
+                .values()
+                .map(|a| a.expire_at)
+                .collect::<Vec<_>>();
+            let index = expire_at.len() - MAX_ENTRIES / 2;
+            let (_, cutoff, _) = expire_at.select_nth_unstable(index);
+            let cutoff = *cutoff;
+            self.attempts.retain(|_, a| a.expire_at > cutoff);
+        }
+    }
+}
+
+// 登录请求的客户端 IP, 由限流层放到请求扩展中
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub String);
+
+#[derive(Debug, Deserialize)]
+struct LoginBody {
+    phone_number: Option<String>,
+}
+
+// 可信的反向代理
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrustedProxies {
+    // 直接连接的对端总是代理, 例如部署平台的负载均衡, 地址不固定无法列出
+    peer: bool,
+    ips: Vec<IpAddr>,
+}
+
+impl TrustedProxies {
+    // 逗号分隔的代理地址, peer 表示信任直接连接的对端
+    fn parse(value: &str) -> Self {
+        let mut proxies = Self::default();
+        for v in value.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            if v.eq_ignore_ascii_case("peer") {
+                proxies.peer = true;
+                continue;
+            }
+            match v.parse() {
+                Ok(ip) => proxies.ips.push(ip),
+                Err(_) => tracing::warn!("ignore invalid trusted proxy {v}"),
+            }
+        }
+        proxies
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.peer && self.ips.is_empty()
+    }
+}
+
+// 客户端 IP 取连接的对端地址, 对端是可信代理时从右往左取 X-Forwarded-For 中
+// 第一个不是可信代理的地址, 更左边的值由客户端控制, 不能使用
+fn client_ip(req: &Request, trusted_proxies: &TrustedProxies) -> Option<IpAddr> {
+    let ConnectInfo(peer) = req.extensions().get::<ConnectInfo<SocketAddr>>()?;
+    let mut ip = peer.ip();
+    if !trusted_proxies.peer && !trusted_proxies.ips.contains(&ip) {
+        return Some(ip);
+    }
+
+    let forwarded = req
+        .headers()
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .collect::<Vec<_>>();
+    for hop in forwarded.into_iter().rev() {
+        // 无法解析时停在最后一个可信的地址上
+        let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        ip = hop;
+        if !trusted_proxies.ips.contains(&ip) {
+            break;
+        }
+    }
+    Some(ip)
+}
+
+fn too_many_attempts(wait: Duration) -> Response {
+    let secs = wait.as_secs().max(1);
+    let status = StatusCode::TOO_MANY_REQUESTS;
+    let body = Json(json!({
+        "code": status.as_u16(),
+        "reason": format!("Too many login attempts, retry after {secs} seconds"),
+    }));
+
+    let mut response = (status, body).into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+    response
+}
+
+// 按账号和 IP 限制登录失败次数, 连续失败后指数退避, 失败过多时临时锁定
+#[derive(Debug, Clone, Default)]
+pub struct LoginLimitLayer {
+    limiter: Arc<Mutex<Limiter>>,
+    trusted_proxies: Arc<TrustedProxies>,
+}
+
+impl LoginLimitLayer {
+    pub fn new(trusted_proxies: TrustedProxies) -> Self {
+        Self {
+            limiter: Default::default(),
+            trusted_proxies: Arc::new(trusted_proxies),
+        }
+    }
+
+    // 环境变量 TRUSTED_PROXIES 为逗号分隔的反向代理地址, 没有代理时不需要设置.
+    // 部署在平台的代理之后时默认信任直接连接的对端, 否则所有客户端会共用代理的 IP
+    pub fn from_env(behind_proxy: bool) -> Self {
+        let mut trusted_proxies =
+            TrustedProxies::parse(&std::env::var("TRUSTED_PROXIES").unwrap_or_default());
+        if behind_proxy && trusted_proxies.is_empty() {
+            tracing::info!("TRUSTED_PROXIES is not set, trust the platform proxy");
+            trusted_proxies.peer = true;
+        }
+        Self::new(trusted_proxies)
+    }
+}
+
+impl<S> Layer<S> for LoginLimitLayer {
+    type Service = LoginLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LoginLimit {
+            inner,
+            limiter: self.limiter.clone(),
+            trusted_proxies: self.trusted_proxies.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginLimit<S> {
+    inner: S,
+    limiter: Arc<Mutex<Limiter>>,
+    trusted_proxies: Arc<TrustedProxies>,
+}
+
+impl<S> Service<Request> for LoginLimit<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // 使用已经 ready 的 inner, 留一个克隆给下一次调用
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = self.limiter.clone();
+        let trusted_proxies = self.trusted_proxies.clone();
+
+        Box::pin(async move {
+            // 拿不到对端地址说明没有用 into_make_service_with_connect_info 启动,
+            // 不能让所有请求共用一个计数
+            let Some(ip) = client_ip(&req, &trusted_proxies) else {
+                tracing::error!("login limit requires ConnectInfo<SocketAddr>");
+                return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            };
+            let (mut parts, body) = req.into_parts();
+            let bytes = match to_bytes(body, MAX_BODY_SIZE).await {
+                Ok(bytes) => bytes,
+                Err(_) => return Ok(StatusCode::PAYLOAD_TOO_LARGE.into_response()),
+            };
+            let phone_number = serde_json::from_slice::<LoginBody>(&bytes)
+                .ok()
+                .and_then(|b| b.phone_number);
+
+            let ip_key = format!("ip:{ip}");
+            let account_key = phone_number.map(|p| format!("account:{p}"));
+
+            // 转发前先按失败记一次, 并发的请求也会被计数和退避, 成功后再撤销
+            let wait = {
+                let mut limiter = limiter.lock().unwrap();
+                let now = Instant::now();
+                let wait = [Some(&ip_key), account_key.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|key| limiter.blocked(key, now))
+                    .max();
+                if wait.is_none() {
+                    limiter.fail(ip_key.clone(), &IP_POLICY, now);
+                    if let Some(account_key) = &account_key {
+                        limiter.fail(account_key.clone(), &ACCOUNT_POLICY, now);
+                    }
+                }
+                wait
+            };
+            if let Some(wait) = wait {
+                tracing::warn!("login blocked for {ip}, retry after {wait:?}");
+                return Ok(too_many_attempts(wait));
+            }
+
+            parts.extensions.insert(ClientIp(ip.to_string()));
+            let response = inner
+                .call(Request::from_parts(parts, Body::from(bytes)))
+                .await?;
+
+            if response.status().is_success() {
+                let mut limiter = limiter.lock().unwrap();
+                limiter.release(&ip_key, &IP_POLICY);
+                if let Some(account_key) = &account_key {
+                    limiter.succeed(account_key);
+                }
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_wait() {
+        let secs = |failures| ACCOUNT_POLICY.wait(failures).as_secs();
+
+        assert_eq!(secs(0), 0);
+        assert_eq!(secs(3), 0);
+        assert_eq!(secs(4), 1);
+        assert_eq!(secs(5), 2);
+        assert_eq!(secs(9), 32);
+        assert_eq!(secs(10), 15 * 60);
+        assert_eq!(secs(100), 15 * 60);
+    }
+
+    #[test]
+    fn policy_wait_capped_by_max_backoff() {
+        assert_eq!(IP_POLICY.wait(19).as_secs(), 256);
+        assert_eq!(IP_POLICY.wait(20).as_secs(), 5 * 60);
+        assert_eq!(IP_POLICY.wait(49).as_secs(), 5 * 60);
+        assert_eq!(IP_POLICY.wait(50).as_secs(), 60 * 60);
+    }
+
+    #[test]
+    fn blocked_after_free_failures() {
+        let mut limiter = Limiter::default();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            limiter.fail("a".into(), &ACCOUNT_POLICY, now);
+            assert_eq!(limiter.blocked("a", now), None);
+        }
+        limiter.fail("a".into(), &ACCOUNT_POLICY, now);
+        assert_eq!(limiter.blocked("a", now), Some(Duration::from_secs(1)));
+        assert_eq!(limiter.blocked("a", now + Duration::from_secs(1)), None);
+        assert_eq!(limiter.blocked("b", now), None);
+    }
+
+    #[test]
+    fn failures_reset_after_lockout() {
+        let mut limiter = Limiter::default();
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            limiter.fail("a".into(), &ACCOUNT_POLICY, now);
+        }
+        assert_eq!(limiter.blocked("a", now), Some(ACCOUNT_POLICY.lockout));
+
+        let later = now + ACCOUNT_POLICY.lockout;
+        assert_eq!(limiter.blocked("a", later), None);
+        limiter.fail("a".into(), &ACCOUNT_POLICY, later);
+        assert_eq!(limiter.attempts["a"].failures, 1);
+    }
+
+    #[test]
+    fn release_undoes_reservation() {
+        let mut limiter = Limiter::default();
+        let now = Instant::now();
+
+        for _ in 0..4 {
+            limiter.fail("a".into(), &ACCOUNT_POLICY, now);
+        }
+        assert!(limiter.blocked("a", now).is_some());
+
+        limiter.release("a", &ACCOUNT_POLICY);
+        assert_eq!(limiter.attempts["a"].failures, 3);
+        assert_eq!(limiter.blocked("a", now), None);
+
+        limiter.succeed("a");
+        assert!(!limiter.attempts.contains_key("a"));
+    }
+
+    #[test]
+    fn cleanup_removes_expired() {
+        let mut limiter = Limiter::default();
+        let now = Instant::now();
+
+        for i in 0..MAX_ENTRIES {
+            limiter.fail(format!("account:{i}"), &ACCOUNT_POLICY, now);
+        }
+        assert_eq!(limiter.attempts.len(), MAX_ENTRIES);
+
+        limiter.fail("a".into(), &ACCOUNT_POLICY, now + ACCOUNT_POLICY.lockout);
+        assert_eq!(limiter.attempts.len(), 1);
+    }
+
+    #[test]
+    fn cleanup_bounds_fresh_entries() {
+        let mut limiter = Limiter::default();
+        let now = Instant::now();
+
+        for i in 0..MAX_ENTRIES * 3 {
+            let now = now + Duration::from_millis(i as u64);
+            limiter.fail(format!("account:{i}"), &ACCOUNT_POLICY, now);
+            assert!(limiter.attempts.len() <= MAX_ENTRIES);
+        }
+        // 保留的是最近失败的记录
+        assert!(limiter
+            .attempts
+            .contains_key(&format!("account:{}", MAX_ENTRIES * 3 - 1)));
+        assert!(!limiter.attempts.contains_key("account:0"));
+    }
+
+    fn request(peer: &str, forwarded: Option<&str>) -> Request {
+        let mut req = Request::new(Body::empty());
+        req.extensions_mut()
+            .insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        if let Some(forwarded) = forwarded {
+            req.headers_mut()
+                .insert("x-forwarded-for", forwarded.parse().unwrap());
+        }
+        req
+    }
+
+    #[test]
+    fn client_ip_from_peer() {
+        let proxies = TrustedProxies::default();
+
+        let req = request("1.1.1.1:1000", Some("2.2.2.2"));
+        assert_eq!(client_ip(&req, &proxies), "1.1.1.1".parse().ok());
+
+        let req = Request::new(Body::empty());
+        assert_eq!(client_ip(&req, &proxies), None);
+    }
+
+    #[test]
+    fn client_ip_behind_trusted_proxy() {
+        let proxies = TrustedProxies::parse("10.0.0.1, 10.0.0.2, invalid");
+        assert_eq!(proxies.ips.len(), 2);
+
+        let req = request("10.0.0.1:1000", Some("9.9.9.9, 3.3.3.3, 10.0.0.2"));
+        assert_eq!(client_ip(&req, &proxies), "3.3.3.3".parse().ok());
+
+        let req = request("10.0.0.1:1000", None);
+        assert_eq!(client_ip(&req, &proxies), "10.0.0.1".parse().ok());
+
+        let req = request("10.0.0.1:1000", Some("3.3.3.3, garbage, 10.0.0.2"));
+        assert_eq!(client_ip(&req, &proxies), "10.0.0.2".parse().ok());
+    }
+
+    #[test]
+    fn client_ip_behind_platform_proxy() {
+        let proxies = TrustedProxies::parse("peer");
+        assert!(proxies.peer);
+
+        let req = request("172.16.0.9:1000", Some("9.9.9.9, 3.3.3.3"));
+        assert_eq!(client_ip(&req, &proxies), "3.3.3.3".parse().ok());
+
+        let req = request("172.16.0.9:1000", None);
+        assert_eq!(client_ip(&req, &proxies), "172.16.0.9".parse().ok());
+    }
+}
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use service::{
+    accounting_period::{
+        AccountingPeriodListQueryParams, AccountingPeriodService, ClosePeriodParams,
+        ReopenPeriodParams,
+    },
+    workshop::WorkshopRole,
+};
+
+use crate::{
+    error::{AppError, AuthError},
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/accounting_period", post(close).get(find))
+        .route("/accounting_period/:id", get(find_by_id))
+        .route("/accounting_period/:id/reopen", post(reopen))
+}
+
+// 按月结账, 结账后该月的单据不能再修改
+async fn close(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop {
+        workshop_id,
+        user_id,
+        ..
+    }: CurrentWorkshop,
+    Json(params): Json<ClosePeriodParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AccountingPeriodService::close(&db, workshop_id, user_id, params).await?,
+    ))
+}
+
+// 只有工坊主可以反结账
+async fn reopen(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop {
+        workshop_id,
+        user_id,
+        role,
+    }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<ReopenPeriodParams>,
+) -> Result<impl IntoResponse, AppError> {
+    if role != WorkshopRole::Owner {
+        return Err(AuthError::Forbidden.into());
+    }
+
+    Ok(Json(
+        AccountingPeriodService::reopen(&db, workshop_id, user_id, id, params).await?,
+    ))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AccountingPeriodService::find_by_id(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(AccountingPeriodListQueryParams { list_query }): ValidatedListQuery<
+        AccountingPeriodListQueryParams,
+    >,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AccountingPeriodService::find_by_workshop_id(&db, workshop_id, list_query).await?,
+    ))
+}
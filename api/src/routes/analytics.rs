@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use service::analytics::{
+    AnalyticsService, DefectQueryParams, ForecastQueryParams, ProductivityQueryParams,
+    ProfitQueryParams,
+};
+
+use crate::{error::AppError, extract::ValidatedQuery, jwt::CurrentWorkshop, state::AppState};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/analytics/productivity/staff", get(staff_ranking))
+        .route("/analytics/productivity/clothing", get(clothing_ranking))
+        .route("/analytics/productivity/trend", get(trend))
+        .route("/analytics/forecast", get(forecast))
+        .route("/analytics/profit", get(profit))
+        .route("/analytics/defect", get(defects))
+        .route("/analytics/quality", get(quality))
+}
+
+// 员工产量排行及与平均水平对比
+async fn staff_ranking(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(params): Query<ProductivityQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AnalyticsService::staff_ranking(&db, workshop_id, params).await?,
+    ))
+}
+
+// 每件服装的员工产量排行
+async fn clothing_ranking(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(params): Query<ProductivityQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AnalyticsService::clothing_ranking(&db, workshop_id, params).await?,
+    ))
+}
+
+// 按日/周/月的产量趋势
+async fn trend(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(params): Query<ProductivityQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AnalyticsService::trend(&db, workshop_id, params).await?,
+    ))
+}
+
+// 按月的需求预测, 返回历史数据和预测数据
+async fn forecast(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedQuery(params): ValidatedQuery<ForecastQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AnalyticsService::forecast(&db, workshop_id, params).await?,
+    ))
+}
+
+// 按日/周/月的利润, 成本包括人工和进货
+async fn profit(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(params): Query<ProfitQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AnalyticsService::profit(&db, workshop_id, params).await?,
+    ))
+}
+
+// 按员工和服装统计退货次品率
+async fn defects(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(params): Query<DefectQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AnalyticsService::defects(&db, workshop_id, params).await?,
+    ))
+}
+
+// 按员工和服装统计质检次品率, 以及次品原因分布
+async fn quality(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(params): Query<DefectQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AnalyticsService::quality(&db, workshop_id, params).await?,
+    ))
+}
@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use service::attendance::{
+    AttendanceListQueryParams, AttendanceService, AttendanceSheetQueryParams,
+    CreateAttendanceParams, UpdateAttendanceParams,
+};
+
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/attendance", post(create).get(find))
+        .route("/attendance/sheet", get(sheet))
+        .route(
+            "/attendance/:id",
+            get(find_by_id).put(update).delete(delete),
+        )
+        .route("/attendance/:id/settle", post(settle))
+}
+
+async fn create(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateAttendanceParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AttendanceService::create(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn delete(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(AttendanceService::delete(&db, workshop_id, id).await?))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<UpdateAttendanceParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AttendanceService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn settle(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(AttendanceService::settle(&db, workshop_id, id).await?))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AttendanceService::find_by_id(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(AttendanceListQueryParams {
+        list_query,
+        staff_ids,
+    }): ValidatedListQuery<AttendanceListQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AttendanceService::find_by_workshop_id(&db, workshop_id, list_query, staff_ids).await?,
+    ))
+}
+
+// 月度考勤表
+async fn sheet(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(AttendanceSheetQueryParams {
+        year,
+        month,
+        staff_ids,
+    }): Query<AttendanceSheetQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        AttendanceService::monthly_sheet(&db, workshop_id, year, month, staff_ids).await?,
+    ))
+}
@@ -1,15 +1,22 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Path, State},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
 use service::{
-    boss::{BossService, CreateBossParams, UpdateBossParams},
-    ListQueryParams,
+    boss::{BossListQueryParams, BossService, CreateBossParams, UpdateBossParams},
+    clothing::ClothingService,
 };
 
-use crate::{error::AppError, jwt::Claims, state::AppState};
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    routes::clothing::images,
+    state::AppState,
+    storage,
+};
 
 pub fn route() -> Router<AppState> {
     Router::new()
@@ -19,44 +26,55 @@ pub fn route() -> Router<AppState> {
 
 async fn create(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Json(params): Json<CreateBossParams>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateBossParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(BossService::create(&db, user_id, params).await?))
+    Ok(Json(BossService::create(&db, workshop_id, params).await?))
 }
 
+// 删除老板时会级联删除服装, 同时清理服装图片
 async fn delete(
-    State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    State(AppState { db, storage, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(BossService::delete(&db, user_id, id).await?))
+    let clothings = ClothingService::find_by_boss_id(&db, workshop_id, id).await?;
+    let boss = BossService::delete(&db, workshop_id, id).await?;
+
+    let urls = clothings
+        .into_iter()
+        .flat_map(|clothing| images(clothing.image, clothing.thumbnail));
+    storage::delete_all(storage.as_ref(), urls).await;
+
+    Ok(Json(boss))
 }
 
 async fn update(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
     Json(params): Json<UpdateBossParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(BossService::update(&db, user_id, id, params).await?))
+    Ok(Json(
+        BossService::update(&db, workshop_id, id, params).await?,
+    ))
 }
 
 async fn find_by_id(
     State(AppState { db, .. }): State<AppState>,
     Path(id): Path<i32>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(BossService::find_by_id(&db, user_id, id).await?))
+    Ok(Json(BossService::find_by_id(&db, workshop_id, id).await?))
 }
 
-// 查找当前用户的所有老板列表
+// 查找当前工坊的所有老板列表
 async fn find(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Query(params): Query<ListQueryParams>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(BossListQueryParams { list_query }): ValidatedListQuery<BossListQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        BossService::find_by_user_id(&db, user_id, params).await?,
+        BossService::find_by_workshop_id(&db, workshop_id, list_query).await?,
     ))
 }
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Multipart, Path, State},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
@@ -8,59 +8,123 @@ use service::clothing::{
     ClothingListQueryParams, ClothingService, CreateClothingParams, UpdateClothingParams,
 };
 
-use crate::{error::AppError, jwt::Claims, state::AppState};
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+    storage,
+};
+
+const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024;
 
 pub fn route() -> Router<AppState> {
     Router::new()
         .route("/clothing", post(create).get(find))
         .route("/clothing/:id", get(find_by_id).put(update).delete(delete))
+        .route(
+            "/clothing/:id/image",
+            post(upload_image).layer(DefaultBodyLimit::max(MAX_IMAGE_SIZE)),
+        )
+}
+
+// 服装的图片和缩略图地址
+pub(crate) fn images(
+    image: Option<String>,
+    thumbnail: Option<String>,
+) -> impl Iterator<Item = String> {
+    image.into_iter().chain(thumbnail)
 }
 
 async fn create(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Json(params): Json<CreateClothingParams>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateClothingParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ClothingService::create(&db, user_id, params).await?))
+    Ok(Json(
+        ClothingService::create(&db, workshop_id, params).await?,
+    ))
 }
 
 async fn delete(
-    State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    State(AppState { db, storage, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ClothingService::delete(&db, user_id, id).await?))
+    let clothing = ClothingService::delete(&db, workshop_id, id).await?;
+
+    storage::delete_all(
+        storage.as_ref(),
+        images(clothing.image.clone(), clothing.thumbnail.clone()),
+    )
+    .await;
+
+    Ok(Json(clothing))
 }
 
 async fn update(
-    State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    State(AppState { db, storage, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
     Json(params): Json<UpdateClothingParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(
-        ClothingService::update(&db, user_id, id, params).await?,
-    ))
+    let old = ClothingService::find_by_id(&db, workshop_id, id).await?;
+    let clothing = ClothingService::update(&db, workshop_id, id, params).await?;
+
+    // 只有传了新图片替换旧图片时才删除旧文件
+    if old.image != clothing.image {
+        storage::delete_all(storage.as_ref(), images(old.image, old.thumbnail)).await;
+    }
+
+    Ok(Json(clothing))
+}
+
+// 上传服装图片, 同时生成缩略图, 替换掉旧的图片
+async fn upload_image(
+    State(AppState { db, storage, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, AppError> {
+    let old = ClothingService::find_by_id(&db, workshop_id, id).await?;
+
+    let field = multipart
+        .next_field()
+        .await?
+        .ok_or(anyhow::anyhow!("缺少图片"))?;
+    let data = field.bytes().await?;
+
+    let (image, thumbnail) =
+        storage::save_image(storage.as_ref(), &format!("clothing/{id}"), data.to_vec()).await?;
+
+    let clothing =
+        ClothingService::set_image(&db, workshop_id, id, Some(image), Some(thumbnail)).await?;
+
+    storage::delete_all(storage.as_ref(), images(old.image, old.thumbnail)).await;
+
+    Ok(Json(clothing))
 }
 
 async fn find_by_id(
     State(AppState { db, .. }): State<AppState>,
     Path(id): Path<i32>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ClothingService::find_by_id(&db, user_id, id).await?))
+    Ok(Json(
+        ClothingService::find_by_id(&db, workshop_id, id).await?,
+    ))
 }
 
-// 查找当前用户的所有老板列表
+// 查找当前工坊的所有老板列表
 async fn find(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Query(ClothingListQueryParams {
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(ClothingListQueryParams {
         boss_ids,
         list_query,
-    }): Query<ClothingListQueryParams>,
+    }): ValidatedListQuery<ClothingListQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ClothingService::find_by_user_id(&db, user_id, list_query, boss_ids).await?,
+        ClothingService::find_by_workshop_id(&db, workshop_id, list_query, boss_ids).await?,
     ))
 }
@@ -0,0 +1,24 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use service::dashboard::{DashboardQueryParams, DashboardService};
+
+use crate::{error::AppError, jwt::CurrentWorkshop, state::AppState};
+
+pub fn route() -> Router<AppState> {
+    Router::new().route("/dashboard", get(summary))
+}
+
+// 首页汇总数据
+async fn summary(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(params): Query<DashboardQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        DashboardService::summary(&db, workshop_id, params).await?,
+    ))
+}
@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use service::inspection::{
+    CreateInspectionParams, InspectionListQueryParams, InspectionService, UpdateInspectionParams,
+};
+
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/inspection", post(create).get(find))
+        .route(
+            "/inspection/:id",
+            get(find_by_id).put(update).delete(delete),
+        )
+}
+
+// 当前用户作为质检员
+async fn create(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop {
+        workshop_id,
+        user_id,
+        ..
+    }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateInspectionParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        InspectionService::create(&db, workshop_id, user_id, params).await?,
+    ))
+}
+
+async fn delete(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(InspectionService::delete(&db, workshop_id, id).await?))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<UpdateInspectionParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        InspectionService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        InspectionService::find_by_id(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(InspectionListQueryParams {
+        list_query,
+        production_ids,
+    }): ValidatedListQuery<InspectionListQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        InspectionService::find_by_workshop_id(&db, workshop_id, list_query, production_ids)
+            .await?,
+    ))
+}
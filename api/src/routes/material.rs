@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use service::material::{
+    CreateMaterialParams, MaterialConsumptionListQueryParams, MaterialListQueryParams,
+    MaterialService, RequirementParams, SetBomParams, UpdateMaterialParams,
+};
+
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/material", post(create).get(find))
+        .route("/material/:id", get(find_by_id).put(update).delete(delete))
+        .route("/material/:id/consumption", get(consumption))
+        .route("/material/requirement", post(requirement))
+        .route("/clothing/:id/material", get(find_bom).put(set_bom))
+}
+
+async fn create(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateMaterialParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        MaterialService::create(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn delete(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(MaterialService::delete(&db, workshop_id, id).await?))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    Json(params): Json<UpdateMaterialParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        MaterialService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        MaterialService::find_by_id(&db, workshop_id, id).await?,
+    ))
+}
+
+// 查找当前工坊的所有物料列表
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(MaterialListQueryParams { list_query }): ValidatedListQuery<
+        MaterialListQueryParams,
+    >,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        MaterialService::find_by_workshop_id(&db, workshop_id, list_query).await?,
+    ))
+}
+
+async fn consumption(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedListQuery(MaterialConsumptionListQueryParams { list_query }): ValidatedListQuery<
+        MaterialConsumptionListQueryParams,
+    >,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        MaterialService::find_consumption(&db, workshop_id, id, list_query).await?,
+    ))
+}
+
+// 开工前检查计划生产的缺料情况
+async fn requirement(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<RequirementParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        MaterialService::requirement(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn find_bom(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(MaterialService::find_bom(&db, workshop_id, id).await?))
+}
+
+async fn set_bom(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<SetBomParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        MaterialService::set_bom(&db, workshop_id, id, params).await?,
+    ))
+}
@@ -1,7 +1,20 @@
+pub mod accounting_period;
+pub mod analytics;
+pub mod attendance;
 pub mod boss;
 pub mod clothing;
+pub mod dashboard;
+pub mod inspection;
+pub mod material;
+pub mod payroll;
+pub mod process_step;
 pub mod procurement;
 pub mod production;
+pub mod sales_order;
+pub mod search;
 pub mod shipment;
 pub mod staff;
+pub mod staff_adjustment;
+pub mod supplier;
 pub mod user;
+pub mod workshop;
@@ -0,0 +1,24 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use service::payroll::{PayrollQueryParams, PayrollService};
+
+use crate::{error::AppError, jwt::CurrentWorkshop, state::AppState};
+
+pub fn route() -> Router<AppState> {
+    Router::new().route("/payroll", get(summary))
+}
+
+// 员工工资汇总, 计件工资 + 计日工资
+async fn summary(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(params): Query<PayrollQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        PayrollService::summary(&db, workshop_id, params).await?,
+    ))
+}
@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use service::process_step::{
+    CreateProcessStepParams, ProcessStepListQueryParams, ProcessStepService, ProgressQueryParams,
+    UpdateProcessStepParams,
+};
+
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/process_step", post(create).get(find))
+        .route("/process_step/progress", get(progress))
+        .route(
+            "/process_step/:id",
+            get(find_by_id).put(update).delete(delete),
+        )
+}
+
+async fn create(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateProcessStepParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ProcessStepService::create(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn delete(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ProcessStepService::delete(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<UpdateProcessStepParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ProcessStepService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ProcessStepService::find_by_id(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(ProcessStepListQueryParams {
+        clothing_ids,
+        list_query,
+    }): ValidatedListQuery<ProcessStepListQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ProcessStepService::find_by_workshop_id(&db, workshop_id, list_query, clothing_ids).await?,
+    ))
+}
+
+// 在制品报表
+async fn progress(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(ProgressQueryParams { clothing_ids }): Query<ProgressQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ProcessStepService::progress(&db, workshop_id, clothing_ids).await?,
+    ))
+}
@@ -5,11 +5,19 @@ use axum::{
     Json, Router,
 };
 use service::{
-    procurement::{CreateProcurementParams, ProcurementService, UpdateProcurementParams},
-    ListQueryParams,
+    expand::ExpandQueryParams,
+    procurement::{
+        CreateProcurementParams, ProcurementListQueryParams, ProcurementService,
+        UpdateProcurementParams,
+    },
 };
 
-use crate::{error::AppError, jwt::Claims, state::AppState};
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
 
 pub fn route() -> Router<AppState> {
     Router::new()
@@ -22,49 +30,57 @@ pub fn route() -> Router<AppState> {
 
 async fn create(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Json(params): Json<CreateProcurementParams>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateProcurementParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ProcurementService::create(&db, user_id, params).await?,
+        ProcurementService::create(&db, workshop_id, params).await?,
     ))
 }
 
 async fn delete(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ProcurementService::delete(&db, user_id, id).await?))
+    Ok(Json(
+        ProcurementService::delete(&db, workshop_id, id).await?,
+    ))
 }
 
 async fn update(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
     Json(params): Json<UpdateProcurementParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ProcurementService::update(&db, user_id, id, params).await?,
+        ProcurementService::update(&db, workshop_id, id, params).await?,
     ))
 }
 
 async fn find_by_id(
     State(AppState { db, .. }): State<AppState>,
     Path(id): Path<i32>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ProcurementService::find_by_id(&db, user_id, id).await?,
+        ProcurementService::find_by_id(&db, workshop_id, id, expand).await?,
     ))
 }
 
 async fn find(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Query(params): Query<ListQueryParams>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(ProcurementListQueryParams {
+        list_query,
+        supplier_ids,
+    }): ValidatedListQuery<ProcurementListQueryParams>,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ProcurementService::find_by_user_id(&db, user_id, params).await?,
+        ProcurementService::find_by_workshop_id(&db, workshop_id, list_query, supplier_ids, expand)
+            .await?,
     ))
 }
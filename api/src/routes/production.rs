@@ -4,11 +4,20 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use service::production::{
-    CreateProductionParams, ProductionListQueryParams, ProductionService, UpdateProductionParams,
+use service::{
+    expand::ExpandQueryParams,
+    production::{
+        CreateProductionParams, ProductionListQueryParams, ProductionService,
+        SettleProductionParams, UnsettleProductionParams, UpdateProductionParams,
+    },
 };
 
-use crate::{error::AppError, jwt::Claims, state::AppState};
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
 
 pub fn route() -> Router<AppState> {
     Router::new()
@@ -18,60 +27,111 @@ pub fn route() -> Router<AppState> {
             get(find_by_id).put(update).delete(delete),
         )
         .route("/production/:id/settle", post(settle))
+        .route("/production/:id/unsettle", post(unsettle))
+        .route("/production/:id/settlement", get(find_settlements))
 }
 
 async fn create(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Json(params): Json<CreateProductionParams>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateProductionParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ProductionService::create(&db, user_id, params).await?))
+    Ok(Json(
+        ProductionService::create(&db, workshop_id, params).await?,
+    ))
 }
 
 async fn delete(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ProductionService::delete(&db, user_id, id).await?))
+    Ok(Json(ProductionService::delete(&db, workshop_id, id).await?))
 }
 
 async fn update(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
     Json(params): Json<UpdateProductionParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ProductionService::update(&db, user_id, id, params).await?,
+        ProductionService::update(&db, workshop_id, id, params).await?,
     ))
 }
 
+// 不指定明细时结算全部未结算的明细
 async fn settle(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop {
+        workshop_id,
+        user_id,
+        ..
+    }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    params: Option<Json<SettleProductionParams>>,
+) -> Result<impl IntoResponse, AppError> {
+    let params = params.map(|Json(params)| params).unwrap_or_default();
+    Ok(Json(
+        ProductionService::settle(&db, workshop_id, user_id, id, params).await?,
+    ))
+}
+
+async fn unsettle(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop {
+        workshop_id,
+        user_id,
+        ..
+    }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<UnsettleProductionParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ProductionService::unsettle(&db, workshop_id, user_id, id, params).await?,
+    ))
+}
+
+async fn find_settlements(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ProductionService::settle(&db, user_id, id).await?))
+    Ok(Json(
+        ProductionService::find_settlements(&db, workshop_id, id).await?,
+    ))
 }
 
 async fn find_by_id(
     State(AppState { db, .. }): State<AppState>,
     Path(id): Path<i32>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ProductionService::find_by_id(&db, user_id, id).await?))
+    Ok(Json(
+        ProductionService::find_by_id(&db, workshop_id, id, expand).await?,
+    ))
 }
 
 async fn find(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Query(ProductionListQueryParams {
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(ProductionListQueryParams {
         list_query,
         staff_ids,
-    }): Query<ProductionListQueryParams>,
+        kind,
+    }): ValidatedListQuery<ProductionListQueryParams>,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ProductionService::find_by_user_id(&db, user_id, list_query, staff_ids).await?,
+        ProductionService::find_by_workshop_id(
+            &db,
+            workshop_id,
+            list_query,
+            staff_ids,
+            kind,
+            expand,
+        )
+        .await?,
     ))
 }
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use service::{
+    expand::ExpandQueryParams,
+    sales_order::{
+        CreateSalesOrderParams, SalesOrderListQueryParams, SalesOrderService,
+        UpdateSalesOrderParams,
+    },
+};
+
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/sales_order", post(create).get(find))
+        .route(
+            "/sales_order/:id",
+            get(find_by_id).put(update).delete(delete),
+        )
+}
+
+async fn create(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateSalesOrderParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SalesOrderService::create(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn delete(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(SalesOrderService::delete(&db, workshop_id, id).await?))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    Json(params): Json<UpdateSalesOrderParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SalesOrderService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SalesOrderService::find_by_id(&db, workshop_id, id, expand).await?,
+    ))
+}
+
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(SalesOrderListQueryParams {
+        list_query,
+        boss_ids,
+    }): ValidatedListQuery<SalesOrderListQueryParams>,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SalesOrderService::find_by_workshop_id(&db, workshop_id, list_query, boss_ids, expand)
+            .await?,
+    ))
+}
@@ -0,0 +1,19 @@
+use axum::{extract::State, response::IntoResponse, routing::get, Json, Router};
+use service::search::{SearchQueryParams, SearchService};
+
+use crate::{error::AppError, extract::ValidatedQuery, jwt::CurrentWorkshop, state::AppState};
+
+pub fn route() -> Router<AppState> {
+    Router::new().route("/search", get(search))
+}
+
+// 全局搜索老板, 员工, 服装和单据备注
+async fn search(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedQuery(SearchQueryParams { q, limit }): ValidatedQuery<SearchQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SearchService::search(&db, workshop_id, &q, limit.unwrap_or(10)).await?,
+    ))
+}
@@ -1,63 +1,241 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
     response::IntoResponse,
-    routing::{get, post},
+    routing::{get, post, put},
     Json, Router,
 };
 use service::{
-    shipment::{CreateShipmentParams, ShipmentService, UpdateShipmentParams},
-    ListQueryParams,
+    expand::ExpandQueryParams,
+    shipment::{
+        AllocateShipmentItemParams, ConfirmDeliveryParams, CreateShipmentParams,
+        ShipmentListQueryParams, ShipmentService, UpdateShipmentParams, UpdateShipmentStatusParams,
+    },
+    shipment_return::{
+        CreateShipmentReturnParams, ShipmentReturnListQueryParams, ShipmentReturnService,
+        UpdateShipmentReturnParams,
+    },
 };
 
-use crate::{error::AppError, jwt::Claims, state::AppState};
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+    storage,
+};
+
+const MAX_SIGNATURE_SIZE: usize = 2 * 1024 * 1024;
 
 pub fn route() -> Router<AppState> {
     Router::new()
         .route("/shipment", post(create).get(find))
         .route("/shipment/:id", get(find_by_id).put(update).delete(delete))
+        .route("/shipment/:id/item/:item_id", put(allocate))
+        .route("/shipment/:id/status", put(set_status))
+        .route(
+            "/shipment/:id/delivery",
+            post(confirm_delivery).layer(DefaultBodyLimit::max(MAX_SIGNATURE_SIZE)),
+        )
+        .route("/shipment_return", post(create_return).get(find_returns))
+        .route(
+            "/shipment_return/:id",
+            get(find_return_by_id)
+                .put(update_return)
+                .delete(delete_return),
+        )
 }
 
 async fn create(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Json(params): Json<CreateShipmentParams>,
+    CurrentWorkshop {
+        user_id,
+        workshop_id,
+        ..
+    }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateShipmentParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ShipmentService::create(&db, user_id, params).await?))
+    Ok(Json(
+        ShipmentService::create(&db, workshop_id, user_id, params).await?,
+    ))
 }
 
 async fn delete(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ShipmentService::delete(&db, user_id, id).await?))
+    Ok(Json(ShipmentService::delete(&db, workshop_id, id).await?))
 }
 
 async fn update(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
     Json(params): Json<UpdateShipmentParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ShipmentService::update(&db, user_id, id, params).await?,
+        ShipmentService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+// 将出货明细分配到订单明细
+async fn allocate(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path((id, item_id)): Path<(i32, i32)>,
+    Json(params): Json<AllocateShipmentItemParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ShipmentService::allocate(&db, workshop_id, id, item_id, params).await?,
+    ))
+}
+
+// 打包, 发货, 退回等状态变更
+async fn set_status(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop {
+        user_id,
+        workshop_id,
+        ..
+    }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    Json(params): Json<UpdateShipmentStatusParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ShipmentService::set_status(&db, workshop_id, user_id, id, params).await?,
     ))
 }
 
+// 签收确认, 表单包含签收人 receiver 和可选的签名图片 signature
+async fn confirm_delivery(
+    State(AppState { db, storage, .. }): State<AppState>,
+    CurrentWorkshop {
+        user_id,
+        workshop_id,
+        ..
+    }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, AppError> {
+    // 先确认出货单属于当前工坊, 再保存签名图片
+    ShipmentService::find_model(&db, workshop_id, id).await?;
+
+    let mut receiver = None;
+    let mut signature = None;
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("receiver") => receiver = Some(field.text().await?),
+            Some("signature") => signature = Some(field.bytes().await?),
+            _ => {}
+        }
+    }
+    let receiver = receiver.ok_or(anyhow::anyhow!("缺少签收人"))?;
+
+    let signature = match signature {
+        Some(data) => Some(
+            storage::save_original(storage.as_ref(), &format!("shipment/{id}"), data.to_vec())
+                .await?,
+        ),
+        None => None,
+    };
+
+    let params = ConfirmDeliveryParams {
+        receiver,
+        signature: signature.clone(),
+    };
+    match ShipmentService::confirm_delivery(&db, workshop_id, user_id, id, params).await {
+        Ok(shipment) => Ok(Json(shipment)),
+        Err(e) => {
+            storage::delete_all(storage.as_ref(), signature).await;
+            Err(e.into())
+        }
+    }
+}
+
 async fn find_by_id(
     State(AppState { db, .. }): State<AppState>,
     Path(id): Path<i32>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(ShipmentService::find_by_id(&db, user_id, id).await?))
+    Ok(Json(
+        ShipmentService::find_by_id(&db, workshop_id, id, expand).await?,
+    ))
 }
 
 async fn find(
     State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Query(params): Query<ListQueryParams>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(ShipmentListQueryParams { list_query }): ValidatedListQuery<
+        ShipmentListQueryParams,
+    >,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ShipmentService::find_by_workshop_id(&db, workshop_id, list_query, expand).await?,
+    ))
+}
+
+// 登记老板退回的次品, 可以指定责任员工扣款
+async fn create_return(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateShipmentReturnParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ShipmentReturnService::create(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn delete_return(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ShipmentReturnService::delete(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn update_return(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    Json(params): Json<UpdateShipmentReturnParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ShipmentReturnService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn find_return_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        ShipmentReturnService::find_by_id(&db, workshop_id, id, expand).await?,
+    ))
+}
+
+async fn find_returns(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(ShipmentReturnListQueryParams {
+        list_query,
+        shipment_ids,
+    }): ValidatedListQuery<ShipmentReturnListQueryParams>,
+    Query(ExpandQueryParams { expand }): Query<ExpandQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        ShipmentService::find_by_user_id(&db, user_id, params).await?,
+        ShipmentReturnService::find_by_workshop_id(
+            &db,
+            workshop_id,
+            list_query,
+            shipment_ids,
+            expand,
+        )
+        .await?,
     ))
 }
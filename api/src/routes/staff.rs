@@ -1,62 +1,87 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Path, State},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
 use service::{
-    staff::{CreateStaffParams, StaffService, UpdateStaffParams},
-    ListQueryParams,
+    payroll::PayrollService,
+    staff::{CreateStaffParams, StaffListQueryParams, StaffService, UpdateStaffParams},
 };
 
-use crate::{error::AppError, jwt::Claims, state::AppState};
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
 
 pub fn route() -> Router<AppState> {
     Router::new()
         .route("/staff", post(create).get(find))
         .route("/staff/:id", get(find_by_id).put(update).delete(delete))
+        .route("/staff/:id/balance", get(balance))
 }
 
 async fn create(
-    State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Json(params): Json<CreateStaffParams>,
+    State(AppState { db, cipher, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateStaffParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(StaffService::create(&db, user_id, params).await?))
+    Ok(Json(
+        StaffService::create(&db, &cipher, workshop_id, params).await?,
+    ))
 }
 
 async fn delete(
-    State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    State(AppState { db, cipher, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(StaffService::delete(&db, user_id, id).await?))
+    Ok(Json(
+        StaffService::delete(&db, &cipher, workshop_id, id).await?,
+    ))
 }
 
 async fn update(
-    State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
+    State(AppState { db, cipher, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
     Path(id): Path<i32>,
     Json(params): Json<UpdateStaffParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(StaffService::update(&db, user_id, id, params).await?))
+    Ok(Json(
+        StaffService::update(&db, &cipher, workshop_id, id, params).await?,
+    ))
 }
 
 async fn find_by_id(
+    State(AppState { db, cipher, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        StaffService::find_by_id(&db, &cipher, workshop_id, id).await?,
+    ))
+}
+
+// 员工未结算余额, 扣除预支等
+async fn balance(
     State(AppState { db, .. }): State<AppState>,
     Path(id): Path<i32>,
-    Claims { user_id, .. }: Claims,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(StaffService::find_by_id(&db, user_id, id).await?))
+    Ok(Json(PayrollService::balance(&db, workshop_id, id).await?))
 }
 
-// 查找当前用户的所有老板列表
+// 查找当前工坊的所有老板列表
 async fn find(
-    State(AppState { db, .. }): State<AppState>,
-    Claims { user_id, .. }: Claims,
-    Query(params): Query<ListQueryParams>,
+    State(AppState { db, cipher, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(StaffListQueryParams { list_query, status }): ValidatedListQuery<
+        StaffListQueryParams,
+    >,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(
-        StaffService::find_by_user_id(&db, user_id, params).await?,
+        StaffService::find_by_workshop_id(&db, &cipher, workshop_id, list_query, status).await?,
     ))
 }
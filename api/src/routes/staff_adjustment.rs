@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use service::staff_adjustment::{
+    CreateStaffAdjustmentParams, StaffAdjustmentListQueryParams, StaffAdjustmentService,
+    UpdateStaffAdjustmentParams,
+};
+
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/staff_adjustment", post(create).get(find))
+        .route(
+            "/staff_adjustment/:id",
+            get(find_by_id).put(update).delete(delete),
+        )
+        .route("/staff_adjustment/:id/settle", post(settle))
+}
+
+async fn create(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateStaffAdjustmentParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        StaffAdjustmentService::create(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn delete(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        StaffAdjustmentService::delete(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<UpdateStaffAdjustmentParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        StaffAdjustmentService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn settle(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        StaffAdjustmentService::settle(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        StaffAdjustmentService::find_by_id(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(StaffAdjustmentListQueryParams {
+        list_query,
+        staff_ids,
+        kind,
+    }): ValidatedListQuery<StaffAdjustmentListQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        StaffAdjustmentService::find_by_workshop_id(&db, workshop_id, list_query, staff_ids, kind)
+            .await?,
+    ))
+}
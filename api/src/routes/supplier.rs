@@ -0,0 +1,161 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use service::{
+    supplier::{
+        CreateSupplierParams, SupplierListQueryParams, SupplierService, UpdateSupplierParams,
+    },
+    supplier_payment::{
+        CreateSupplierPaymentParams, SupplierPaymentListQueryParams, SupplierPaymentService,
+        UpdateSupplierPaymentParams,
+    },
+};
+
+use crate::{
+    error::AppError,
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::CurrentWorkshop,
+    state::AppState,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/supplier", post(create).get(find))
+        .route("/supplier/:id", get(find_by_id).put(update).delete(delete))
+        .route("/supplier/:id/history", get(history))
+        .route("/supplier/payable", get(payables))
+        .route("/supplier_payment", post(create_payment).get(find_payments))
+        .route(
+            "/supplier_payment/:id",
+            get(find_payment_by_id)
+                .put(update_payment)
+                .delete(delete_payment),
+        )
+}
+
+async fn create(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateSupplierParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierService::create(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn delete(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(SupplierService::delete(&db, workshop_id, id).await?))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<UpdateSupplierParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierService::find_by_id(&db, workshop_id, id).await?,
+    ))
+}
+
+// 查找当前工坊的所有供应商列表
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(SupplierListQueryParams { list_query }): ValidatedListQuery<
+        SupplierListQueryParams,
+    >,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierService::find_by_workshop_id(&db, workshop_id, list_query).await?,
+    ))
+}
+
+// 供应商的进货汇总
+async fn history(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(SupplierService::history(&db, workshop_id, id).await?))
+}
+
+// 各供应商的应付账款
+async fn payables(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(SupplierService::payables(&db, workshop_id).await?))
+}
+
+async fn create_payment(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedJson(params): ValidatedJson<CreateSupplierPaymentParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierPaymentService::create(&db, workshop_id, params).await?,
+    ))
+}
+
+async fn delete_payment(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierPaymentService::delete(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn update_payment(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    Path(id): Path<i32>,
+    ValidatedJson(params): ValidatedJson<UpdateSupplierPaymentParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierPaymentService::update(&db, workshop_id, id, params).await?,
+    ))
+}
+
+async fn find_payment_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Path(id): Path<i32>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierPaymentService::find_by_id(&db, workshop_id, id).await?,
+    ))
+}
+
+async fn find_payments(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop { workshop_id, .. }: CurrentWorkshop,
+    ValidatedListQuery(SupplierPaymentListQueryParams {
+        list_query,
+        supplier_ids,
+    }): ValidatedListQuery<SupplierPaymentListQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        SupplierPaymentService::find_by_workshop_id(&db, workshop_id, list_query, supplier_ids)
+            .await?,
+    ))
+}
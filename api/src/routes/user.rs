@@ -1,14 +1,48 @@
-use crate::{error::AppError, jwt::Claims, state::AppState};
-use axum::{extract::State, response::IntoResponse, routing::post, Json, Router};
+use crate::{
+    error::{AppError, AuthError},
+    extract::{ValidatedJson, ValidatedListQuery},
+    jwt::Claims,
+    rate_limit::{ClientIp, LoginLimitLayer},
+    state::AppState,
+};
+use axum::{
+    extract::State,
+    response::IntoResponse,
+    routing::{get, post, put},
+    Extension, Json, Router,
+};
 use bcrypt::{verify, DEFAULT_COST};
+use chrono::{Duration, Utc};
+use sea_orm::DbErr;
 use serde::Deserialize;
 use serde_json::json;
-use service::user::CreateUserParams;
+use service::{
+    login_event::{CreateLoginEventParams, LoginEventListQueryParams, LoginEventService},
+    user::{
+        ChangePasswordParams, CreateUserParams, ResetCodeParams, ResetPasswordParams,
+        UpdateUserParams, UserInfo, UserService,
+    },
+};
+use std::sync::LazyLock;
 
-pub fn route() -> Router<AppState> {
+// 重置码有效期
+const RESET_CODE_TTL_MINUTES: i64 = 30;
+
+const INVALID_RESET_CODE: &str = "重置码错误或已失效";
+
+// 没有可用的重置码时用来校验的哈希, 和真实的重置码耗时相同
+static UNUSED_CODE_HASH: LazyLock<String> =
+    LazyLock::new(|| bcrypt::hash(uuid::Uuid::new_v4().to_string(), DEFAULT_COST).unwrap());
+
+// 登录和重置密码共用失败计数, 按账号和 IP 限流
+pub fn route(limit: LoginLimitLayer) -> Router<AppState> {
     Router::new()
-        .route("/user/login", post(login))
-        .route("/user", post(create).delete(logoff))
+        .route("/user/login", post(login).layer(limit.clone()))
+        .route("/user/login_event", get(login_events))
+        .route("/user", post(create).put(update).delete(logoff))
+        .route("/user/password", put(change_password))
+        .route("/user/password/reset", post(reset_password).layer(limit))
+        .route("/user/reset_code", post(reset_code))
 }
 
 #[derive(Debug, Deserialize)]
@@ -19,54 +53,165 @@ struct LoginParams {
 
 async fn login(
     State(state): State<AppState>,
+    Extension(ClientIp(ip)): Extension<ClientIp>,
     Json(params): Json<LoginParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user =
-        service::user::UserService::find_by_phone_number(&state.db, params.phone_number).await?;
+    let user = UserService::find_by_phone_number(&state.db, params.phone_number.clone()).await;
 
-    if verify(params.password, &user.password)? {
-        return Err(anyhow::anyhow!("密码错误").into());
-    }
+    let (user, reason) = match user {
+        Ok(user) if verify(&params.password, &user.password)? => (Some(user), None),
+        Ok(user) => (Some(user), Some("密码错误")),
+        Err(DbErr::RecordNotFound(_)) => (None, Some("用户不存在")),
+        Err(e) => return Err(e.into()),
+    };
+
+    LoginEventService::create(
+        &state.db,
+        CreateLoginEventParams {
+            phone_number: params.phone_number,
+            user_id: user.as_ref().map(|u| u.id),
+            ip,
+            success: reason.is_none(),
+            reason: reason.map(String::from),
+        },
+    )
+    .await?;
+
+    let user = match (user, reason) {
+        (Some(user), None) => user,
+        // 不区分用户不存在和密码错误, 避免泄露已注册的手机号
+        _ => return Err(anyhow::anyhow!("手机号或密码错误").into()),
+    };
 
     let claims = Claims::new(user.id);
 
     Ok(Json(json!({
         "token": claims.encode(&state.jwt_secret)?,
-        "user": {
-            "id": user.id,
-            "username": user.username,
-            "phone_number": user.phone_number,
-            "create_at": user.create_at,
-        },
+        "user": UserInfo::from(user),
     })))
 }
 
+// 当前账号的登录记录
+async fn login_events(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    ValidatedListQuery(LoginEventListQueryParams { list_query }): ValidatedListQuery<
+        LoginEventListQueryParams,
+    >,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        LoginEventService::find_by_user_id(&db, user_id, list_query).await?,
+    ))
+}
+
 async fn create(
     State(AppState { db, .. }): State<AppState>,
-    Json(mut params): Json<CreateUserParams>,
+    ValidatedJson(mut params): ValidatedJson<CreateUserParams>,
 ) -> Result<impl IntoResponse, AppError> {
     params.password = bcrypt::hash(params.password, DEFAULT_COST)?;
 
-    let user = service::user::UserService::create(&db, params).await?;
+    let user = UserService::create(&db, params).await?;
 
-    Ok(Json(json!({
-        "id": user.id,
-        "username": user.username,
-        "phone_number": user.phone_number,
-        "create_at": user.create_at,
-    })))
+    Ok(Json(UserInfo::from(user)))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    ValidatedJson(params): ValidatedJson<UpdateUserParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = UserService::update(&db, user_id, params).await?;
+
+    Ok(Json(UserInfo::from(user)))
 }
 
 async fn logoff(
     State(AppState { db, .. }): State<AppState>,
     Claims { user_id, .. }: Claims,
 ) -> Result<impl IntoResponse, AppError> {
-    let user = service::user::UserService::delete(&db, user_id).await?;
+    let user = UserService::delete(&db, user_id).await?;
+
+    Ok(Json(UserInfo::from(user)))
+}
+
+// 修改密码需要验证原密码
+async fn change_password(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    ValidatedJson(params): ValidatedJson<ChangePasswordParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = UserService::find_by_id(&db, user_id).await?;
+
+    if !verify(params.old_password, &user.password)? {
+        return Err(anyhow::anyhow!("原密码错误").into());
+    }
+
+    let password = bcrypt::hash(params.new_password, DEFAULT_COST)?;
+    let user = UserService::set_password(&db, user_id, password).await?;
+
+    Ok(Json(UserInfo::from(user)))
+}
+
+// 管理员生成重置码, 线下交给忘记密码的用户
+async fn reset_code(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    Json(params): Json<ResetCodeParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let admin = UserService::find_by_id(&db, user_id).await?;
+    if !admin.is_admin {
+        return Err(AuthError::Forbidden.into());
+    }
+
+    let user = UserService::find_by_phone_number(&db, params.phone_number).await?;
+
+    let code = format!("{:06}", uuid::Uuid::new_v4().as_u128() % 1_000_000);
+    let expire_at = (Utc::now() + Duration::minutes(RESET_CODE_TTL_MINUTES)).fixed_offset();
+    UserService::create_reset_code(
+        &db,
+        user.id,
+        admin.id,
+        bcrypt::hash(&code, DEFAULT_COST)?,
+        expire_at,
+    )
+    .await?;
 
     Ok(Json(json!({
-        "id": user.id,
-        "username": user.username,
-        "phone_number":user.phone_number,
-        "create_at": user.create_at,
+        "user_id": user.id,
+        "code": code,
+        "expire_at": expire_at,
     })))
 }
+
+async fn reset_password(
+    State(AppState { db, .. }): State<AppState>,
+    ValidatedJson(params): ValidatedJson<ResetPasswordParams>,
+) -> Result<impl IntoResponse, AppError> {
+    // 用户不存在, 没有可用的重置码和重置码错误返回同样的错误, 避免泄露已注册的手机号
+    let reset = match UserService::find_by_phone_number(&db, params.phone_number).await {
+        Ok(user) => {
+            match UserService::find_reset_code(&db, user.id, Utc::now().fixed_offset()).await {
+                Ok(reset) => Some(reset),
+                Err(DbErr::RecordNotFound(_)) => None,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(DbErr::RecordNotFound(_)) => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    let Some(reset) = reset else {
+        // 同样校验一次, 响应时间也不能区分
+        verify(params.code, &UNUSED_CODE_HASH)?;
+        return Err(anyhow::anyhow!(INVALID_RESET_CODE).into());
+    };
+    if !verify(params.code, &reset.code)? {
+        UserService::fail_reset_code(&db, reset.id).await?;
+        return Err(anyhow::anyhow!(INVALID_RESET_CODE).into());
+    }
+
+    let password = bcrypt::hash(params.new_password, DEFAULT_COST)?;
+    let user = UserService::reset_password(&db, reset, password).await?;
+
+    Ok(Json(UserInfo::from(user)))
+}
@@ -0,0 +1,174 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use sea_orm::{DbConn, DbErr};
+use serde_json::json;
+use service::{
+    clothing::ClothingService,
+    workshop::{
+        AddMemberParams, CreateWorkshopParams, UpdateWorkshopParams, WorkshopRole, WorkshopService,
+    },
+};
+
+use crate::{
+    error::{AppError, AuthError},
+    extract::ValidatedJson,
+    jwt::{Claims, CurrentWorkshop},
+    routes::clothing::images,
+    state::AppState,
+    storage,
+};
+
+pub fn route() -> Router<AppState> {
+    Router::new()
+        .route("/workshop", post(create).get(find))
+        .route("/workshop/current", get(current))
+        .route("/workshop/:id", get(find_by_id).put(update).delete(remove))
+        .route("/workshop/:id/select", post(select))
+        .route("/workshop/:id/member", post(add_member).get(find_members))
+        .route("/workshop/:id/member/:user_id", delete(remove_member))
+}
+
+// 只有工坊主可以修改工坊和管理成员
+async fn check_owner(db: &DbConn, workshop_id: i32, user_id: i32) -> Result<(), AppError> {
+    match WorkshopService::membership(db, workshop_id, user_id).await {
+        Ok(member) if member.role == WorkshopRole::Owner => Ok(()),
+        Ok(_) | Err(DbErr::RecordNotFound(_)) => Err(AuthError::Forbidden.into()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn check_member(db: &DbConn, workshop_id: i32, user_id: i32) -> Result<(), AppError> {
+    match WorkshopService::membership(db, workshop_id, user_id).await {
+        Ok(_) => Ok(()),
+        Err(DbErr::RecordNotFound(_)) => Err(AuthError::Forbidden.into()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn create(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    ValidatedJson(params): ValidatedJson<CreateWorkshopParams>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(WorkshopService::create(&db, user_id, params).await?))
+}
+
+// 当前用户加入的所有工坊
+async fn find(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(WorkshopService::find_by_user_id(&db, user_id).await?))
+}
+
+// 当前请求所在的工坊
+async fn current(
+    State(AppState { db, .. }): State<AppState>,
+    CurrentWorkshop {
+        workshop_id, role, ..
+    }: CurrentWorkshop,
+) -> Result<impl IntoResponse, AppError> {
+    let workshop = WorkshopService::find_by_id(&db, workshop_id).await?;
+
+    Ok(Json(json!({
+        "workshop": workshop,
+        "role": role,
+    })))
+}
+
+async fn find_by_id(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    check_member(&db, id, user_id).await?;
+
+    Ok(Json(WorkshopService::find_by_id(&db, id).await?))
+}
+
+async fn update(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    Path(id): Path<i32>,
+    Json(params): Json<UpdateWorkshopParams>,
+) -> Result<impl IntoResponse, AppError> {
+    check_owner(&db, id, user_id).await?;
+
+    Ok(Json(WorkshopService::update(&db, id, params).await?))
+}
+
+// 删除工坊会级联删除工坊的所有数据, 同时清理服装图片
+async fn remove(
+    State(AppState { db, storage, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    check_owner(&db, id, user_id).await?;
+
+    let clothings = ClothingService::find_all(&db, id).await?;
+    let workshop = WorkshopService::delete(&db, id).await?;
+
+    let urls = clothings
+        .into_iter()
+        .flat_map(|clothing| images(clothing.image, clothing.thumbnail));
+    storage::delete_all(storage.as_ref(), urls).await;
+
+    Ok(Json(workshop))
+}
+
+// 切换工坊, 签发带有工坊 id 的 token
+async fn select(
+    State(state): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    check_member(&state.db, id, user_id).await?;
+
+    let workshop = WorkshopService::find_by_id(&state.db, id).await?;
+    let claims = Claims::with_workshop(user_id, id);
+
+    Ok(Json(json!({
+        "token": claims.encode(&state.jwt_secret)?,
+        "workshop": workshop,
+    })))
+}
+
+async fn find_members(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    check_member(&db, id, user_id).await?;
+
+    Ok(Json(WorkshopService::find_members(&db, id).await?))
+}
+
+async fn add_member(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    Path(id): Path<i32>,
+    Json(params): Json<AddMemberParams>,
+) -> Result<impl IntoResponse, AppError> {
+    check_owner(&db, id, user_id).await?;
+
+    Ok(Json(WorkshopService::add_member(&db, id, params).await?))
+}
+
+// 工坊主可以移除成员, 成员也可以自己退出
+async fn remove_member(
+    State(AppState { db, .. }): State<AppState>,
+    Claims { user_id, .. }: Claims,
+    Path((id, member_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError> {
+    if member_id != user_id {
+        check_owner(&db, id, user_id).await?;
+    }
+
+    Ok(Json(
+        WorkshopService::remove_member(&db, id, member_id).await?,
+    ))
+}
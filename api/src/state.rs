@@ -1,13 +1,31 @@
+use std::sync::Arc;
+
 use sea_orm::DatabaseConnection;
 
+use service::crypto::Cipher;
+
+use crate::storage::Storage;
+
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub db: DatabaseConnection,
     pub jwt_secret: String,
+    pub storage: Arc<dyn Storage>,
+    pub cipher: Arc<Cipher>,
 }
 
 impl AppState {
-    pub fn new(db: DatabaseConnection, jwt_secret: String) -> Self {
-        Self { db, jwt_secret }
+    pub fn new(
+        db: DatabaseConnection,
+        jwt_secret: String,
+        storage: Arc<dyn Storage>,
+        cipher: Cipher,
+    ) -> Self {
+        Self {
+            db,
+            jwt_secret,
+            storage,
+            cipher: Arc::new(cipher),
+        }
     }
 }
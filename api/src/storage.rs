@@ -0,0 +1,196 @@
+use std::{
+    fmt::Debug,
+    path::{Component, Path, PathBuf},
+};
+
+use axum::async_trait;
+
+// 图片等文件的存储后端
+#[async_trait]
+pub trait Storage: Debug + Send + Sync {
+    // 保存文件, 返回可以访问的url
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> anyhow::Result<String>;
+
+    // 根据url删除文件, 不是由该存储保存的url会被忽略
+    async fn delete(&self, url: &str) -> anyhow::Result<()>;
+}
+
+// 保存在本地目录, 通过 base_url 对外提供访问
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>, base_url: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.into(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+        let key = Path::new(key);
+        if !key.components().all(|c| matches!(c, Component::Normal(_))) {
+            anyhow::bail!("invalid storage key: {}", key.display());
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> anyhow::Result<String> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, data).await?;
+
+        Ok(format!("{}/{}", self.base_url, key))
+    }
+
+    async fn delete(&self, url: &str) -> anyhow::Result<()> {
+        let Some(key) = url.strip_prefix(&format!("{}/", self.base_url)) else {
+            return Ok(());
+        };
+
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// 兼容S3协议的对象存储, 配置从 AWS_* 环境变量读取
+#[cfg(feature = "s3")]
+#[derive(Debug)]
+pub struct S3Storage {
+    store: object_store::aws::AmazonS3,
+    base_url: String,
+}
+
+#[cfg(feature = "s3")]
+impl S3Storage {
+    // base_url 为对外访问的地址, 如 https://bucket.example.com
+    pub fn from_env(base_url: impl Into<String>) -> anyhow::Result<Self> {
+        let store = object_store::aws::AmazonS3Builder::from_env().build()?;
+        Ok(Self {
+            store,
+            base_url: base_url.into(),
+        })
+    }
+}
+
+#[cfg(feature = "s3")]
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> anyhow::Result<String> {
+        use object_store::{Attribute, Attributes, ObjectStore, PutOptions};
+
+        let mut attributes = Attributes::new();
+        attributes.insert(Attribute::ContentType, content_type.to_string().into());
+        let options = PutOptions {
+            attributes,
+            ..Default::default()
+        };
+
+        self.store
+            .put_opts(&object_store::path::Path::from(key), data.into(), options)
+            .await?;
+
+        Ok(format!("{}/{}", self.base_url, key))
+    }
+
+    async fn delete(&self, url: &str) -> anyhow::Result<()> {
+        use object_store::ObjectStore;
+
+        let Some(key) = url.strip_prefix(&format!("{}/", self.base_url)) else {
+            return Ok(());
+        };
+
+        match self
+            .store
+            .delete(&object_store::path::Path::from(key))
+            .await
+        {
+            Err(object_store::Error::NotFound { .. }) => Ok(()),
+            res => Ok(res?),
+        }
+    }
+}
+
+const THUMBNAIL_SIZE: u32 = 320;
+
+// 保存原图并生成缩略图, 返回 (原图url, 缩略图url)
+pub async fn save_image(
+    storage: &dyn Storage,
+    prefix: &str,
+    data: Vec<u8>,
+) -> anyhow::Result<(String, String)> {
+    let (data, format, thumbnail) = tokio::task::spawn_blocking(move || {
+        let format = image::guess_format(&data)?;
+        let thumbnail = image::load_from_memory_with_format(&data, format)?
+            .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .into_rgb8();
+
+        let mut buffer = std::io::Cursor::new(vec![]);
+        thumbnail.write_to(&mut buffer, image::ImageFormat::Jpeg)?;
+
+        anyhow::Ok((data, format, buffer.into_inner()))
+    })
+    .await??;
+
+    let name = uuid::Uuid::new_v4();
+    let extension = format.extensions_str().first().copied().unwrap_or("img");
+
+    let image = storage
+        .put(
+            &format!("{prefix}/{name}.{extension}"),
+            data,
+            format.to_mime_type(),
+        )
+        .await?;
+    let thumbnail = storage
+        .put(
+            &format!("{prefix}/{name}_thumbnail.jpg"),
+            thumbnail,
+            image::ImageFormat::Jpeg.to_mime_type(),
+        )
+        .await?;
+
+    Ok((image, thumbnail))
+}
+
+// 保存原图, 不生成缩略图, 返回图片url
+pub async fn save_original(
+    storage: &dyn Storage,
+    prefix: &str,
+    data: Vec<u8>,
+) -> anyhow::Result<String> {
+    let format = image::guess_format(&data)?;
+    let name = uuid::Uuid::new_v4();
+    let extension = format.extensions_str().first().copied().unwrap_or("img");
+
+    storage
+        .put(
+            &format!("{prefix}/{name}.{extension}"),
+            data,
+            format.to_mime_type(),
+        )
+        .await
+}
+
+// 删除不再使用的文件, 失败时只记录日志
+pub async fn delete_all(storage: &dyn Storage, urls: impl IntoIterator<Item = String>) {
+    for url in urls {
+        if let Err(e) = storage.delete(&url).await {
+            tracing::warn!("failed to delete {url}: {e}");
+        }
+    }
+}
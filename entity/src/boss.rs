@@ -13,21 +13,24 @@ pub struct Model {
     pub address: Option<String>,
     pub description: Option<String>,
     pub create_at: DateTimeWithTimeZone,
-    pub user_id: i32,
+    pub pinyin: String,
+    pub workshop_id: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::clothing::Entity")]
     Clothing,
+    #[sea_orm(has_many = "super::sales_order::Entity")]
+    SalesOrder,
     #[sea_orm(
-        belongs_to = "super::user::Entity",
-        from = "Column::UserId",
-        to = "super::user::Column::Id",
+        belongs_to = "super::workshop::Entity",
+        from = "Column::WorkshopId",
+        to = "super::workshop::Column::Id",
         on_update = "Cascade",
         on_delete = "Cascade"
     )]
-    User,
+    Workshop,
 }
 
 impl Related<super::clothing::Entity> for Entity {
@@ -36,9 +39,15 @@ impl Related<super::clothing::Entity> for Entity {
     }
 }
 
-impl Related<super::user::Entity> for Entity {
+impl Related<super::sales_order::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::User.def()
+        Relation::SalesOrder.def()
+    }
+}
+
+impl Related<super::workshop::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Workshop.def()
     }
 }
 
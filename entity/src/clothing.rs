@@ -13,8 +13,10 @@ pub struct Model {
     #[sea_orm(column_type = "Float")]
     pub price: f32,
     pub image: Option<String>,
+    pub thumbnail: Option<String>,
     pub create_at: DateTimeWithTimeZone,
     pub boss_id: i32,
+    pub pinyin: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -27,12 +29,20 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Boss,
+    #[sea_orm(has_many = "super::clothing_material::Entity")]
+    ClothingMaterial,
+    #[sea_orm(has_many = "super::process_step::Entity")]
+    ProcessStep,
     #[sea_orm(has_many = "super::procurement_item::Entity")]
     ProcurementItem,
     #[sea_orm(has_many = "super::production_item::Entity")]
     ProductionItem,
+    #[sea_orm(has_many = "super::sales_order_item::Entity")]
+    SalesOrderItem,
     #[sea_orm(has_many = "super::shipment_item::Entity")]
     ShipmentItem,
+    #[sea_orm(has_many = "super::shipment_return_item::Entity")]
+    ShipmentReturnItem,
 }
 
 impl Related<super::boss::Entity> for Entity {
@@ -41,6 +51,18 @@ impl Related<super::boss::Entity> for Entity {
     }
 }
 
+impl Related<super::clothing_material::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ClothingMaterial.def()
+    }
+}
+
+impl Related<super::process_step::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProcessStep.def()
+    }
+}
+
 impl Related<super::procurement_item::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProcurementItem.def()
@@ -53,10 +75,22 @@ impl Related<super::production_item::Entity> for Entity {
     }
 }
 
+impl Related<super::sales_order_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SalesOrderItem.def()
+    }
+}
+
 impl Related<super::shipment_item::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ShipmentItem.def()
     }
 }
 
+impl Related<super::shipment_return_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShipmentReturnItem.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -2,13 +2,36 @@
 
 pub mod prelude;
 
+pub mod accounting_period;
+pub mod accounting_period_event;
+pub mod attendance;
 pub mod boss;
 pub mod clothing;
+pub mod clothing_material;
+pub mod inspection;
+pub mod login_event;
+pub mod material;
+pub mod material_consumption;
+pub mod password_reset;
+pub mod process_step;
 pub mod procurement;
 pub mod procurement_item;
+pub mod procurement_material;
 pub mod production;
 pub mod production_item;
+pub mod production_settlement;
+pub mod sales_order;
+pub mod sales_order_item;
+pub mod sea_orm_active_enums;
 pub mod shipment;
+pub mod shipment_event;
 pub mod shipment_item;
+pub mod shipment_return;
+pub mod shipment_return_item;
 pub mod staff;
+pub mod staff_adjustment;
+pub mod supplier;
+pub mod supplier_payment;
 pub mod user;
+pub mod workshop;
+pub mod workshop_member;
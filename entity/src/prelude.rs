@@ -1,12 +1,34 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.0.0-rc.5
 
+pub use super::accounting_period::Entity as AccountingPeriod;
+pub use super::accounting_period_event::Entity as AccountingPeriodEvent;
+pub use super::attendance::Entity as Attendance;
 pub use super::boss::Entity as Boss;
 pub use super::clothing::Entity as Clothing;
+pub use super::clothing_material::Entity as ClothingMaterial;
+pub use super::inspection::Entity as Inspection;
+pub use super::login_event::Entity as LoginEvent;
+pub use super::material::Entity as Material;
+pub use super::material_consumption::Entity as MaterialConsumption;
+pub use super::password_reset::Entity as PasswordReset;
+pub use super::process_step::Entity as ProcessStep;
 pub use super::procurement::Entity as Procurement;
 pub use super::procurement_item::Entity as ProcurementItem;
+pub use super::procurement_material::Entity as ProcurementMaterial;
 pub use super::production::Entity as Production;
 pub use super::production_item::Entity as ProductionItem;
+pub use super::production_settlement::Entity as ProductionSettlement;
+pub use super::sales_order::Entity as SalesOrder;
+pub use super::sales_order_item::Entity as SalesOrderItem;
 pub use super::shipment::Entity as Shipment;
+pub use super::shipment_event::Entity as ShipmentEvent;
 pub use super::shipment_item::Entity as ShipmentItem;
+pub use super::shipment_return::Entity as ShipmentReturn;
+pub use super::shipment_return_item::Entity as ShipmentReturnItem;
 pub use super::staff::Entity as Staff;
+pub use super::staff_adjustment::Entity as StaffAdjustment;
+pub use super::supplier::Entity as Supplier;
+pub use super::supplier_payment::Entity as SupplierPayment;
 pub use super::user::Entity as User;
+pub use super::workshop::Entity as Workshop;
+pub use super::workshop_member::Entity as WorkshopMember;
@@ -3,28 +3,41 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "procurement")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub description: Option<String>,
     pub create_at: DateTimeWithTimeZone,
-    pub user_id: i32,
+    pub workshop_id: i32,
+    pub supplier_id: Option<i32>,
+    #[sea_orm(column_type = "Float")]
+    pub total_cost: f32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::procurement_item::Entity")]
     ProcurementItem,
+    #[sea_orm(has_many = "super::procurement_material::Entity")]
+    ProcurementMaterial,
     #[sea_orm(
-        belongs_to = "super::user::Entity",
-        from = "Column::UserId",
-        to = "super::user::Column::Id",
+        belongs_to = "super::supplier::Entity",
+        from = "Column::SupplierId",
+        to = "super::supplier::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Supplier,
+    #[sea_orm(
+        belongs_to = "super::workshop::Entity",
+        from = "Column::WorkshopId",
+        to = "super::workshop::Column::Id",
         on_update = "Cascade",
         on_delete = "Cascade"
     )]
-    User,
+    Workshop,
 }
 
 impl Related<super::procurement_item::Entity> for Entity {
@@ -33,9 +46,21 @@ impl Related<super::procurement_item::Entity> for Entity {
     }
 }
 
-impl Related<super::user::Entity> for Entity {
+impl Related<super::procurement_material::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProcurementMaterial.def()
+    }
+}
+
+impl Related<super::supplier::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Supplier.def()
+    }
+}
+
+impl Related<super::workshop::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::User.def()
+        Relation::Workshop.def()
     }
 }
 
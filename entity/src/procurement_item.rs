@@ -3,7 +3,7 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "procurement_item")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -11,6 +11,10 @@ pub struct Model {
     pub amount: i32,
     pub procurement_id: i32,
     pub clothing_id: i32,
+    #[sea_orm(column_type = "Float")]
+    pub unit_cost: f32,
+    #[sea_orm(column_type = "Float")]
+    pub total_cost: f32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.0.0-rc.5
 
+use super::sea_orm_active_enums::ProductionKind;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -14,12 +15,29 @@ pub struct Model {
     #[sea_orm(column_type = "Float")]
     pub total_salary: f32,
     pub settled: bool,
+    pub kind: ProductionKind,
+    pub shipment_return_id: Option<i32>,
+    #[sea_orm(column_type = "Float")]
+    pub settled_amount: f32,
+    pub settled_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::material_consumption::Entity")]
+    MaterialConsumption,
     #[sea_orm(has_many = "super::production_item::Entity")]
     ProductionItem,
+    #[sea_orm(has_many = "super::production_settlement::Entity")]
+    ProductionSettlement,
+    #[sea_orm(
+        belongs_to = "super::shipment_return::Entity",
+        from = "Column::ShipmentReturnId",
+        to = "super::shipment_return::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    ShipmentReturn,
     #[sea_orm(
         belongs_to = "super::staff::Entity",
         from = "Column::StaffId",
@@ -30,12 +48,30 @@ pub enum Relation {
     Staff,
 }
 
+impl Related<super::material_consumption::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MaterialConsumption.def()
+    }
+}
+
 impl Related<super::production_item::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProductionItem.def()
     }
 }
 
+impl Related<super::production_settlement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductionSettlement.def()
+    }
+}
+
+impl Related<super::shipment_return::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShipmentReturn.def()
+    }
+}
+
 impl Related<super::staff::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Staff.def()
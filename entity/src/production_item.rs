@@ -15,6 +15,8 @@ pub struct Model {
     pub clothing_id: i32,
     #[sea_orm(column_type = "Float")]
     pub salary: f32,
+    pub process_step_id: Option<i32>,
+    pub settled: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -27,6 +29,16 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Clothing,
+    #[sea_orm(has_one = "super::inspection::Entity")]
+    Inspection,
+    #[sea_orm(
+        belongs_to = "super::process_step::Entity",
+        from = "Column::ProcessStepId",
+        to = "super::process_step::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    ProcessStep,
     #[sea_orm(
         belongs_to = "super::production::Entity",
         from = "Column::ProductionId",
@@ -43,6 +55,18 @@ impl Related<super::clothing::Entity> for Entity {
     }
 }
 
+impl Related<super::inspection::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Inspection.def()
+    }
+}
+
+impl Related<super::process_step::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProcessStep.def()
+    }
+}
+
 impl Related<super::production::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Production.def()
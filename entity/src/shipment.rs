@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.0.0-rc.5
 
+use super::sea_orm_active_enums::ShipmentStatus;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -10,21 +11,36 @@ pub struct Model {
     pub id: i32,
     pub description: Option<String>,
     pub create_at: DateTimeWithTimeZone,
-    pub user_id: i32,
+    pub workshop_id: i32,
+    pub status: ShipmentStatus,
+    // 签收人和签名图片
+    pub receiver: Option<String>,
+    pub signature: Option<String>,
+    pub dispatched_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::shipment_event::Entity")]
+    ShipmentEvent,
     #[sea_orm(has_many = "super::shipment_item::Entity")]
     ShipmentItem,
+    #[sea_orm(has_many = "super::shipment_return::Entity")]
+    ShipmentReturn,
     #[sea_orm(
-        belongs_to = "super::user::Entity",
-        from = "Column::UserId",
-        to = "super::user::Column::Id",
+        belongs_to = "super::workshop::Entity",
+        from = "Column::WorkshopId",
+        to = "super::workshop::Column::Id",
         on_update = "Cascade",
         on_delete = "Cascade"
     )]
-    User,
+    Workshop,
+}
+
+impl Related<super::shipment_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShipmentEvent.def()
+    }
 }
 
 impl Related<super::shipment_item::Entity> for Entity {
@@ -33,9 +49,15 @@ impl Related<super::shipment_item::Entity> for Entity {
     }
 }
 
-impl Related<super::user::Entity> for Entity {
+impl Related<super::shipment_return::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShipmentReturn.def()
+    }
+}
+
+impl Related<super::workshop::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::User.def()
+        Relation::Workshop.def()
     }
 }
 
@@ -11,6 +11,7 @@ pub struct Model {
     pub amount: i32,
     pub shipment_id: i32,
     pub clothing_id: i32,
+    pub sales_order_item_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -23,6 +24,14 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Clothing,
+    #[sea_orm(
+        belongs_to = "super::sales_order_item::Entity",
+        from = "Column::SalesOrderItemId",
+        to = "super::sales_order_item::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    SalesOrderItem,
     #[sea_orm(
         belongs_to = "super::shipment::Entity",
         from = "Column::ShipmentId",
@@ -31,6 +40,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Shipment,
+    #[sea_orm(has_many = "super::shipment_return_item::Entity")]
+    ShipmentReturnItem,
 }
 
 impl Related<super::clothing::Entity> for Entity {
@@ -39,10 +50,22 @@ impl Related<super::clothing::Entity> for Entity {
     }
 }
 
+impl Related<super::sales_order_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SalesOrderItem.def()
+    }
+}
+
 impl Related<super::shipment::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Shipment.def()
     }
 }
 
+impl Related<super::shipment_return_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShipmentReturnItem.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.0.0-rc.5
 
+use super::sea_orm_active_enums::StaffStatus;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -12,21 +13,42 @@ pub struct Model {
     pub phone_number: String,
     pub description: Option<String>,
     pub create_at: DateTimeWithTimeZone,
-    pub user_id: i32,
+    pub pinyin: String,
+    pub workshop_id: i32,
+    // 加密保存, 不直接序列化
+    #[serde(skip_serializing)]
+    pub id_card: Option<String>,
+    #[serde(skip_serializing)]
+    pub bank_account: Option<String>,
+    pub hire_date: Option<Date>,
+    pub leave_date: Option<Date>,
+    pub status: StaffStatus,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::attendance::Entity")]
+    Attendance,
     #[sea_orm(has_many = "super::production::Entity")]
     Production,
+    #[sea_orm(has_many = "super::shipment_return_item::Entity")]
+    ShipmentReturnItem,
+    #[sea_orm(has_many = "super::staff_adjustment::Entity")]
+    StaffAdjustment,
     #[sea_orm(
-        belongs_to = "super::user::Entity",
-        from = "Column::UserId",
-        to = "super::user::Column::Id",
+        belongs_to = "super::workshop::Entity",
+        from = "Column::WorkshopId",
+        to = "super::workshop::Column::Id",
         on_update = "Cascade",
         on_delete = "Cascade"
     )]
-    User,
+    Workshop,
+}
+
+impl Related<super::attendance::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attendance.def()
+    }
 }
 
 impl Related<super::production::Entity> for Entity {
@@ -35,9 +57,21 @@ impl Related<super::production::Entity> for Entity {
     }
 }
 
-impl Related<super::user::Entity> for Entity {
+impl Related<super::shipment_return_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShipmentReturnItem.def()
+    }
+}
+
+impl Related<super::staff_adjustment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StaffAdjustment.def()
+    }
+}
+
+impl Related<super::workshop::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::User.def()
+        Relation::Workshop.def()
     }
 }
 
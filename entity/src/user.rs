@@ -13,41 +13,67 @@ pub struct Model {
     #[sea_orm(unique)]
     pub phone_number: String,
     pub create_at: DateTimeWithTimeZone,
+    pub is_admin: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
-    #[sea_orm(has_many = "super::boss::Entity")]
-    Boss,
-    #[sea_orm(has_many = "super::procurement::Entity")]
-    Procurement,
-    #[sea_orm(has_many = "super::shipment::Entity")]
-    Shipment,
-    #[sea_orm(has_many = "super::staff::Entity")]
-    Staff,
+    #[sea_orm(has_many = "super::accounting_period_event::Entity")]
+    AccountingPeriodEvent,
+    #[sea_orm(has_many = "super::inspection::Entity")]
+    Inspection,
+    #[sea_orm(has_many = "super::login_event::Entity")]
+    LoginEvent,
+    #[sea_orm(has_many = "super::production_settlement::Entity")]
+    ProductionSettlement,
+    #[sea_orm(has_many = "super::shipment_event::Entity")]
+    ShipmentEvent,
+    #[sea_orm(has_many = "super::workshop_member::Entity")]
+    WorkshopMember,
 }
 
-impl Related<super::boss::Entity> for Entity {
+impl Related<super::accounting_period_event::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Boss.def()
+        Relation::AccountingPeriodEvent.def()
     }
 }
 
-impl Related<super::procurement::Entity> for Entity {
+impl Related<super::inspection::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Procurement.def()
+        Relation::Inspection.def()
     }
 }
 
-impl Related<super::shipment::Entity> for Entity {
+impl Related<super::login_event::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Shipment.def()
+        Relation::LoginEvent.def()
     }
 }
 
-impl Related<super::staff::Entity> for Entity {
+impl Related<super::production_settlement::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Staff.def()
+        Relation::ProductionSettlement.def()
+    }
+}
+
+impl Related<super::shipment_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShipmentEvent.def()
+    }
+}
+
+impl Related<super::workshop_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WorkshopMember.def()
+    }
+}
+
+impl Related<super::workshop::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::workshop_member::Relation::Workshop.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::workshop_member::Relation::User.def().rev())
     }
 }
 
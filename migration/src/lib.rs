@@ -10,6 +10,26 @@ mod m20241008_102213_shipment_item;
 mod m20241008_102230_procurement_item;
 mod m20241009_014954_production;
 mod m20241009_015500_production_item;
+mod m20241020_083015_process_step;
+mod m20241021_021540_attendance;
+mod m20241022_064210_staff_adjustment;
+mod m20241023_031205_clothing_thumbnail;
+mod m20241024_021830_search;
+mod m20241025_013240_password_reset;
+mod m20241026_074512_login_event;
+mod m20241027_060318_workshop;
+mod m20241028_031742_material;
+mod m20241029_015826_supplier;
+mod m20241030_063514_procurement_cost;
+mod m20241031_081204_sales_order;
+mod m20241101_023915_shipment_status;
+mod m20241102_014406_shipment_return;
+mod m20241103_062157_inspection;
+mod m20241104_030812_accounting_period;
+mod m20241105_072436_partial_settlement;
+mod m20241106_021953_staff_profile;
+mod m20241107_024518_password_reset_attempt;
+mod m20241108_031542_shipment_dispatched_at;
 
 pub struct Migrator;
 
@@ -27,6 +47,26 @@ impl MigratorTrait for Migrator {
             Box::new(m20241008_102230_procurement_item::Migration),
             Box::new(m20241009_014954_production::Migration),
             Box::new(m20241009_015500_production_item::Migration),
+            Box::new(m20241020_083015_process_step::Migration),
+            Box::new(m20241021_021540_attendance::Migration),
+            Box::new(m20241022_064210_staff_adjustment::Migration),
+            Box::new(m20241023_031205_clothing_thumbnail::Migration),
+            Box::new(m20241024_021830_search::Migration),
+            Box::new(m20241025_013240_password_reset::Migration),
+            Box::new(m20241026_074512_login_event::Migration),
+            Box::new(m20241027_060318_workshop::Migration),
+            Box::new(m20241028_031742_material::Migration),
+            Box::new(m20241029_015826_supplier::Migration),
+            Box::new(m20241030_063514_procurement_cost::Migration),
+            Box::new(m20241031_081204_sales_order::Migration),
+            Box::new(m20241101_023915_shipment_status::Migration),
+            Box::new(m20241102_014406_shipment_return::Migration),
+            Box::new(m20241103_062157_inspection::Migration),
+            Box::new(m20241104_030812_accounting_period::Migration),
+            Box::new(m20241105_072436_partial_settlement::Migration),
+            Box::new(m20241106_021953_staff_profile::Migration),
+            Box::new(m20241107_024518_password_reset_attempt::Migration),
+            Box::new(m20241108_031542_shipment_dispatched_at::Migration),
         ]
     }
 }
@@ -50,4 +50,9 @@ pub enum Staff {
     Description,
     PhoneNumber,
     CreateAt,
+    IdCard,
+    BankAccount,
+    HireDate,
+    LeaveDate,
+    Status,
 }
@@ -51,5 +51,6 @@ pub enum Clothing {
     Description,
     Price,
     Image,
+    Thumbnail,
     CreateAt,
 }
@@ -45,4 +45,7 @@ pub enum Shipment {
     UserId,
     CreateAt,
     Description,
+    Status,
+    Receiver,
+    Signature,
 }
@@ -46,4 +46,6 @@ pub enum Procurement {
     UserId,
     CreateAt,
     Description,
+    SupplierId,
+    TotalCost,
 }
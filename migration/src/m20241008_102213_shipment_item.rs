@@ -44,10 +44,11 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(DeriveIden)]
-enum ShipmentItem {
+pub enum ShipmentItem {
     Table,
     Id,
     ShipmentId,
     ClothingId,
     Amount,
+    SalesOrderItemId,
 }
@@ -44,10 +44,12 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(DeriveIden)]
-enum ProcurementItem {
+pub enum ProcurementItem {
     Table,
     Id,
     ProcurementId,
     ClothingId,
     Amount,
+    UnitCost,
+    TotalCost,
 }
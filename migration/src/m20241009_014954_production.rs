@@ -50,4 +50,8 @@ pub enum Production {
     Settled,
     CreateAt,
     Description,
+    Kind,
+    ShipmentReturnId,
+    SettledAmount,
+    SettledAt,
 }
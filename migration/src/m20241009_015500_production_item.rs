@@ -54,4 +54,6 @@ pub enum ProductionItem {
     ProductionId,
     Salary,
     Count,
+    ProcessStepId,
+    Settled,
 }
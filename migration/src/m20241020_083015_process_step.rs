@@ -0,0 +1,85 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::{
+    m20241008_102110_clothing::Clothing, m20241009_015500_production_item::ProductionItem,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProcessStep::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ProcessStep::Id))
+                    .col(string(ProcessStep::Name))
+                    .col(string_null(ProcessStep::Description))
+                    .col(integer(ProcessStep::Sequence).default(Expr::value(0)))
+                    .col(
+                        timestamp_with_time_zone(ProcessStep::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(ProcessStep::ClothingId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ProcessStep::Table, ProcessStep::ClothingId)
+                            .to(Clothing::Table, Clothing::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 生产记录关联到具体工序, 旧数据没有工序所以允许为空
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProductionItem::Table)
+                    .add_column(integer_null(ProductionItem::ProcessStepId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-production_item-process_step_id")
+                            .from_tbl(ProductionItem::Table)
+                            .from_col(ProductionItem::ProcessStepId)
+                            .to_tbl(ProcessStep::Table)
+                            .to_col(ProcessStep::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProductionItem::Table)
+                    .drop_foreign_key(Alias::new("fk-production_item-process_step_id"))
+                    .drop_column(ProductionItem::ProcessStepId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ProcessStep::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ProcessStep {
+    Table,
+    Id,
+    ClothingId,
+    Name,
+    Description,
+    Sequence,
+    CreateAt,
+}
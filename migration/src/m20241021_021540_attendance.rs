@@ -0,0 +1,72 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20241008_102057_staff::Staff;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attendance::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Attendance::Id))
+                    .col(date(Attendance::WorkDate))
+                    .col(timestamp_with_time_zone_null(Attendance::CheckIn))
+                    .col(timestamp_with_time_zone_null(Attendance::CheckOut))
+                    .col(float(Attendance::DayRate))
+                    .col(string_null(Attendance::Description))
+                    .col(boolean(Attendance::Settled).default(Expr::value(false)))
+                    .col(
+                        timestamp_with_time_zone(Attendance::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(Attendance::StaffId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Attendance::Table, Attendance::StaffId)
+                            .to(Staff::Table, Staff::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 每个员工每天只有一条考勤
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-attendance-staff_id-work_date")
+                    .table(Attendance::Table)
+                    .col(Attendance::StaffId)
+                    .col(Attendance::WorkDate)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Attendance::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Attendance {
+    Table,
+    Id,
+    StaffId,
+    WorkDate,
+    CheckIn,
+    CheckOut,
+    DayRate,
+    Description,
+    Settled,
+    CreateAt,
+}
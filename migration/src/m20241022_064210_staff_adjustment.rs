@@ -0,0 +1,91 @@
+use sea_orm_migration::{
+    prelude::{extension::postgres::Type, *},
+    schema::*,
+};
+
+use crate::m20241008_102057_staff::Staff;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AdjustmentKind::Enum)
+                    .values(AdjustmentKind::variants())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(StaffAdjustment::Table)
+                    .if_not_exists()
+                    .col(pk_auto(StaffAdjustment::Id))
+                    .col(enumeration(
+                        StaffAdjustment::Kind,
+                        AdjustmentKind::Enum,
+                        AdjustmentKind::variants(),
+                    ))
+                    .col(float(StaffAdjustment::Amount))
+                    .col(string_null(StaffAdjustment::Description))
+                    .col(boolean(StaffAdjustment::Settled).default(Expr::value(false)))
+                    .col(
+                        timestamp_with_time_zone(StaffAdjustment::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(StaffAdjustment::StaffId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(StaffAdjustment::Table, StaffAdjustment::StaffId)
+                            .to(Staff::Table, Staff::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StaffAdjustment::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(AdjustmentKind::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum StaffAdjustment {
+    Table,
+    Id,
+    StaffId,
+    Kind,
+    Amount,
+    Description,
+    Settled,
+    CreateAt,
+}
+
+#[derive(DeriveIden)]
+pub enum AdjustmentKind {
+    #[sea_orm(iden = "adjustment_kind")]
+    Enum,
+    Advance,
+    Bonus,
+    Fine,
+    Deduction,
+}
+
+impl AdjustmentKind {
+    fn variants() -> [Self; 4] {
+        [Self::Advance, Self::Bonus, Self::Fine, Self::Deduction]
+    }
+}
@@ -0,0 +1,158 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::{
+    m20241008_102048_boss::Boss, m20241008_102057_staff::Staff, m20241008_102110_clothing::Clothing,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// (表名, 需要模糊搜索的列)
+const SEARCH_INDEXES: [(&str, &[&str]); 6] = [
+    ("boss", &["name", "phone_number", "description", "pinyin"]),
+    ("staff", &["name", "phone_number", "description", "pinyin"]),
+    ("clothing", &["name", "description", "pinyin"]),
+    ("shipment", &["description"]),
+    ("procurement", &["description"]),
+    ("production", &["description"]),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Boss::Table)
+                    .add_column(string(Pinyin::Pinyin).default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Staff::Table)
+                    .add_column(string(Pinyin::Pinyin).default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Clothing::Table)
+                    .add_column(string(Pinyin::Pinyin).default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        for table in ["boss", "staff", "clothing"] {
+            backfill(manager, table).await?;
+        }
+
+        let db = manager.get_connection();
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm")
+            .await?;
+
+        for (table, columns) in SEARCH_INDEXES {
+            let columns = columns
+                .iter()
+                .map(|c| format!("{c} gin_trgm_ops"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            db.execute_unprepared(&format!(
+                r#"CREATE INDEX IF NOT EXISTS "idx-{table}-search" ON "{table}" USING gin ({columns})"#
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for (table, _) in SEARCH_INDEXES {
+            db.execute_unprepared(&format!(r#"DROP INDEX IF EXISTS "idx-{table}-search""#))
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Clothing::Table)
+                    .drop_column(Pinyin::Pinyin)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Staff::Table)
+                    .drop_column(Pinyin::Pinyin)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Boss::Table)
+                    .drop_column(Pinyin::Pinyin)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+// 为已有数据生成拼音首字母
+async fn backfill(manager: &SchemaManager<'_>, table: &str) -> Result<(), DbErr> {
+    let rows = manager
+        .get_connection()
+        .query_all(
+            manager.get_database_backend().build(
+                Query::select()
+                    .columns([Alias::new("id"), Alias::new("name")])
+                    .from(Alias::new(table)),
+            ),
+        )
+        .await?;
+
+    for row in rows {
+        let id: i32 = row.try_get("", "id")?;
+        let name: String = row.try_get("", "name")?;
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Alias::new(table))
+                    .value(Pinyin::Pinyin, pinyin_initials(&name))
+                    .and_where(Expr::col(Alias::new("id")).eq(id))
+                    .to_owned(),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[derive(DeriveIden)]
+enum Pinyin {
+    Pinyin,
+}
+
+// 回填时的拼音首字母规则, 和迁移时的 SearchService 保持一致, 之后修改服务层不影响这里
+fn pinyin_initials(s: &str) -> String {
+    s.chars()
+        .filter_map(|c| match c {
+            c if c.is_ascii_alphanumeric() => Some(c.to_ascii_lowercase()),
+            c if c.is_ascii() => None,
+            c => deunicode::deunicode_char(c)?
+                .chars()
+                .next()
+                .filter(char::is_ascii_alphanumeric)
+                .map(|c| c.to_ascii_lowercase()),
+        })
+        .collect()
+}
@@ -0,0 +1,85 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(boolean(UserAdmin::IsAdmin).default(Expr::value(false)))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordReset::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PasswordReset::Id))
+                    .col(string(PasswordReset::Code))
+                    .col(timestamp_with_time_zone(PasswordReset::ExpireAt))
+                    .col(boolean(PasswordReset::Used).default(Expr::value(false)))
+                    .col(
+                        timestamp_with_time_zone(PasswordReset::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(PasswordReset::UserId))
+                    .col(integer(PasswordReset::IssuedBy))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PasswordReset::Table, PasswordReset::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PasswordReset::Table, PasswordReset::IssuedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PasswordReset::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(UserAdmin::IsAdmin)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserAdmin {
+    IsAdmin,
+}
+
+#[derive(DeriveIden)]
+pub enum PasswordReset {
+    Table,
+    Id,
+    UserId,
+    IssuedBy,
+    Code,
+    ExpireAt,
+    Used,
+    CreateAt,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginEvent::Table)
+                    .if_not_exists()
+                    .col(pk_auto(LoginEvent::Id))
+                    .col(string(LoginEvent::PhoneNumber))
+                    .col(string(LoginEvent::Ip))
+                    .col(boolean(LoginEvent::Success))
+                    .col(string_null(LoginEvent::Reason))
+                    .col(
+                        timestamp_with_time_zone(LoginEvent::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer_null(LoginEvent::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(LoginEvent::Table, LoginEvent::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-login_event-user_id-create_at")
+                    .table(LoginEvent::Table)
+                    .col(LoginEvent::UserId)
+                    .col(LoginEvent::CreateAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginEvent::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum LoginEvent {
+    Table,
+    Id,
+    UserId,
+    PhoneNumber,
+    Ip,
+    Success,
+    Reason,
+    CreateAt,
+}
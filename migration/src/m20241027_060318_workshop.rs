@@ -0,0 +1,245 @@
+use sea_orm_migration::{
+    prelude::{extension::postgres::Type, *},
+    schema::*,
+};
+
+use crate::m20220101_000001_create_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// 原来按 user_id 归属, 现在归属到工坊
+const SCOPED_TABLES: [&str; 4] = ["boss", "staff", "procurement", "shipment"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Workshop::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Workshop::Id))
+                    .col(string(Workshop::Name))
+                    .col(string_null(Workshop::Description))
+                    .col(
+                        timestamp_with_time_zone(Workshop::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(WorkshopRole::Enum)
+                    .values(WorkshopRole::variants())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WorkshopMember::Table)
+                    .if_not_exists()
+                    .col(pk_auto(WorkshopMember::Id))
+                    .col(enumeration(
+                        WorkshopMember::Role,
+                        WorkshopRole::Enum,
+                        WorkshopRole::variants(),
+                    ))
+                    .col(
+                        timestamp_with_time_zone(WorkshopMember::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(WorkshopMember::WorkshopId))
+                    .col(integer(WorkshopMember::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WorkshopMember::Table, WorkshopMember::WorkshopId)
+                            .to(Workshop::Table, Workshop::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WorkshopMember::Table, WorkshopMember::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-workshop_member-workshop_id-user_id")
+                    .table(WorkshopMember::Table)
+                    .col(WorkshopMember::WorkshopId)
+                    .col(WorkshopMember::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 每个已有用户建一个同 id 的工坊, 原有数据归属到这个工坊
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"INSERT INTO "workshop" ("id", "name", "create_at")
+                SELECT "id", "username", "create_at" FROM "user""#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"SELECT setval(pg_get_serial_sequence('workshop', 'id'),
+                COALESCE((SELECT MAX("id") FROM "workshop"), 0) + 1, false)"#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"INSERT INTO "workshop_member" ("workshop_id", "user_id", "role")
+                SELECT "id", "id", 'owner' FROM "user""#,
+        )
+        .await?;
+
+        for table in SCOPED_TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .add_column(integer_null(Scope::WorkshopId))
+                        .to_owned(),
+                )
+                .await?;
+
+            db.execute_unprepared(&format!(
+                r#"UPDATE "{table}" SET "workshop_id" = "user_id""#
+            ))
+            .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .modify_column(integer(Scope::WorkshopId))
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name(format!("fk-{table}-workshop_id"))
+                                .from_tbl(Alias::new(table))
+                                .from_col(Scope::WorkshopId)
+                                .to_tbl(Workshop::Table)
+                                .to_col(Workshop::Id)
+                                .on_delete(ForeignKeyAction::Cascade)
+                                .on_update(ForeignKeyAction::Cascade),
+                        )
+                        .drop_column(Scope::UserId)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // 数据归还给工坊的第一个创建者
+        for table in SCOPED_TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .add_column(integer_null(Scope::UserId))
+                        .to_owned(),
+                )
+                .await?;
+
+            db.execute_unprepared(&format!(
+                r#"UPDATE "{table}" SET "user_id" = (
+                    SELECT MIN("user_id") FROM "workshop_member"
+                    WHERE "workshop_member"."workshop_id" = "{table}"."workshop_id"
+                        AND "role" = 'owner'
+                )"#
+            ))
+            .await?;
+            db.execute_unprepared(&format!(r#"DELETE FROM "{table}" WHERE "user_id" IS NULL"#))
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .modify_column(integer(Scope::UserId))
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name(format!("fk-{table}-user_id"))
+                                .from_tbl(Alias::new(table))
+                                .from_col(Scope::UserId)
+                                .to_tbl(User::Table)
+                                .to_col(User::Id)
+                                .on_delete(ForeignKeyAction::Cascade)
+                                .on_update(ForeignKeyAction::Cascade),
+                        )
+                        .drop_column(Scope::WorkshopId)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(WorkshopMember::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(WorkshopRole::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Workshop::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Workshop {
+    Table,
+    Id,
+    Name,
+    Description,
+    CreateAt,
+    ReworkRate,
+    PayPassedOnly,
+}
+
+#[derive(DeriveIden)]
+enum Scope {
+    UserId,
+    WorkshopId,
+}
+
+#[derive(DeriveIden)]
+pub enum WorkshopMember {
+    Table,
+    Id,
+    WorkshopId,
+    UserId,
+    Role,
+    CreateAt,
+}
+
+#[derive(DeriveIden)]
+pub enum WorkshopRole {
+    #[sea_orm(iden = "workshop_role")]
+    Enum,
+    Owner,
+    Member,
+}
+
+impl WorkshopRole {
+    fn variants() -> [Self; 2] {
+        [Self::Owner, Self::Member]
+    }
+}
@@ -0,0 +1,208 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::{
+    m20241008_102110_clothing::Clothing, m20241008_102132_procurement::Procurement,
+    m20241009_014954_production::Production, m20241027_060318_workshop::Workshop,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Material::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Material::Id))
+                    .col(string(Material::Name))
+                    .col(string(Material::Unit))
+                    .col(float(Material::Stock).default(Expr::value(0.0)))
+                    .col(float(Material::Cost).default(Expr::value(0.0)))
+                    .col(string_null(Material::Description))
+                    .col(
+                        timestamp_with_time_zone(Material::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(Material::WorkshopId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Material::Table, Material::WorkshopId)
+                            .to(Workshop::Table, Workshop::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 进货单中的物料明细, 入库时增加物料库存
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProcurementMaterial::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ProcurementMaterial::Id))
+                    .col(float(ProcurementMaterial::Amount))
+                    .col(integer(ProcurementMaterial::ProcurementId))
+                    .col(integer(ProcurementMaterial::MaterialId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                ProcurementMaterial::Table,
+                                ProcurementMaterial::ProcurementId,
+                            )
+                            .to(Procurement::Table, Procurement::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ProcurementMaterial::Table, ProcurementMaterial::MaterialId)
+                            .to(Material::Table, Material::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 物料清单, 每件服装需要的物料用量
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClothingMaterial::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ClothingMaterial::Id))
+                    .col(float(ClothingMaterial::Usage))
+                    .col(integer(ClothingMaterial::ClothingId))
+                    .col(integer(ClothingMaterial::MaterialId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ClothingMaterial::Table, ClothingMaterial::ClothingId)
+                            .to(Clothing::Table, Clothing::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ClothingMaterial::Table, ClothingMaterial::MaterialId)
+                            .to(Material::Table, Material::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-clothing_material-clothing_id-material_id")
+                    .table(ClothingMaterial::Table)
+                    .col(ClothingMaterial::ClothingId)
+                    .col(ClothingMaterial::MaterialId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 生产时按物料清单扣减的物料, 删除生产记录时归还
+        manager
+            .create_table(
+                Table::create()
+                    .table(MaterialConsumption::Table)
+                    .if_not_exists()
+                    .col(pk_auto(MaterialConsumption::Id))
+                    .col(float(MaterialConsumption::Amount))
+                    .col(
+                        timestamp_with_time_zone(MaterialConsumption::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(MaterialConsumption::ProductionId))
+                    .col(integer(MaterialConsumption::MaterialId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                MaterialConsumption::Table,
+                                MaterialConsumption::ProductionId,
+                            )
+                            .to(Production::Table, Production::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MaterialConsumption::Table, MaterialConsumption::MaterialId)
+                            .to(Material::Table, Material::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MaterialConsumption::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ClothingMaterial::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ProcurementMaterial::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Material::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Material {
+    Table,
+    Id,
+    Name,
+    Unit,
+    Stock,
+    Cost,
+    Description,
+    CreateAt,
+    WorkshopId,
+}
+
+#[derive(DeriveIden)]
+pub enum ProcurementMaterial {
+    Table,
+    Id,
+    Amount,
+    UnitCost,
+    TotalCost,
+    ProcurementId,
+    MaterialId,
+}
+
+#[derive(DeriveIden)]
+pub enum ClothingMaterial {
+    Table,
+    Id,
+    Usage,
+    ClothingId,
+    MaterialId,
+}
+
+#[derive(DeriveIden)]
+pub enum MaterialConsumption {
+    Table,
+    Id,
+    Amount,
+    CreateAt,
+    ProductionId,
+    MaterialId,
+}
@@ -0,0 +1,89 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::{m20241008_102132_procurement::Procurement, m20241027_060318_workshop::Workshop};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Supplier::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Supplier::Id))
+                    .col(string(Supplier::Name))
+                    .col(string_null(Supplier::Contact))
+                    .col(string_null(Supplier::PhoneNumber))
+                    .col(string_null(Supplier::Address))
+                    .col(string_null(Supplier::Description))
+                    .col(string(Supplier::Pinyin).default(""))
+                    .col(
+                        timestamp_with_time_zone(Supplier::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(Supplier::WorkshopId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Supplier::Table, Supplier::WorkshopId)
+                            .to(Workshop::Table, Workshop::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 旧的进货单没有供应商, 删除供应商后保留进货记录
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Procurement::Table)
+                    .add_column(integer_null(Procurement::SupplierId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-procurement-supplier_id")
+                            .from_tbl(Procurement::Table)
+                            .from_col(Procurement::SupplierId)
+                            .to_tbl(Supplier::Table)
+                            .to_col(Supplier::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Procurement::Table)
+                    .drop_foreign_key(Alias::new("fk-procurement-supplier_id"))
+                    .drop_column(Procurement::SupplierId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Supplier::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Supplier {
+    Table,
+    Id,
+    Name,
+    Contact,
+    PhoneNumber,
+    Address,
+    Description,
+    Pinyin,
+    CreateAt,
+    WorkshopId,
+}
@@ -0,0 +1,144 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::{
+    m20241008_102132_procurement::Procurement, m20241008_102230_procurement_item::ProcurementItem,
+    m20241027_060318_workshop::Workshop, m20241028_031742_material::ProcurementMaterial,
+    m20241029_015826_supplier::Supplier,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 旧的进货记录没有价格, 成本按 0 计算
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProcurementItem::Table)
+                    .add_column(float(ProcurementItem::UnitCost).default(Expr::value(0.0)))
+                    .add_column(float(ProcurementItem::TotalCost).default(Expr::value(0.0)))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProcurementMaterial::Table)
+                    .add_column(float(ProcurementMaterial::UnitCost).default(Expr::value(0.0)))
+                    .add_column(float(ProcurementMaterial::TotalCost).default(Expr::value(0.0)))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Procurement::Table)
+                    .add_column(float(Procurement::TotalCost).default(Expr::value(0.0)))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 物料原来的单价作为已有物料进货的成本
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"UPDATE "procurement_material" SET "unit_cost" = "material"."cost",
+                "total_cost" = "material"."cost" * "procurement_material"."amount"
+                FROM "material" WHERE "material"."id" = "procurement_material"."material_id""#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"UPDATE "procurement" SET "total_cost" = COALESCE((
+                SELECT SUM("total_cost") FROM "procurement_material"
+                WHERE "procurement_material"."procurement_id" = "procurement"."id"
+            ), 0)"#,
+        )
+        .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SupplierPayment::Table)
+                    .if_not_exists()
+                    .col(pk_auto(SupplierPayment::Id))
+                    .col(float(SupplierPayment::Amount))
+                    .col(string_null(SupplierPayment::Description))
+                    .col(
+                        timestamp_with_time_zone(SupplierPayment::PaidAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp_with_time_zone(SupplierPayment::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(SupplierPayment::SupplierId))
+                    .col(integer(SupplierPayment::WorkshopId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SupplierPayment::Table, SupplierPayment::SupplierId)
+                            .to(Supplier::Table, Supplier::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SupplierPayment::Table, SupplierPayment::WorkshopId)
+                            .to(Workshop::Table, Workshop::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SupplierPayment::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Procurement::Table)
+                    .drop_column(Procurement::TotalCost)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProcurementMaterial::Table)
+                    .drop_column(ProcurementMaterial::UnitCost)
+                    .drop_column(ProcurementMaterial::TotalCost)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProcurementItem::Table)
+                    .drop_column(ProcurementItem::UnitCost)
+                    .drop_column(ProcurementItem::TotalCost)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum SupplierPayment {
+    Table,
+    Id,
+    Amount,
+    Description,
+    PaidAt,
+    CreateAt,
+    SupplierId,
+    WorkshopId,
+}
@@ -0,0 +1,134 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::{
+    m20241008_102048_boss::Boss, m20241008_102110_clothing::Clothing,
+    m20241008_102213_shipment_item::ShipmentItem, m20241027_060318_workshop::Workshop,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SalesOrder::Table)
+                    .if_not_exists()
+                    .col(pk_auto(SalesOrder::Id))
+                    .col(date(SalesOrder::DueDate))
+                    .col(string_null(SalesOrder::Description))
+                    .col(
+                        timestamp_with_time_zone(SalesOrder::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(SalesOrder::BossId))
+                    .col(integer(SalesOrder::WorkshopId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SalesOrder::Table, SalesOrder::BossId)
+                            .to(Boss::Table, Boss::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SalesOrder::Table, SalesOrder::WorkshopId)
+                            .to(Workshop::Table, Workshop::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 订单明细, 每种服装一行
+        manager
+            .create_table(
+                Table::create()
+                    .table(SalesOrderItem::Table)
+                    .if_not_exists()
+                    .col(pk_auto(SalesOrderItem::Id))
+                    .col(integer(SalesOrderItem::Amount))
+                    .col(integer(SalesOrderItem::SalesOrderId))
+                    .col(integer(SalesOrderItem::ClothingId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SalesOrderItem::Table, SalesOrderItem::SalesOrderId)
+                            .to(SalesOrder::Table, SalesOrder::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SalesOrderItem::Table, SalesOrderItem::ClothingId)
+                            .to(Clothing::Table, Clothing::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 出货明细可以分配到订单明细, 删除订单后保留出货记录
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ShipmentItem::Table)
+                    .add_column(integer_null(ShipmentItem::SalesOrderItemId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-shipment_item-sales_order_item_id")
+                            .from_tbl(ShipmentItem::Table)
+                            .from_col(ShipmentItem::SalesOrderItemId)
+                            .to_tbl(SalesOrderItem::Table)
+                            .to_col(SalesOrderItem::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ShipmentItem::Table)
+                    .drop_foreign_key(Alias::new("fk-shipment_item-sales_order_item_id"))
+                    .drop_column(ShipmentItem::SalesOrderItemId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(SalesOrderItem::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(SalesOrder::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum SalesOrder {
+    Table,
+    Id,
+    DueDate,
+    Description,
+    CreateAt,
+    BossId,
+    WorkshopId,
+}
+
+#[derive(DeriveIden)]
+pub enum SalesOrderItem {
+    Table,
+    Id,
+    Amount,
+    SalesOrderId,
+    ClothingId,
+}
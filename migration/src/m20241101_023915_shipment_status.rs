@@ -0,0 +1,145 @@
+use sea_orm_migration::{
+    prelude::{extension::postgres::Type, *},
+    schema::*,
+};
+
+use crate::{m20220101_000001_create_table::User, m20241008_102121_shipment::Shipment};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ShipmentStatus::Enum)
+                    .values(ShipmentStatus::variants())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shipment::Table)
+                    .add_column(
+                        enumeration(
+                            Shipment::Status,
+                            ShipmentStatus::Enum,
+                            ShipmentStatus::variants(),
+                        )
+                        .default("draft"),
+                    )
+                    .add_column(string_null(Shipment::Receiver))
+                    .add_column(string_null(Shipment::Signature))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 状态变更记录, 包括变更时间和操作人
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShipmentEvent::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ShipmentEvent::Id))
+                    .col(enumeration(
+                        ShipmentEvent::Status,
+                        ShipmentStatus::Enum,
+                        ShipmentStatus::variants(),
+                    ))
+                    .col(
+                        timestamp_with_time_zone(ShipmentEvent::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(ShipmentEvent::ShipmentId))
+                    .col(integer_null(ShipmentEvent::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ShipmentEvent::Table, ShipmentEvent::ShipmentId)
+                            .to(Shipment::Table, Shipment::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ShipmentEvent::Table, ShipmentEvent::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已有的出货单都已经扣减过库存, 视为已发货
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE shipment SET status = 'dispatched';
+                INSERT INTO shipment_event (status, create_at, shipment_id)
+                SELECT 'dispatched', create_at, id FROM shipment;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShipmentEvent::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shipment::Table)
+                    .drop_column(Shipment::Status)
+                    .drop_column(Shipment::Receiver)
+                    .drop_column(Shipment::Signature)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ShipmentStatus::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ShipmentEvent {
+    Table,
+    Id,
+    Status,
+    CreateAt,
+    ShipmentId,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+pub enum ShipmentStatus {
+    #[sea_orm(iden = "shipment_status")]
+    Enum,
+    Draft,
+    Packed,
+    Dispatched,
+    Delivered,
+    Returned,
+}
+
+impl ShipmentStatus {
+    fn variants() -> [Self; 5] {
+        [
+            Self::Draft,
+            Self::Packed,
+            Self::Dispatched,
+            Self::Delivered,
+            Self::Returned,
+        ]
+    }
+}
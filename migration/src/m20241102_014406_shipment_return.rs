@@ -0,0 +1,231 @@
+use sea_orm_migration::{
+    prelude::{extension::postgres::Type, *},
+    schema::*,
+};
+
+use crate::{
+    m20241008_102057_staff::Staff, m20241008_102110_clothing::Clothing,
+    m20241008_102121_shipment::Shipment, m20241008_102213_shipment_item::ShipmentItem,
+    m20241009_014954_production::Production, m20241022_064210_staff_adjustment::StaffAdjustment,
+    m20241027_060318_workshop::Workshop,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 老板退回的次品
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShipmentReturn::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ShipmentReturn::Id))
+                    .col(string_null(ShipmentReturn::Description))
+                    .col(
+                        timestamp_with_time_zone(ShipmentReturn::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(ShipmentReturn::ShipmentId))
+                    .col(integer(ShipmentReturn::WorkshopId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ShipmentReturn::Table, ShipmentReturn::ShipmentId)
+                            .to(Shipment::Table, Shipment::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ShipmentReturn::Table, ShipmentReturn::WorkshopId)
+                            .to(Workshop::Table, Workshop::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 退货明细, 可以指定责任员工并扣款
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShipmentReturnItem::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ShipmentReturnItem::Id))
+                    .col(integer(ShipmentReturnItem::Amount))
+                    .col(float(ShipmentReturnItem::Deduction).default(Expr::value(0.0)))
+                    .col(integer(ShipmentReturnItem::ShipmentReturnId))
+                    .col(integer(ShipmentReturnItem::ShipmentItemId))
+                    .col(integer(ShipmentReturnItem::ClothingId))
+                    .col(integer_null(ShipmentReturnItem::StaffId))
+                    .col(integer_null(ShipmentReturnItem::StaffAdjustmentId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                ShipmentReturnItem::Table,
+                                ShipmentReturnItem::ShipmentReturnId,
+                            )
+                            .to(ShipmentReturn::Table, ShipmentReturn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                ShipmentReturnItem::Table,
+                                ShipmentReturnItem::ShipmentItemId,
+                            )
+                            .to(ShipmentItem::Table, ShipmentItem::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ShipmentReturnItem::Table, ShipmentReturnItem::ClothingId)
+                            .to(Clothing::Table, Clothing::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ShipmentReturnItem::Table, ShipmentReturnItem::StaffId)
+                            .to(Staff::Table, Staff::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                ShipmentReturnItem::Table,
+                                ShipmentReturnItem::StaffAdjustmentId,
+                            )
+                            .to(StaffAdjustment::Table, StaffAdjustment::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ProductionKind::Enum)
+                    .values(ProductionKind::variants())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 返工的生产记录关联退货单
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Production::Table)
+                    .add_column(
+                        enumeration(
+                            Production::Kind,
+                            ProductionKind::Enum,
+                            ProductionKind::variants(),
+                        )
+                        .default("normal"),
+                    )
+                    .add_column(integer_null(Production::ShipmentReturnId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-production-shipment_return_id")
+                            .from_tbl(Production::Table)
+                            .from_col(Production::ShipmentReturnId)
+                            .to_tbl(ShipmentReturn::Table)
+                            .to_col(ShipmentReturn::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 返工工价 = 正常工价 * 返工系数
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workshop::Table)
+                    .add_column(float(Workshop::ReworkRate).default(Expr::value(1.0)))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workshop::Table)
+                    .drop_column(Workshop::ReworkRate)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Production::Table)
+                    .drop_foreign_key(Alias::new("fk-production-shipment_return_id"))
+                    .drop_column(Production::ShipmentReturnId)
+                    .drop_column(Production::Kind)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ProductionKind::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ShipmentReturnItem::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ShipmentReturn::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ShipmentReturn {
+    Table,
+    Id,
+    Description,
+    CreateAt,
+    ShipmentId,
+    WorkshopId,
+}
+
+#[derive(DeriveIden)]
+pub enum ShipmentReturnItem {
+    Table,
+    Id,
+    Amount,
+    Deduction,
+    ShipmentReturnId,
+    ShipmentItemId,
+    ClothingId,
+    StaffId,
+    StaffAdjustmentId,
+}
+
+#[derive(DeriveIden)]
+pub enum ProductionKind {
+    #[sea_orm(iden = "production_kind")]
+    Enum,
+    Normal,
+    Rework,
+}
+
+impl ProductionKind {
+    fn variants() -> [Self; 2] {
+        [Self::Normal, Self::Rework]
+    }
+}
@@ -0,0 +1,94 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::{
+    m20220101_000001_create_table::User, m20241009_015500_production_item::ProductionItem,
+    m20241027_060318_workshop::Workshop,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 质检记录, 每条生产明细只检验一次
+        manager
+            .create_table(
+                Table::create()
+                    .table(Inspection::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Inspection::Id))
+                    .col(integer(Inspection::Passed))
+                    .col(integer(Inspection::Rejected))
+                    .col(string_null(Inspection::Reason))
+                    .col(
+                        timestamp_with_time_zone(Inspection::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer_uniq(Inspection::ProductionItemId))
+                    .col(integer_null(Inspection::InspectorId))
+                    .col(integer(Inspection::WorkshopId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Inspection::Table, Inspection::ProductionItemId)
+                            .to(ProductionItem::Table, ProductionItem::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Inspection::Table, Inspection::InspectorId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Inspection::Table, Inspection::WorkshopId)
+                            .to(Workshop::Table, Workshop::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 开启后计件工资只按质检合格的件数计算
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workshop::Table)
+                    .add_column(boolean(Workshop::PayPassedOnly).default(Expr::value(false)))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workshop::Table)
+                    .drop_column(Workshop::PayPassedOnly)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Inspection::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Inspection {
+    Table,
+    Id,
+    Passed,
+    Rejected,
+    Reason,
+    CreateAt,
+    ProductionItemId,
+    InspectorId,
+    WorkshopId,
+}
@@ -0,0 +1,160 @@
+use sea_orm_migration::{
+    prelude::{extension::postgres::Type, *},
+    schema::*,
+};
+
+use crate::{m20220101_000001_create_table::User, m20241027_060318_workshop::Workshop};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 已结账的会计期间, 保存结账时的汇总数据
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccountingPeriod::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AccountingPeriod::Id))
+                    .col(timestamp_with_time_zone(AccountingPeriod::StartTime))
+                    .col(timestamp_with_time_zone(AccountingPeriod::EndTime))
+                    .col(boolean(AccountingPeriod::Closed).default(Expr::value(true)))
+                    .col(float(AccountingPeriod::Revenue))
+                    .col(float(AccountingPeriod::PieceSalary))
+                    .col(float(AccountingPeriod::DaySalary))
+                    .col(float(AccountingPeriod::ProcurementCost))
+                    .col(
+                        timestamp_with_time_zone(AccountingPeriod::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(AccountingPeriod::WorkshopId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AccountingPeriod::Table, AccountingPeriod::WorkshopId)
+                            .to(Workshop::Table, Workshop::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 每个工坊同一期间只能结账一次
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-accounting_period-workshop_id-start_time")
+                    .table(AccountingPeriod::Table)
+                    .col(AccountingPeriod::WorkshopId)
+                    .col(AccountingPeriod::StartTime)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(PeriodAction::Enum)
+                    .values(PeriodAction::variants())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 结账和反结账记录
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccountingPeriodEvent::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AccountingPeriodEvent::Id))
+                    .col(enumeration(
+                        AccountingPeriodEvent::Action,
+                        PeriodAction::Enum,
+                        PeriodAction::variants(),
+                    ))
+                    .col(string_null(AccountingPeriodEvent::Reason))
+                    .col(
+                        timestamp_with_time_zone(AccountingPeriodEvent::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(AccountingPeriodEvent::AccountingPeriodId))
+                    .col(integer_null(AccountingPeriodEvent::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                AccountingPeriodEvent::Table,
+                                AccountingPeriodEvent::AccountingPeriodId,
+                            )
+                            .to(AccountingPeriod::Table, AccountingPeriod::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AccountingPeriodEvent::Table, AccountingPeriodEvent::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccountingPeriodEvent::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(PeriodAction::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AccountingPeriod::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AccountingPeriod {
+    Table,
+    Id,
+    StartTime,
+    EndTime,
+    Closed,
+    Revenue,
+    PieceSalary,
+    DaySalary,
+    ProcurementCost,
+    CreateAt,
+    WorkshopId,
+}
+
+#[derive(DeriveIden)]
+pub enum AccountingPeriodEvent {
+    Table,
+    Id,
+    Action,
+    Reason,
+    CreateAt,
+    AccountingPeriodId,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+pub enum PeriodAction {
+    #[sea_orm(iden = "period_action")]
+    Enum,
+    Close,
+    Reopen,
+}
+
+impl PeriodAction {
+    fn variants() -> [Self; 2] {
+        [Self::Close, Self::Reopen]
+    }
+}
@@ -0,0 +1,156 @@
+use sea_orm_migration::{
+    prelude::{extension::postgres::Type, *},
+    schema::*,
+};
+
+use crate::{
+    m20220101_000001_create_table::User, m20241009_014954_production::Production,
+    m20241009_015500_production_item::ProductionItem,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 按生产明细结算
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProductionItem::Table)
+                    .add_column(boolean(ProductionItem::Settled).default(Expr::value(false)))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Production::Table)
+                    .add_column(float(Production::SettledAmount).default(Expr::value(0.0)))
+                    .add_column(timestamp_with_time_zone_null(Production::SettledAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(SettlementAction::Enum)
+                    .values(SettlementAction::variants())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 结算和撤销结算记录, 撤销时必须填写原因
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductionSettlement::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ProductionSettlement::Id))
+                    .col(enumeration(
+                        ProductionSettlement::Action,
+                        SettlementAction::Enum,
+                        SettlementAction::variants(),
+                    ))
+                    .col(float(ProductionSettlement::Amount))
+                    .col(string_null(ProductionSettlement::Reason))
+                    .col(
+                        timestamp_with_time_zone(ProductionSettlement::CreateAt)
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(integer(ProductionSettlement::ProductionId))
+                    .col(integer_null(ProductionSettlement::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                ProductionSettlement::Table,
+                                ProductionSettlement::ProductionId,
+                            )
+                            .to(Production::Table, Production::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ProductionSettlement::Table, ProductionSettlement::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已结算的生产记录视为全部明细已结算, 结算时间未知
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE production_item SET settled = true
+                WHERE production_id IN (SELECT id FROM production WHERE settled);
+                UPDATE production SET settled_amount = total_salary WHERE settled;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProductionSettlement::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(SettlementAction::Enum).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Production::Table)
+                    .drop_column(Production::SettledAmount)
+                    .drop_column(Production::SettledAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProductionItem::Table)
+                    .drop_column(ProductionItem::Settled)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ProductionSettlement {
+    Table,
+    Id,
+    Action,
+    Amount,
+    Reason,
+    CreateAt,
+    ProductionId,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+pub enum SettlementAction {
+    #[sea_orm(iden = "settlement_action")]
+    Enum,
+    Settle,
+    Unsettle,
+}
+
+impl SettlementAction {
+    fn variants() -> [Self; 2] {
+        [Self::Settle, Self::Unsettle]
+    }
+}
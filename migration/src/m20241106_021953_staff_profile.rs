@@ -0,0 +1,73 @@
+use sea_orm_migration::{
+    prelude::{extension::postgres::Type, *},
+    schema::*,
+};
+
+use crate::m20241008_102057_staff::Staff;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(StaffStatus::Enum)
+                    .values(StaffStatus::variants())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 身份证号和收款账号加密保存
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Staff::Table)
+                    .add_column(string_null(Staff::IdCard))
+                    .add_column(string_null(Staff::BankAccount))
+                    .add_column(date_null(Staff::HireDate))
+                    .add_column(date_null(Staff::LeaveDate))
+                    .add_column(
+                        enumeration(Staff::Status, StaffStatus::Enum, StaffStatus::variants())
+                            .default("active"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Staff::Table)
+                    .drop_column(Staff::IdCard)
+                    .drop_column(Staff::BankAccount)
+                    .drop_column(Staff::HireDate)
+                    .drop_column(Staff::LeaveDate)
+                    .drop_column(Staff::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(StaffStatus::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum StaffStatus {
+    #[sea_orm(iden = "staff_status")]
+    Enum,
+    Active,
+    Inactive,
+}
+
+impl StaffStatus {
+    fn variants() -> [Self; 2] {
+        [Self::Active, Self::Inactive]
+    }
+}
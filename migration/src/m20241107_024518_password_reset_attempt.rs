@@ -0,0 +1,37 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20241025_013240_password_reset::PasswordReset;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 重置码输错的次数, 达到上限后作废
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PasswordReset::Table)
+                    .add_column(integer(PasswordResetAttempt::Attempts).default(0))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PasswordReset::Table)
+                    .drop_column(PasswordResetAttempt::Attempts)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PasswordResetAttempt {
+    Attempts,
+}
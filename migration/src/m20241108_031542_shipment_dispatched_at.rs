@@ -0,0 +1,57 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20241008_102121_shipment::Shipment;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 发货时间, 出货收入按发货时间归属结账期间
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shipment::Table)
+                    .add_column(timestamp_with_time_zone_null(ShipmentDispatch::DispatchedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已发货的出货单取发货记录的时间
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE shipment SET dispatched_at = (
+                    SELECT MIN(create_at) FROM shipment_event
+                    WHERE shipment_event.shipment_id = shipment.id
+                    AND shipment_event.status = 'dispatched'
+                )
+                WHERE status IN ('dispatched', 'delivered', 'returned');
+                UPDATE shipment SET dispatched_at = create_at
+                WHERE dispatched_at IS NULL
+                AND status IN ('dispatched', 'delivered', 'returned');
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shipment::Table)
+                    .drop_column(ShipmentDispatch::DispatchedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ShipmentDispatch {
+    DispatchedAt,
+}
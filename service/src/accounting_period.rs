@@ -0,0 +1,428 @@
+use ::entity::accounting_period::{ActiveModel, Column, Entity, Model};
+pub use ::entity::sea_orm_active_enums::PeriodAction;
+use ::entity::{
+    accounting_period_event, attendance, clothing, procurement, production, shipment, shipment_item,
+};
+use chrono::{Datelike, FixedOffset, Months, NaiveTime, Utc};
+use sea_orm::{
+    prelude::{Date, DateTimeWithTimeZone},
+    *,
+};
+use sea_query::{Alias, Expr, Func, SimpleExpr};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    pagination::paginate, shipment::SHIPPED, staff::StaffService, ListQueryParams, ListResult,
+    SortOrder, Sortable,
+};
+pub struct AccountingPeriodService;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClosePeriodParams {
+    // 结账月份中的任意一天
+    pub month: Date,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct ReopenPeriodParams {
+    #[validate(length(min = 1, message = "reason must not be empty"))]
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountingPeriod {
+    #[serde(flatten)]
+    pub period: Model,
+    // 结账和反结账记录
+    pub events: Vec<accounting_period_event::Model>,
+}
+
+// 东八区当天零点
+fn start_of_day(date: Date) -> Result<DateTimeWithTimeZone, DbErr> {
+    FixedOffset::east_opt(8 * 60 * 60)
+        .and_then(|offset| {
+            date.and_time(NaiveTime::MIN)
+                .and_local_timezone(offset)
+                .single()
+        })
+        .ok_or(DbErr::Custom("invalid date".into()))
+}
+
+// 东八区的日期
+pub(crate) fn local_date(time: DateTimeWithTimeZone) -> Date {
+    FixedOffset::east_opt(8 * 60 * 60)
+        .map(|offset| time.with_timezone(&offset).date_naive())
+        .unwrap_or(time.date_naive())
+}
+
+// 月份的起止时间, 不包含结束时间
+fn month_range(month: Date) -> Result<(DateTimeWithTimeZone, DateTimeWithTimeZone), DbErr> {
+    let start = month
+        .with_day(1)
+        .ok_or(DbErr::Custom("invalid month".into()))?;
+    let end = start
+        .checked_add_months(Months::new(1))
+        .ok_or(DbErr::Custom("invalid month".into()))?;
+    Ok((start_of_day(start)?, start_of_day(end)?))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountingPeriodListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+}
+
+impl Sortable for AccountingPeriodListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::StartTime];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+impl AccountingPeriodService {
+    async fn find_model(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("accounting period not found".into()))
+    }
+
+    // time 所在的期间是否已结账
+    pub(crate) async fn is_closed<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        time: DateTimeWithTimeZone,
+    ) -> Result<bool, DbErr> {
+        let closed = Entity::find()
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .filter(Column::Closed.eq(true))
+            .filter(Column::StartTime.lte(time))
+            .filter(Column::EndTime.gt(time))
+            .count(db)
+            .await?;
+        Ok(closed > 0)
+    }
+
+    // 已结账期间内的单据不能再修改或删除
+    pub(crate) async fn check_open<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        time: DateTimeWithTimeZone,
+    ) -> Result<(), DbErr> {
+        if Self::is_closed(db, workshop_id, time).await? {
+            return Err(DbErr::Custom("accounting period has been closed".into()));
+        }
+        Ok(())
+    }
+
+    // 出勤等只有日期的单据按当天零点判断
+    pub(crate) async fn check_date_open<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        date: Date,
+    ) -> Result<(), DbErr> {
+        Self::check_open(db, workshop_id, start_of_day(date)?).await
+    }
+
+    // 已结账期间的条件, 没有已结账期间时返回 None
+    async fn closed_condition<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        range: impl Fn(&Model) -> Condition,
+    ) -> Result<Option<Condition>, DbErr> {
+        let periods = Entity::find()
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .filter(Column::Closed.eq(true))
+            .all(db)
+            .await?;
+        if periods.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            periods.iter().fold(Condition::any(), |condition, period| {
+                condition.add(range(period))
+            }),
+        ))
+    }
+
+    async fn check_none_closed<C, E>(
+        db: &C,
+        select: Select<E>,
+        condition: Option<Condition>,
+    ) -> Result<(), DbErr>
+    where
+        C: ConnectionTrait,
+        E: EntityTrait,
+        E::Model: Sync,
+    {
+        let Some(condition) = condition else {
+            return Ok(());
+        };
+        if select.filter(condition).count(db).await? > 0 {
+            return Err(DbErr::Custom(
+                "cannot delete records of a closed accounting period".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    // 删除老板, 服装, 员工或工坊会级联删除单据, 有单据在已结账期间时不能删除
+    pub(crate) async fn check_cascade_open<C, E>(
+        db: &C,
+        workshop_id: i32,
+        select: Select<E>,
+        time: impl ColumnTrait,
+    ) -> Result<(), DbErr>
+    where
+        C: ConnectionTrait,
+        E: EntityTrait,
+        E::Model: Sync,
+    {
+        let condition = Self::closed_condition(db, workshop_id, |period| {
+            Condition::all()
+                .add(time.gte(period.start_time))
+                .add(time.lt(period.end_time))
+        })
+        .await?;
+        Self::check_none_closed(db, select, condition).await
+    }
+
+    // 出勤等只有日期的单据
+    pub(crate) async fn check_cascade_date_open<C, E>(
+        db: &C,
+        workshop_id: i32,
+        select: Select<E>,
+        date: impl ColumnTrait,
+    ) -> Result<(), DbErr>
+    where
+        C: ConnectionTrait,
+        E: EntityTrait,
+        E::Model: Sync,
+    {
+        let condition = Self::closed_condition(db, workshop_id, |period| {
+            Condition::all()
+                .add(date.gte(local_date(period.start_time)))
+                .add(date.lt(local_date(period.end_time)))
+        })
+        .await?;
+        Self::check_none_closed(db, select, condition).await
+    }
+
+    async fn total<E: EntityTrait>(
+        db: &DbConn,
+        select: Select<E>,
+        amount: SimpleExpr,
+    ) -> Result<f32, DbErr> {
+        Ok(select
+            .column_as(amount.cast_as(Alias::new("real")), "amount")
+            .into_tuple::<Option<f32>>()
+            .one(db)
+            .await?
+            .flatten()
+            .unwrap_or_default())
+    }
+
+    // 结账时的汇总: [收入, 计件工资, 计时工资, 进货成本]
+    async fn snapshot(
+        db: &DbConn,
+        workshop_id: i32,
+        start_time: DateTimeWithTimeZone,
+        end_time: DateTimeWithTimeZone,
+    ) -> Result<[f32; 4], DbErr> {
+        let revenue = shipment_item::Entity::find()
+            .select_only()
+            .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+            .join(JoinType::InnerJoin, shipment_item::Relation::Clothing.def())
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .filter(shipment::Column::Status.is_in(SHIPPED))
+            .filter(shipment::Column::DispatchedAt.gte(start_time))
+            .filter(shipment::Column::DispatchedAt.lt(end_time));
+        let piece_salary = production::Entity::find()
+            .select_only()
+            .filter(
+                production::Column::StaffId
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .filter(production::Column::CreateAt.gte(start_time))
+            .filter(production::Column::CreateAt.lt(end_time));
+        let day_salary = attendance::Entity::find()
+            .select_only()
+            .filter(
+                attendance::Column::StaffId
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .filter(attendance::Column::WorkDate.gte(start_time.date_naive()))
+            .filter(attendance::Column::WorkDate.lt(end_time.date_naive()));
+        let procurement_cost = procurement::Entity::find()
+            .select_only()
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
+            .filter(procurement::Column::CreateAt.gte(start_time))
+            .filter(procurement::Column::CreateAt.lt(end_time));
+
+        Ok([
+            Self::total(
+                db,
+                revenue,
+                Func::sum(
+                    Expr::col((shipment_item::Entity, shipment_item::Column::Amount))
+                        .mul(Expr::col((clothing::Entity, clothing::Column::Price))),
+                )
+                .into(),
+            )
+            .await?,
+            Self::total(
+                db,
+                piece_salary,
+                Expr::col(production::Column::TotalSalary).sum(),
+            )
+            .await?,
+            Self::total(db, day_salary, Expr::col(attendance::Column::DayRate).sum()).await?,
+            Self::total(
+                db,
+                procurement_cost,
+                Expr::col(procurement::Column::TotalCost).sum(),
+            )
+            .await?,
+        ])
+    }
+
+    // 结账, 已反结账的期间重新结账时更新汇总数据
+    pub async fn close(
+        db: &DbConn,
+        workshop_id: i32,
+        user_id: i32,
+        params: ClosePeriodParams,
+    ) -> Result<AccountingPeriod, DbErr> {
+        let (start_time, end_time) = month_range(params.month)?;
+        if end_time.with_timezone(&Utc) > Utc::now() {
+            return Err(DbErr::Custom("accounting period has not ended".into()));
+        }
+
+        let period = Entity::find()
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .filter(Column::StartTime.eq(start_time))
+            .one(db)
+            .await?;
+        if period.as_ref().is_some_and(|period| period.closed) {
+            return Err(DbErr::Custom("accounting period has been closed".into()));
+        }
+
+        let [revenue, piece_salary, day_salary, procurement_cost] =
+            Self::snapshot(db, workshop_id, start_time, end_time).await?;
+
+        let txn = db.begin().await?;
+
+        let mut period = match period {
+            Some(period) => period.into_active_model(),
+            None => ActiveModel {
+                start_time: Set(start_time),
+                end_time: Set(end_time),
+                workshop_id: Set(workshop_id),
+                ..Default::default()
+            },
+        };
+        period.closed = Set(true);
+        period.revenue = Set(revenue);
+        period.piece_salary = Set(piece_salary);
+        period.day_salary = Set(day_salary);
+        period.procurement_cost = Set(procurement_cost);
+        let period = period.save(&txn).await?.try_into_model()?;
+
+        let event = accounting_period_event::ActiveModel {
+            action: Set(PeriodAction::Close),
+            accounting_period_id: Set(period.id),
+            user_id: Set(Some(user_id)),
+            ..Default::default()
+        };
+        event.insert(&txn).await?;
+
+        txn.commit().await?;
+
+        Self::find_by_id(db, workshop_id, period.id).await
+    }
+
+    // 反结账, 必须填写原因
+    pub async fn reopen(
+        db: &DbConn,
+        workshop_id: i32,
+        user_id: i32,
+        id: i32,
+        params: ReopenPeriodParams,
+    ) -> Result<AccountingPeriod, DbErr> {
+        let period = Self::find_model(db, workshop_id, id).await?;
+        if !period.closed {
+            return Err(DbErr::Custom("accounting period is not closed".into()));
+        }
+
+        let txn = db.begin().await?;
+
+        let mut period = period.into_active_model();
+        period.closed = Set(false);
+        let period = period.update(&txn).await?;
+
+        let event = accounting_period_event::ActiveModel {
+            action: Set(PeriodAction::Reopen),
+            reason: Set(Some(params.reason)),
+            accounting_period_id: Set(period.id),
+            user_id: Set(Some(user_id)),
+            ..Default::default()
+        };
+        event.insert(&txn).await?;
+
+        txn.commit().await?;
+
+        Self::find_by_id(db, workshop_id, period.id).await
+    }
+
+    pub async fn find_by_id(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+    ) -> Result<AccountingPeriod, DbErr> {
+        let period = Self::find_model(db, workshop_id, id).await?;
+
+        let events = accounting_period_event::Entity::find()
+            .filter(accounting_period_event::Column::AccountingPeriodId.eq(period.id))
+            .order_by_asc(accounting_period_event::Column::CreateAt)
+            .order_by_asc(accounting_period_event::Column::Id)
+            .all(db)
+            .await?;
+
+        Ok(AccountingPeriod { period, events })
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(Column::WorkshopId.eq(workshop_id));
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::StartTime.gte(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::EndTime.lte(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            AccountingPeriodListQueryParams::SORTABLE,
+            (Column::StartTime, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+}
@@ -0,0 +1,1292 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ::entity::{
+    attendance, boss, clothing, inspection, procurement, procurement_item, production,
+    production_item, sea_orm_active_enums::ProductionKind, shipment, shipment_item,
+    shipment_return, shipment_return_item, staff,
+};
+use chrono::{Datelike, Months, Utc};
+use sea_orm::{
+    prelude::{Date, DateTimeWithTimeZone},
+    *,
+};
+use sea_query::{Alias, Expr, Func, IntoColumnRef, SimpleExpr};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{accounting_period::local_date, shipment::SHIPPED, staff::StaffService};
+pub struct AnalyticsService;
+
+// 统计时间段使用的时区
+const TIME_ZONE: &str = "Asia/Shanghai";
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RankBy {
+    #[default]
+    Count,
+    Salary,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastSource {
+    // 出货量
+    #[default]
+    Shipment,
+    // 进货量
+    Procurement,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastGroupBy {
+    #[default]
+    Clothing,
+    Boss,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductivityQueryParams {
+    pub start_time: Option<DateTimeWithTimeZone>,
+    pub end_time: Option<DateTimeWithTimeZone>,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub staff_ids: Option<Vec<i32>>,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub clothing_ids: Option<Vec<i32>>,
+
+    #[serde(default)]
+    pub rank_by: RankBy,
+
+    #[serde(default)]
+    pub period: Period,
+}
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastQueryParams {
+    #[serde(default)]
+    pub source: ForecastSource,
+
+    #[serde(default)]
+    pub group_by: ForecastGroupBy,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub boss_ids: Option<Vec<i32>>,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub clothing_ids: Option<Vec<i32>>,
+
+    // 预测未来几个月, 默认3个月
+    #[serde(
+        default = "crate::utils::default_horizon",
+        deserialize_with = "crate::utils::parse_option_u64"
+    )]
+    #[validate(range(min = 1, max = 24, message = "horizon must be between 1 and 24"))]
+    pub horizon: Option<u64>,
+
+    // 指数平滑系数, 越大越看重最近的数据
+    #[validate(range(
+        exclusive_min = 0.0,
+        max = 1.0,
+        message = "alpha must be greater than 0 and at most 1"
+    ))]
+    pub alpha: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitQueryParams {
+    pub start_time: Option<DateTimeWithTimeZone>,
+    pub end_time: Option<DateTimeWithTimeZone>,
+
+    #[serde(default)]
+    pub period: Period,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DefectQueryParams {
+    pub start_time: Option<DateTimeWithTimeZone>,
+    pub end_time: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct StaffOutput {
+    staff_id: i32,
+    staff_name: String,
+    count: i64,
+    salary: f32,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ClothingStaffOutput {
+    clothing_id: i32,
+    clothing_name: String,
+    staff_id: i32,
+    staff_name: String,
+    count: i64,
+    salary: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffRank {
+    pub rank: usize,
+    pub staff_id: i32,
+    pub staff_name: String,
+    pub count: i64,
+    pub salary: f32,
+    // 与车间平均水平的比值, 1.0 为平均
+    pub count_ratio: f64,
+    pub salary_ratio: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffRanking {
+    pub average_count: f64,
+    pub average_salary: f64,
+    pub items: Vec<StaffRank>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClothingRanking {
+    pub clothing_id: i32,
+    pub clothing_name: String,
+    #[serde(flatten)]
+    pub ranking: StaffRanking,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct ProductivityTrend {
+    pub period: DateTimeWithTimeZone,
+    pub staff_id: i32,
+    pub count: i64,
+    pub salary: f32,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct VolumeOutput {
+    period: DateTimeWithTimeZone,
+    key_id: i32,
+    quantity: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+pub struct ProfitAmount {
+    // 出货收入 = 出货数量 * 服装单价
+    pub revenue: f32,
+    // 人工成本 = 计件工资 + 计日工资
+    pub labour_cost: f32,
+    // 进货成本, 包括服装和物料
+    pub procurement_cost: f32,
+    pub profit: f32,
+    // 利润率, 没有收入时为 0
+    pub margin: f64,
+}
+
+impl ProfitAmount {
+    fn new(revenue: f32, labour_cost: f32, procurement_cost: f32) -> Self {
+        let profit = revenue - labour_cost - procurement_cost;
+        ProfitAmount {
+            revenue,
+            labour_cost,
+            procurement_cost,
+            profit,
+            margin: if revenue > 0.0 {
+                profit as f64 / revenue as f64
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfitPoint {
+    pub period: DateTimeWithTimeZone,
+    #[serde(flatten)]
+    pub amount: ProfitAmount,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Profit {
+    #[serde(flatten)]
+    pub total: ProfitAmount,
+    pub items: Vec<ProfitPoint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffDefect {
+    pub staff_id: i32,
+    pub staff_name: String,
+    // 正常生产的件数, 不含返工
+    pub produced: i64,
+    pub returned: i64,
+    // 次品率 = 退货数量 / 生产数量
+    pub defect_rate: f64,
+    pub deduction: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClothingDefect {
+    pub clothing_id: i32,
+    pub clothing_name: String,
+    pub shipped: i64,
+    pub returned: i64,
+    // 次品率 = 退货数量 / 出货数量
+    pub defect_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Defects {
+    pub staffs: Vec<StaffDefect>,
+    pub clothings: Vec<ClothingDefect>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffQuality {
+    pub staff_id: i32,
+    pub staff_name: String,
+    pub passed: i64,
+    pub rejected: i64,
+    // 次品率 = 次品数 / 质检件数
+    pub defect_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClothingQuality {
+    pub clothing_id: i32,
+    pub clothing_name: String,
+    pub passed: i64,
+    pub rejected: i64,
+    pub defect_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DefectReason {
+    pub reason: String,
+    pub rejected: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Quality {
+    pub staffs: Vec<StaffQuality>,
+    pub clothings: Vec<ClothingQuality>,
+    pub reasons: Vec<DefectReason>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub struct ForecastPoint {
+    // 月份的第一天
+    pub period: Date,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Forecast {
+    // 服装或老板id, 取决于 group_by
+    pub id: i32,
+    pub name: String,
+    pub history: Vec<ForecastPoint>,
+    pub projection: Vec<ForecastPoint>,
+    // 去除季节因素后的平滑水平
+    pub level: f64,
+    // 1-12月的季节系数, 1.0 表示与平均持平
+    pub seasonal: Vec<f64>,
+}
+
+impl AnalyticsService {
+    // 当前工坊的生产明细, 已关联生产单和员工
+    fn production_items(
+        workshop_id: i32,
+        params: &ProductivityQueryParams,
+    ) -> Select<production_item::Entity> {
+        let mut select = production_item::Entity::find()
+            .select_only()
+            .join(
+                JoinType::InnerJoin,
+                production_item::Relation::Production.def(),
+            )
+            .join(JoinType::InnerJoin, production::Relation::Staff.def())
+            .filter(
+                production::Column::StaffId.in_subquery(StaffService::workshop_staff_ids(
+                    workshop_id,
+                    params.staff_ids.clone(),
+                )),
+            );
+
+        if let Some(clothing_ids) = params.clothing_ids.clone() {
+            select = select.filter(production_item::Column::ClothingId.is_in(clothing_ids));
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(production::Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(production::Column::CreateAt.lt(end_time));
+        }
+
+        select
+            .column_as(
+                Expr::col((production_item::Entity, production_item::Column::Count)).sum(),
+                "count",
+            )
+            .column_as(
+                Expr::col((production_item::Entity, production_item::Column::Salary)).sum(),
+                "salary",
+            )
+    }
+
+    fn date_trunc<C: IntoColumnRef>(unit: &str, column: C) -> SimpleExpr {
+        Self::date_trunc_expr(unit, Expr::col(column).into())
+    }
+
+    // 和结账期间一样按东八区划分日/周/月, 不受数据库会话时区影响
+    fn date_trunc_expr(unit: &str, expr: SimpleExpr) -> SimpleExpr {
+        Func::cust(Alias::new("date_trunc"))
+            .arg(unit)
+            .arg(expr)
+            .arg(TIME_ZONE)
+            .into()
+    }
+
+    fn rank(mut rows: Vec<StaffOutput>, rank_by: RankBy) -> StaffRanking {
+        match rank_by {
+            RankBy::Count => rows.sort_by_key(|r| std::cmp::Reverse(r.count)),
+            RankBy::Salary => rows.sort_by(|a, b| b.salary.total_cmp(&a.salary)),
+        }
+
+        let len = rows.len().max(1) as f64;
+        let average_count = rows.iter().map(|r| r.count as f64).sum::<f64>() / len;
+        let average_salary = rows.iter().map(|r| r.salary as f64).sum::<f64>() / len;
+        let ratio = |value: f64, average: f64| {
+            if average > 0.0 {
+                value / average
+            } else {
+                0.0
+            }
+        };
+
+        let items = rows
+            .into_iter()
+            .enumerate()
+            .map(|(index, row)| StaffRank {
+                rank: index + 1,
+                count_ratio: ratio(row.count as f64, average_count),
+                salary_ratio: ratio(row.salary as f64, average_salary),
+                staff_id: row.staff_id,
+                staff_name: row.staff_name,
+                count: row.count,
+                salary: row.salary,
+            })
+            .collect();
+
+        StaffRanking {
+            average_count,
+            average_salary,
+            items,
+        }
+    }
+
+    // 员工产量排行
+    pub async fn staff_ranking(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ProductivityQueryParams,
+    ) -> Result<StaffRanking, DbErr> {
+        let rows = Self::production_items(workshop_id, &params)
+            .column_as(staff::Column::Id, "staff_id")
+            .column_as(staff::Column::Name, "staff_name")
+            .group_by(staff::Column::Id)
+            .group_by(staff::Column::Name)
+            .into_model::<StaffOutput>()
+            .all(db)
+            .await?;
+
+        Ok(Self::rank(rows, params.rank_by))
+    }
+
+    // 每件服装的员工产量排行
+    pub async fn clothing_ranking(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ProductivityQueryParams,
+    ) -> Result<Vec<ClothingRanking>, DbErr> {
+        let rows = Self::production_items(workshop_id, &params)
+            .join(
+                JoinType::InnerJoin,
+                production_item::Relation::Clothing.def(),
+            )
+            .column_as(clothing::Column::Id, "clothing_id")
+            .column_as(clothing::Column::Name, "clothing_name")
+            .column_as(staff::Column::Id, "staff_id")
+            .column_as(staff::Column::Name, "staff_name")
+            .group_by(clothing::Column::Id)
+            .group_by(clothing::Column::Name)
+            .group_by(staff::Column::Id)
+            .group_by(staff::Column::Name)
+            .order_by_asc(clothing::Column::Id)
+            .into_model::<ClothingStaffOutput>()
+            .all(db)
+            .await?;
+
+        let mut groups: Vec<(i32, String, Vec<StaffOutput>)> = vec![];
+        for row in rows {
+            let output = StaffOutput {
+                staff_id: row.staff_id,
+                staff_name: row.staff_name,
+                count: row.count,
+                salary: row.salary,
+            };
+            match groups.last_mut() {
+                Some((id, _, items)) if *id == row.clothing_id => items.push(output),
+                _ => groups.push((row.clothing_id, row.clothing_name, vec![output])),
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|(clothing_id, clothing_name, rows)| ClothingRanking {
+                clothing_id,
+                clothing_name,
+                ranking: Self::rank(rows, params.rank_by),
+            })
+            .collect())
+    }
+
+    // 按日/周/月统计员工产量
+    pub async fn trend(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ProductivityQueryParams,
+    ) -> Result<Vec<ProductivityTrend>, DbErr> {
+        let period = Self::date_trunc(
+            params.period.as_str(),
+            (production::Entity, production::Column::CreateAt),
+        );
+
+        // 按输出列别名分组, 避免 select 和 group by 中的参数被视为不同表达式
+        Self::production_items(workshop_id, &params)
+            .column_as(period, "period")
+            .column_as(staff::Column::Id, "staff_id")
+            .group_by(Expr::col(Alias::new("period")))
+            .group_by(staff::Column::Id)
+            .order_by_asc(Expr::col(Alias::new("period")))
+            .order_by_asc(staff::Column::Id)
+            .into_model::<ProductivityTrend>()
+            .all(db)
+            .await
+    }
+
+    fn volumes<E: EntityTrait>(select: Select<E>, params: &ForecastQueryParams) -> Select<E> {
+        let key = match params.group_by {
+            ForecastGroupBy::Clothing => clothing::Column::Id,
+            ForecastGroupBy::Boss => clothing::Column::BossId,
+        };
+
+        let mut select = select.column_as(key, "key_id").group_by(key);
+
+        if let Some(boss_ids) = params.boss_ids.clone() {
+            select = select.filter(clothing::Column::BossId.is_in(boss_ids));
+        }
+
+        if let Some(clothing_ids) = params.clothing_ids.clone() {
+            select = select.filter(clothing::Column::Id.is_in(clothing_ids));
+        }
+
+        select
+            .group_by(Expr::col(Alias::new("period")))
+            .order_by_asc(Expr::col(Alias::new("period")))
+    }
+
+    // 按时间段汇总的金额, 统一转换为 real
+    async fn amounts<E: EntityTrait>(
+        db: &DbConn,
+        select: Select<E>,
+        period: SimpleExpr,
+        amount: SimpleExpr,
+    ) -> Result<Vec<(DateTimeWithTimeZone, f32)>, DbErr> {
+        select
+            .column_as(period, "period")
+            .column_as(amount.cast_as(Alias::new("real")), "amount")
+            .group_by(Expr::col(Alias::new("period")))
+            .into_tuple::<(DateTimeWithTimeZone, f32)>()
+            .all(db)
+            .await
+    }
+
+    // 按日/周/月统计收入, 人工成本和进货成本
+    pub async fn profit(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ProfitQueryParams,
+    ) -> Result<Profit, DbErr> {
+        let unit = params.period.as_str();
+
+        let mut revenue = shipment_item::Entity::find()
+            .select_only()
+            .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+            .join(JoinType::InnerJoin, shipment_item::Relation::Clothing.def())
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .filter(shipment::Column::Status.is_in(SHIPPED));
+        let mut piece_salary = production::Entity::find().select_only().filter(
+            production::Column::StaffId
+                .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+        );
+        let mut day_salary = attendance::Entity::find().select_only().filter(
+            attendance::Column::StaffId
+                .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+        );
+        let mut procurement_cost = procurement::Entity::find()
+            .select_only()
+            .filter(procurement::Column::WorkshopId.eq(workshop_id));
+
+        if let Some(start_time) = params.start_time {
+            revenue = revenue.filter(shipment::Column::DispatchedAt.gt(start_time));
+            piece_salary = piece_salary.filter(production::Column::CreateAt.gt(start_time));
+            day_salary =
+                day_salary.filter(attendance::Column::WorkDate.gte(start_time.date_naive()));
+            procurement_cost =
+                procurement_cost.filter(procurement::Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            revenue = revenue.filter(shipment::Column::DispatchedAt.lt(end_time));
+            piece_salary = piece_salary.filter(production::Column::CreateAt.lt(end_time));
+            day_salary = day_salary.filter(attendance::Column::WorkDate.lte(end_time.date_naive()));
+            procurement_cost = procurement_cost.filter(procurement::Column::CreateAt.lt(end_time));
+        }
+
+        let revenue = Self::amounts(
+            db,
+            revenue,
+            Self::date_trunc(unit, (shipment::Entity, shipment::Column::DispatchedAt)),
+            Func::sum(
+                Expr::col((shipment_item::Entity, shipment_item::Column::Amount))
+                    .mul(Expr::col((clothing::Entity, clothing::Column::Price))),
+            )
+            .into(),
+        )
+        .await?;
+        let piece_salary = Self::amounts(
+            db,
+            piece_salary,
+            Self::date_trunc(unit, production::Column::CreateAt),
+            Expr::col(production::Column::TotalSalary).sum(),
+        )
+        .await?;
+        // 出勤日期没有时区, 按当天零点计入
+        let day_salary = Self::amounts(
+            db,
+            day_salary,
+            Self::date_trunc_expr(
+                unit,
+                Expr::col(attendance::Column::WorkDate)
+                    .cast_as(Alias::new("timestamp with time zone")),
+            ),
+            Expr::col(attendance::Column::DayRate).sum(),
+        )
+        .await?;
+        let procurement_cost = Self::amounts(
+            db,
+            procurement_cost,
+            Self::date_trunc(unit, procurement::Column::CreateAt),
+            Expr::col(procurement::Column::TotalCost).sum(),
+        )
+        .await?;
+
+        // [收入, 人工成本, 进货成本]
+        let mut periods: BTreeMap<DateTimeWithTimeZone, [f32; 3]> = BTreeMap::new();
+        for (rows, column) in [
+            (revenue, 0),
+            (piece_salary, 1),
+            (day_salary, 1),
+            (procurement_cost, 2),
+        ] {
+            for (period, amount) in rows {
+                periods.entry(period).or_default()[column] += amount;
+            }
+        }
+
+        let items = periods
+            .into_iter()
+            .map(
+                |(period, [revenue, labour_cost, procurement_cost])| ProfitPoint {
+                    period,
+                    amount: ProfitAmount::new(revenue, labour_cost, procurement_cost),
+                },
+            )
+            .collect::<Vec<_>>();
+        let total = items.iter().fold([0.0; 3], |[r, l, p], item| {
+            [
+                r + item.amount.revenue,
+                l + item.amount.labour_cost,
+                p + item.amount.procurement_cost,
+            ]
+        });
+
+        Ok(Profit {
+            total: ProfitAmount::new(total[0], total[1], total[2]),
+            items,
+        })
+    }
+
+    // 按月统计的历史出货量/进货量, 并预测未来几个月
+    pub async fn forecast(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ForecastQueryParams,
+    ) -> Result<Vec<Forecast>, DbErr> {
+        let rows = match params.source {
+            ForecastSource::Shipment => {
+                let select = shipment_item::Entity::find()
+                    .select_only()
+                    .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+                    .join(JoinType::InnerJoin, shipment_item::Relation::Clothing.def())
+                    .column_as(
+                        Self::date_trunc(
+                            "month",
+                            (shipment::Entity, shipment::Column::DispatchedAt),
+                        ),
+                        "period",
+                    )
+                    .column_as(
+                        Expr::col((shipment_item::Entity, shipment_item::Column::Amount)).sum(),
+                        "quantity",
+                    )
+                    .filter(shipment::Column::WorkshopId.eq(workshop_id))
+                    .filter(shipment::Column::Status.is_in(SHIPPED));
+
+                Self::volumes(select, &params)
+                    .into_model::<VolumeOutput>()
+                    .all(db)
+                    .await?
+            }
+            ForecastSource::Procurement => {
+                let select = procurement_item::Entity::find()
+                    .select_only()
+                    .join(
+                        JoinType::InnerJoin,
+                        procurement_item::Relation::Procurement.def(),
+                    )
+                    .join(
+                        JoinType::InnerJoin,
+                        procurement_item::Relation::Clothing.def(),
+                    )
+                    .column_as(
+                        Self::date_trunc(
+                            "month",
+                            (procurement::Entity, procurement::Column::CreateAt),
+                        ),
+                        "period",
+                    )
+                    .column_as(
+                        Expr::col((procurement_item::Entity, procurement_item::Column::Amount))
+                            .sum(),
+                        "quantity",
+                    )
+                    .filter(procurement::Column::WorkshopId.eq(workshop_id));
+
+                Self::volumes(select, &params)
+                    .into_model::<VolumeOutput>()
+                    .all(db)
+                    .await?
+            }
+        };
+
+        let mut series: HashMap<i32, Vec<ForecastPoint>> = HashMap::new();
+        for row in rows {
+            series.entry(row.key_id).or_default().push(ForecastPoint {
+                period: local_date(row.period),
+                quantity: row.quantity as f64,
+            });
+        }
+
+        let ids = series.keys().copied().collect::<Vec<_>>();
+        let names: HashMap<i32, String> = match params.group_by {
+            ForecastGroupBy::Clothing => clothing::Entity::find()
+                .filter(clothing::Column::Id.is_in(ids))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|c| (c.id, c.name))
+                .collect(),
+            ForecastGroupBy::Boss => boss::Entity::find()
+                .filter(boss::Column::Id.is_in(ids))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|b| (b.id, b.name))
+                .collect(),
+        };
+
+        let alpha = params.alpha.unwrap_or(0.5);
+        let horizon = params.horizon.unwrap_or(3) as usize;
+        // 最近没有数据的月份记为0, 预测从下个月开始
+        let this_month = local_date(Utc::now().fixed_offset())
+            .with_day(1)
+            .ok_or(DbErr::Custom("invalid date".into()))?;
+
+        let mut forecasts = series
+            .into_iter()
+            .map(|(id, points)| {
+                let history = fill_months(points, this_month);
+                let seasonal = seasonal_factors(&history);
+                let level = smoothed_level(&history, &seasonal, alpha);
+                let projection = project(&history, &seasonal, level, horizon);
+                Forecast {
+                    id,
+                    name: names.get(&id).cloned().unwrap_or_default(),
+                    history,
+                    projection,
+                    level,
+                    seasonal: seasonal.to_vec(),
+                }
+            })
+            .collect::<Vec<_>>();
+        forecasts.sort_by_key(|f| f.id);
+
+        Ok(forecasts)
+    }
+
+    // 按员工和服装统计退货次品
+    pub async fn defects(
+        db: &DbConn,
+        workshop_id: i32,
+        params: DefectQueryParams,
+    ) -> Result<Defects, DbErr> {
+        let mut returned = shipment_return_item::Entity::find()
+            .select_only()
+            .join(
+                JoinType::InnerJoin,
+                shipment_return_item::Relation::ShipmentReturn.def(),
+            )
+            .filter(shipment_return::Column::WorkshopId.eq(workshop_id));
+        let mut produced = production_item::Entity::find()
+            .select_only()
+            .join(
+                JoinType::InnerJoin,
+                production_item::Relation::Production.def(),
+            )
+            .filter(
+                production::Column::StaffId
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .filter(production::Column::Kind.eq(ProductionKind::Normal));
+        let mut shipped = shipment_item::Entity::find()
+            .select_only()
+            .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .filter(shipment::Column::Status.is_in(SHIPPED));
+
+        if let Some(start_time) = params.start_time {
+            returned = returned.filter(shipment_return::Column::CreateAt.gt(start_time));
+            produced = produced.filter(production::Column::CreateAt.gt(start_time));
+            shipped = shipped.filter(shipment::Column::DispatchedAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            returned = returned.filter(shipment_return::Column::CreateAt.lt(end_time));
+            produced = produced.filter(production::Column::CreateAt.lt(end_time));
+            shipped = shipped.filter(shipment::Column::DispatchedAt.lt(end_time));
+        }
+
+        let returned_amount = Expr::col((
+            shipment_return_item::Entity,
+            shipment_return_item::Column::Amount,
+        ))
+        .sum();
+
+        let staff_returned: HashMap<i32, (i64, f32)> = returned
+            .clone()
+            .column(shipment_return_item::Column::StaffId)
+            .column_as(returned_amount.clone(), "returned")
+            .column_as(
+                Expr::col((
+                    shipment_return_item::Entity,
+                    shipment_return_item::Column::Deduction,
+                ))
+                .sum(),
+                "deduction",
+            )
+            .filter(shipment_return_item::Column::StaffId.is_not_null())
+            .group_by(shipment_return_item::Column::StaffId)
+            .into_tuple::<(i32, i64, f32)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|(staff_id, returned, deduction)| (staff_id, (returned, deduction)))
+            .collect();
+        let staff_produced: HashMap<i32, i64> = produced
+            .column(production::Column::StaffId)
+            .column_as(
+                Expr::col((production_item::Entity, production_item::Column::Count)).sum(),
+                "produced",
+            )
+            .group_by(production::Column::StaffId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        let clothing_returned: HashMap<i32, i64> = returned
+            .column(shipment_return_item::Column::ClothingId)
+            .column_as(returned_amount, "returned")
+            .group_by(shipment_return_item::Column::ClothingId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+        let clothing_shipped: HashMap<i32, i64> = shipped
+            .column(shipment_item::Column::ClothingId)
+            .column_as(
+                Expr::col((shipment_item::Entity, shipment_item::Column::Amount)).sum(),
+                "shipped",
+            )
+            .filter(shipment_item::Column::ClothingId.is_in(clothing_returned.keys().copied()))
+            .group_by(shipment_item::Column::ClothingId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        let rate = |returned: i64, total: i64| {
+            if total > 0 {
+                returned as f64 / total as f64
+            } else {
+                0.0
+            }
+        };
+
+        // 只返回有退货的员工和服装, 按退货数量从多到少排序
+        let mut staffs = staff::Entity::find()
+            .filter(staff::Column::Id.is_in(staff_returned.keys().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|staff| {
+                let (returned, deduction) =
+                    staff_returned.get(&staff.id).copied().unwrap_or_default();
+                let produced = staff_produced.get(&staff.id).copied().unwrap_or_default();
+                StaffDefect {
+                    staff_id: staff.id,
+                    staff_name: staff.name,
+                    produced,
+                    returned,
+                    defect_rate: rate(returned, produced),
+                    deduction,
+                }
+            })
+            .collect::<Vec<_>>();
+        staffs.sort_by_key(|s| (std::cmp::Reverse(s.returned), s.staff_id));
+
+        let mut clothings = clothing::Entity::find()
+            .filter(clothing::Column::Id.is_in(clothing_returned.keys().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|clothing| {
+                let returned = clothing_returned
+                    .get(&clothing.id)
+                    .copied()
+                    .unwrap_or_default();
+                let shipped = clothing_shipped
+                    .get(&clothing.id)
+                    .copied()
+                    .unwrap_or_default();
+                ClothingDefect {
+                    clothing_id: clothing.id,
+                    clothing_name: clothing.name,
+                    shipped,
+                    returned,
+                    defect_rate: rate(returned, shipped),
+                }
+            })
+            .collect::<Vec<_>>();
+        clothings.sort_by_key(|c| (std::cmp::Reverse(c.returned), c.clothing_id));
+
+        Ok(Defects { staffs, clothings })
+    }
+
+    // 按员工和服装统计质检次品率
+    pub async fn quality(
+        db: &DbConn,
+        workshop_id: i32,
+        params: DefectQueryParams,
+    ) -> Result<Quality, DbErr> {
+        let mut select = inspection::Entity::find()
+            .select_only()
+            .join(
+                JoinType::InnerJoin,
+                inspection::Relation::ProductionItem.def(),
+            )
+            .join(
+                JoinType::InnerJoin,
+                production_item::Relation::Production.def(),
+            )
+            .filter(inspection::Column::WorkshopId.eq(workshop_id));
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(inspection::Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(inspection::Column::CreateAt.lt(end_time));
+        }
+
+        let passed = Expr::col((inspection::Entity, inspection::Column::Passed)).sum();
+        let rejected = Expr::col((inspection::Entity, inspection::Column::Rejected)).sum();
+
+        let staff_counts: HashMap<i32, (i64, i64)> = select
+            .clone()
+            .column(production::Column::StaffId)
+            .column_as(passed.clone(), "passed")
+            .column_as(rejected.clone(), "rejected")
+            .group_by(production::Column::StaffId)
+            .into_tuple::<(i32, i64, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|(staff_id, passed, rejected)| (staff_id, (passed, rejected)))
+            .collect();
+        let clothing_counts: HashMap<i32, (i64, i64)> = select
+            .clone()
+            .column(production_item::Column::ClothingId)
+            .column_as(passed, "passed")
+            .column_as(rejected.clone(), "rejected")
+            .group_by(production_item::Column::ClothingId)
+            .into_tuple::<(i32, i64, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|(clothing_id, passed, rejected)| (clothing_id, (passed, rejected)))
+            .collect();
+        let mut reasons = select
+            .column(inspection::Column::Reason)
+            .column_as(rejected, "rejected")
+            .filter(inspection::Column::Reason.is_not_null())
+            .filter(inspection::Column::Rejected.gt(0))
+            .group_by(inspection::Column::Reason)
+            .into_tuple::<(String, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|(reason, rejected)| DefectReason { reason, rejected })
+            .collect::<Vec<_>>();
+        reasons.sort_by(|a, b| b.rejected.cmp(&a.rejected).then(a.reason.cmp(&b.reason)));
+
+        let rate = |passed: i64, rejected: i64| {
+            if passed + rejected > 0 {
+                rejected as f64 / (passed + rejected) as f64
+            } else {
+                0.0
+            }
+        };
+
+        // 按次品率从高到低排序, 便于找出需要培训的员工
+        let mut staffs = staff::Entity::find()
+            .filter(staff::Column::Id.is_in(staff_counts.keys().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|staff| {
+                let (passed, rejected) = staff_counts.get(&staff.id).copied().unwrap_or_default();
+                StaffQuality {
+                    staff_id: staff.id,
+                    staff_name: staff.name,
+                    passed,
+                    rejected,
+                    defect_rate: rate(passed, rejected),
+                }
+            })
+            .collect::<Vec<_>>();
+        staffs.sort_by(|a, b| {
+            b.defect_rate
+                .total_cmp(&a.defect_rate)
+                .then(a.staff_id.cmp(&b.staff_id))
+        });
+
+        let mut clothings = clothing::Entity::find()
+            .filter(clothing::Column::Id.is_in(clothing_counts.keys().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|clothing| {
+                let (passed, rejected) = clothing_counts
+                    .get(&clothing.id)
+                    .copied()
+                    .unwrap_or_default();
+                ClothingQuality {
+                    clothing_id: clothing.id,
+                    clothing_name: clothing.name,
+                    passed,
+                    rejected,
+                    defect_rate: rate(passed, rejected),
+                }
+            })
+            .collect::<Vec<_>>();
+        clothings.sort_by(|a, b| {
+            b.defect_rate
+                .total_cmp(&a.defect_rate)
+                .then(a.clothing_id.cmp(&b.clothing_id))
+        });
+
+        Ok(Quality {
+            staffs,
+            clothings,
+            reasons,
+        })
+    }
+}
+
+// 补齐没有数据的月份直到 until 所在的月份, 数量记为0
+fn fill_months(points: Vec<ForecastPoint>, until: Date) -> Vec<ForecastPoint> {
+    let mut filled: Vec<ForecastPoint> = Vec::with_capacity(points.len());
+    let pad = |filled: &mut Vec<ForecastPoint>, end: Date| {
+        if let Some(last) = filled.last().copied() {
+            let mut period = last.period + Months::new(1);
+            while period < end {
+                filled.push(ForecastPoint {
+                    period,
+                    quantity: 0.0,
+                });
+                period = period + Months::new(1);
+            }
+        }
+    };
+    for point in points {
+        pad(&mut filled, point.period);
+        filled.push(point);
+    }
+    if let Some(end) = until.checked_add_months(Months::new(1)) {
+        pad(&mut filled, end);
+    }
+    filled
+}
+
+// 季节系数: 某月的平均值 / 总平均值, 不满一年时不考虑季节
+fn seasonal_factors(history: &[ForecastPoint]) -> [f64; 12] {
+    let mut factors = [1.0; 12];
+    if history.len() < 12 {
+        return factors;
+    }
+
+    let average = history.iter().map(|p| p.quantity).sum::<f64>() / history.len() as f64;
+    if average <= 0.0 {
+        return factors;
+    }
+
+    let mut sums = [(0.0, 0usize); 12];
+    for point in history {
+        let month = point.period.month0() as usize;
+        sums[month].0 += point.quantity;
+        sums[month].1 += 1;
+    }
+
+    for (factor, (sum, count)) in factors.iter_mut().zip(sums) {
+        if count > 0 {
+            *factor = sum / count as f64 / average;
+        }
+    }
+    factors
+}
+
+// 对去除季节因素后的数据做简单指数平滑
+fn smoothed_level(history: &[ForecastPoint], seasonal: &[f64; 12], alpha: f64) -> f64 {
+    let deseasonalized = history.iter().map(|point| {
+        let factor = seasonal[point.period.month0() as usize];
+        if factor > 0.0 {
+            point.quantity / factor
+        } else {
+            point.quantity
+        }
+    });
+
+    deseasonalized
+        .reduce(|level, value| alpha * value + (1.0 - alpha) * level)
+        .unwrap_or_default()
+}
+
+fn project(
+    history: &[ForecastPoint],
+    seasonal: &[f64; 12],
+    level: f64,
+    horizon: usize,
+) -> Vec<ForecastPoint> {
+    let Some(last) = history.last() else {
+        return vec![];
+    };
+
+    (1..=horizon as u32)
+        .map_while(|offset| {
+            let period = last.period.checked_add_months(Months::new(offset))?;
+            Some(ForecastPoint {
+                period,
+                quantity: level * seasonal[period.month0() as usize],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(year: i32, month: u32) -> Date {
+        Date::from_ymd_opt(year, month, 1).unwrap()
+    }
+
+    fn point(period: Date, quantity: f64) -> ForecastPoint {
+        ForecastPoint { period, quantity }
+    }
+
+    #[test]
+    fn fill_months_pads_gaps_and_until() {
+        let filled = fill_months(
+            vec![point(month(2024, 1), 5.0), point(month(2024, 3), 7.0)],
+            month(2024, 5),
+        );
+        assert_eq!(
+            filled,
+            vec![
+                point(month(2024, 1), 5.0),
+                point(month(2024, 2), 0.0),
+                point(month(2024, 3), 7.0),
+                point(month(2024, 4), 0.0),
+                point(month(2024, 5), 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn fill_months_crosses_years() {
+        let filled = fill_months(vec![point(month(2023, 11), 1.0)], month(2024, 2));
+        let periods = filled.iter().map(|p| p.period).collect::<Vec<_>>();
+        assert_eq!(
+            periods,
+            vec![
+                month(2023, 11),
+                month(2023, 12),
+                month(2024, 1),
+                month(2024, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn fill_months_keeps_current_month_data() {
+        let filled = fill_months(vec![point(month(2024, 5), 3.0)], month(2024, 5));
+        assert_eq!(filled, vec![point(month(2024, 5), 3.0)]);
+        assert!(fill_months(vec![], month(2024, 5)).is_empty());
+    }
+
+    #[test]
+    fn seasonal_factors_need_a_full_year() {
+        let history = (1..=11)
+            .map(|m| point(month(2024, m), m as f64))
+            .collect::<Vec<_>>();
+        assert_eq!(seasonal_factors(&history), [1.0; 12]);
+    }
+
+    #[test]
+    fn seasonal_factors_compare_month_to_average() {
+        // 12月是其他月份的3倍, 总平均值为 (11 + 3) / 12
+        let history = (1..=12)
+            .map(|m| point(month(2024, m), if m == 12 { 3.0 } else { 1.0 }))
+            .collect::<Vec<_>>();
+        let factors = seasonal_factors(&history);
+        let average = 14.0 / 12.0;
+        assert!((factors[11] - 3.0 / average).abs() < 1e-9);
+        assert!((factors[0] - 1.0 / average).abs() < 1e-9);
+    }
+
+    #[test]
+    fn seasonal_factors_ignore_empty_history() {
+        let history = (1..=12)
+            .map(|m| point(month(2024, m), 0.0))
+            .collect::<Vec<_>>();
+        assert_eq!(seasonal_factors(&history), [1.0; 12]);
+    }
+
+    #[test]
+    fn smoothed_level_weights_recent_values() {
+        let history = vec![
+            point(month(2024, 1), 10.0),
+            point(month(2024, 2), 20.0),
+            point(month(2024, 3), 40.0),
+        ];
+        let seasonal = [1.0; 12];
+        // 10 -> 0.5 * 20 + 0.5 * 10 = 15 -> 0.5 * 40 + 0.5 * 15 = 27.5
+        assert_eq!(smoothed_level(&history, &seasonal, 0.5), 27.5);
+        assert_eq!(smoothed_level(&history, &seasonal, 1.0), 40.0);
+        assert_eq!(smoothed_level(&[], &seasonal, 0.5), 0.0);
+    }
+
+    #[test]
+    fn smoothed_level_removes_seasonality() {
+        let history = vec![point(month(2024, 1), 20.0)];
+        let mut seasonal = [1.0; 12];
+        seasonal[0] = 2.0;
+        assert_eq!(smoothed_level(&history, &seasonal, 0.5), 10.0);
+    }
+
+    #[test]
+    fn project_applies_seasonality_after_last_month() {
+        let history = vec![point(month(2024, 11), 10.0)];
+        let mut seasonal = [1.0; 12];
+        seasonal[0] = 1.5;
+        assert_eq!(
+            project(&history, &seasonal, 10.0, 3),
+            vec![
+                point(month(2024, 12), 10.0),
+                point(month(2025, 1), 15.0),
+                point(month(2025, 2), 10.0),
+            ]
+        );
+        assert!(project(&[], &seasonal, 10.0, 3).is_empty());
+        assert!(project(&history, &seasonal, 10.0, 0).is_empty());
+    }
+
+    #[test]
+    fn alpha_must_be_in_range() {
+        let params = |alpha: f64| ForecastQueryParams {
+            source: ForecastSource::default(),
+            group_by: ForecastGroupBy::default(),
+            boss_ids: None,
+            clothing_ids: None,
+            horizon: Some(3),
+            alpha: Some(alpha),
+        };
+        assert!(params(0.5).validate().is_ok());
+        assert!(params(1.0).validate().is_ok());
+        assert!(params(0.0).validate().is_err());
+        assert!(params(1.5).validate().is_err());
+        assert!(params(f64::NAN).validate().is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use ::entity::{
+    attendance::{ActiveModel, Column, Entity, Model},
+    staff,
+};
+use sea_orm::{
+    prelude::{Date, DateTimeWithTimeZone},
+    *,
+};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::{
+    accounting_period::AccountingPeriodService, pagination::paginate, staff::StaffService,
+    ListQueryParams, ListResult, SortOrder, Sortable,
+};
+pub struct AttendanceService;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[validate(schema(function = "validate_create_times"))]
+pub struct CreateAttendanceParams {
+    pub staff_id: i32,
+    pub work_date: Date,
+    pub check_in: Option<DateTimeWithTimeZone>,
+    pub check_out: Option<DateTimeWithTimeZone>,
+    #[validate(range(min = 0.0, message = "day_rate must not be negative"))]
+    pub day_rate: f32,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[validate(schema(function = "validate_update_times"))]
+pub struct UpdateAttendanceParams {
+    pub check_in: Option<DateTimeWithTimeZone>,
+    pub check_out: Option<DateTimeWithTimeZone>,
+    #[validate(range(min = 0.0, message = "day_rate must not be negative"))]
+    pub day_rate: Option<f32>,
+    pub description: Option<String>,
+}
+
+// 签到时间不能晚于签退时间
+fn check_times(
+    check_in: Option<DateTimeWithTimeZone>,
+    check_out: Option<DateTimeWithTimeZone>,
+) -> Result<(), ValidationError> {
+    match (check_in, check_out) {
+        (Some(check_in), Some(check_out)) if check_in > check_out => {
+            Err(ValidationError::new("check_out")
+                .with_message("check_in must not be later than check_out".into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_create_times(params: &CreateAttendanceParams) -> Result<(), ValidationError> {
+    check_times(params.check_in, params.check_out)
+}
+
+fn validate_update_times(params: &UpdateAttendanceParams) -> Result<(), ValidationError> {
+    check_times(params.check_in, params.check_out)
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub staff_ids: Option<Vec<i32>>,
+}
+
+impl Sortable for AttendanceListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::WorkDate, Column::DayRate, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceSheetQueryParams {
+    pub year: i32,
+    pub month: u32,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub staff_ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceSheet {
+    pub staff: staff::Model,
+    pub records: Vec<Model>,
+    // 出勤天数
+    pub days: usize,
+    pub total_wage: f32,
+}
+
+impl AttendanceService {
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        params: CreateAttendanceParams,
+    ) -> Result<Model, DbErr> {
+        let staff = StaffService::find_model(db, workshop_id, params.staff_id).await?;
+        AccountingPeriodService::check_date_open(db, workshop_id, params.work_date).await?;
+
+        // 同一员工同一天只能有一条考勤
+        let exists = Entity::find()
+            .filter(Column::StaffId.eq(staff.id))
+            .filter(Column::WorkDate.eq(params.work_date))
+            .one(db)
+            .await?;
+        if exists.is_some() {
+            return Err(DbErr::Custom(format!(
+                "attendance of staff {} on {} already exists",
+                staff.id, params.work_date
+            )));
+        }
+
+        let model = ActiveModel {
+            work_date: Set(params.work_date),
+            check_in: Set(params.check_in),
+            check_out: Set(params.check_out),
+            day_rate: Set(params.day_rate),
+            description: Set(params.description),
+            staff_id: Set(staff.id),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        AccountingPeriodService::check_date_open(db, workshop_id, model.work_date).await?;
+
+        let model_clone = model.clone();
+        model.delete(db).await?;
+        Ok(model_clone)
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateAttendanceParams,
+    ) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        AccountingPeriodService::check_date_open(db, workshop_id, model.work_date).await?;
+
+        // 只传了签到或签退时, 和原有的时间一起检查
+        check_times(
+            params.check_in.or(model.check_in),
+            params.check_out.or(model.check_out),
+        )
+        .map_err(|err| DbErr::Custom(err.to_string()))?;
+
+        let mut model = model.into_active_model();
+        if let Some(day_rate) = params.day_rate {
+            model.day_rate = Set(day_rate);
+        }
+        if let Some(check_in) = params.check_in {
+            model.check_in = Set(Some(check_in));
+        }
+        if let Some(check_out) = params.check_out {
+            model.check_out = Set(Some(check_out));
+        }
+        if let Some(description) = params.description {
+            model.description = Set(Some(description));
+        }
+
+        model.update(db).await
+    }
+
+    pub async fn settle(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let mut model = model.into_active_model();
+        model.settled = Set(true);
+
+        model.update(db).await
+    }
+
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(
+                Column::StaffId.in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("attendance not found".into()))
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+        staff_ids: Option<Vec<i32>>,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(
+            Column::StaffId.in_subquery(StaffService::workshop_staff_ids(workshop_id, staff_ids)),
+        );
+
+        if let Some(search) = &params.search {
+            select = select.filter(Column::Description.contains(search));
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::WorkDate.gte(start_time.date_naive()));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::WorkDate.lte(end_time.date_naive()));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            AttendanceListQueryParams::SORTABLE,
+            (Column::WorkDate, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+
+    // 月度考勤表, 每个员工一行
+    pub async fn monthly_sheet(
+        db: &DbConn,
+        workshop_id: i32,
+        year: i32,
+        month: u32,
+        staff_ids: Option<Vec<i32>>,
+    ) -> Result<Vec<AttendanceSheet>, DbErr> {
+        let first_day =
+            Date::from_ymd_opt(year, month, 1).ok_or(DbErr::Custom("invalid month".into()))?;
+        let next_month = if month == 12 {
+            Date::from_ymd_opt(year + 1, 1, 1)
+        } else {
+            Date::from_ymd_opt(year, month + 1, 1)
+        }
+        .ok_or(DbErr::Custom("invalid month".into()))?;
+
+        let staffs = staff::Entity::find()
+            .filter(
+                staff::Column::Id
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, staff_ids)),
+            )
+            .order_by_asc(staff::Column::Id)
+            .all(db)
+            .await?;
+
+        let records = Entity::find()
+            .filter(Column::StaffId.is_in(staffs.iter().map(|s| s.id)))
+            .filter(Column::WorkDate.gte(first_day))
+            .filter(Column::WorkDate.lt(next_month))
+            .order_by_asc(Column::WorkDate)
+            .all(db)
+            .await?;
+
+        let mut records_by_staff: HashMap<i32, Vec<Model>> = HashMap::new();
+        for record in records {
+            records_by_staff
+                .entry(record.staff_id)
+                .or_default()
+                .push(record);
+        }
+
+        Ok(staffs
+            .into_iter()
+            .map(|staff| {
+                let records = records_by_staff.remove(&staff.id).unwrap_or_default();
+                AttendanceSheet {
+                    days: records.len(),
+                    total_wage: records.iter().map(|r| r.day_rate).sum(),
+                    records,
+                    staff,
+                }
+            })
+            .collect())
+    }
+}
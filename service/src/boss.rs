@@ -1,12 +1,18 @@
 pub struct BossService;
-use crate::{ListQueryParams, ListResult};
+use crate::{
+    clothing::ClothingService, pagination::paginate, search::pinyin_initials, ListQueryParams,
+    ListResult, SortOrder, Sortable,
+};
 use ::entity::boss::{ActiveModel, Column, Entity, Model};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateBossParams {
+    #[validate(length(min = 1, max = 32, message = "name must be 1 to 32 characters"))]
     pub name: String,
+    #[validate(custom(function = "crate::utils::validate_phone_number"))]
     pub phone_number: String,
     pub description: Option<String>,
     pub address: Option<String>,
@@ -20,25 +26,50 @@ pub struct UpdateBossParams {
     pub address: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct BossListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+}
+
+impl Sortable for BossListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::Name, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
 impl BossService {
     pub async fn create(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         params: CreateBossParams,
     ) -> Result<Model, DbErr> {
         let model = ActiveModel {
+            pinyin: sea_orm::ActiveValue::Set(pinyin_initials(&params.name)),
             name: sea_orm::ActiveValue::Set(params.name),
             phone_number: sea_orm::ActiveValue::Set(params.phone_number),
             description: sea_orm::ActiveValue::Set(params.description),
             address: sea_orm::ActiveValue::Set(params.address),
-            user_id: sea_orm::ActiveValue::Set(user_id),
+            workshop_id: sea_orm::ActiveValue::Set(workshop_id),
             ..Default::default()
         };
         model.insert(db).await
     }
 
-    pub async fn delete(db: &DbConn, user_id: i32, id: i32) -> Result<Model, DbErr> {
-        let model = Self::find_by_id(db, user_id, id).await?;
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        ClothingService::check_cascade_open(
+            db,
+            workshop_id,
+            ClothingService::workshop_clothing_ids(workshop_id, Some(vec![model.id])),
+        )
+        .await?;
 
         let model_clone = model.clone();
         model.delete(db).await?;
@@ -47,14 +78,15 @@ impl BossService {
 
     pub async fn update(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         id: i32,
         params: UpdateBossParams,
     ) -> Result<Model, DbErr> {
-        let model = Self::find_by_id(db, user_id, id).await?;
+        let model = Self::find_by_id(db, workshop_id, id).await?;
 
         let mut model = model.into_active_model();
         if let Some(name) = params.name {
+            model.pinyin = sea_orm::ActiveValue::Set(pinyin_initials(&name));
             model.name = sea_orm::ActiveValue::Set(name);
         }
         if let Some(phone_number) = params.phone_number {
@@ -66,29 +98,31 @@ impl BossService {
         model.update(db).await
     }
 
-    pub async fn find_by_id(db: &DbConn, user_id: i32, id: i32) -> Result<Model, DbErr> {
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
         Entity::find_by_id(id)
-            .filter(Column::UserId.eq(user_id))
+            .filter(Column::WorkshopId.eq(workshop_id))
             .one(db)
             .await?
             .ok_or(DbErr::RecordNotFound("Cannot find boss".into()))
     }
 
-    // 查找用户关联的model
-    pub async fn find_by_user_id(
+    // 查找工坊关联的model
+    pub async fn find_by_workshop_id(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         params: ListQueryParams,
     ) -> Result<ListResult<Model>, DbErr> {
-        let mut select = Entity::find().order_by_desc(Column::CreateAt);
+        let mut select = Entity::find();
 
-        select = select.filter(Column::UserId.eq(user_id));
+        select = select.filter(Column::WorkshopId.eq(workshop_id));
 
-        if let Some(search) = params.search {
+        if let Some(search) = &params.search {
             select = select.filter(
                 Column::Name
-                    .contains(&search)
-                    .or(Column::Description.contains(&search)),
+                    .contains(search)
+                    .or(Column::Description.contains(search))
+                    .or(Column::PhoneNumber.contains(search))
+                    .or(Column::Pinyin.contains(search.to_lowercase())),
             );
         }
 
@@ -100,10 +134,14 @@ impl BossService {
             select = select.filter(Column::CreateAt.lt(end_time));
         }
 
-        let total = select.clone().count(db).await?;
-        let (page, page_size) = (params.page.unwrap(), params.page_size.unwrap());
-        let data = select.paginate(db, page_size).fetch_page(page).await?;
-
-        Ok(ListResult { total, data })
+        paginate(
+            db,
+            select,
+            &params,
+            BossListQueryParams::SORTABLE,
+            (Column::CreateAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
     }
 }
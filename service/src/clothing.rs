@@ -1,18 +1,28 @@
+use std::collections::{HashMap, HashSet};
+
 use ::entity::{
     boss,
     clothing::{ActiveModel, Column, Entity, Model},
+    procurement, procurement_item, production, production_item, shipment, shipment_item,
+    shipment_return, shipment_return_item,
 };
 use sea_orm::*;
-use sea_query::Query;
+use sea_query::{Expr, Query, SelectStatement};
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-use crate::{boss::BossService, ListQueryParams, ListResult};
+use crate::{
+    accounting_period::AccountingPeriodService, boss::BossService, pagination::paginate,
+    search::pinyin_initials, shipment::SHIPPED, ListQueryParams, ListResult, SortOrder, Sortable,
+};
 pub struct ClothingService;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateClothingParams {
     pub boss_id: i32,
+    #[validate(length(min = 1, max = 64, message = "name must be 1 to 64 characters"))]
     pub name: String,
+    #[validate(range(exclusive_min = 0.0, message = "price must be greater than 0"))]
     pub price: f32,
     pub description: Option<String>,
     pub image: Option<String>,
@@ -25,25 +35,92 @@ pub struct UpdateClothingParams {
     pub image: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct ClothingListQueryParams {
     #[serde(deserialize_with = "crate::utils::parse_svc", default)]
     pub boss_ids: Option<Vec<i32>>,
 
     #[serde(flatten)]
+    #[validate(nested)]
     pub list_query: ListQueryParams,
 }
 
+impl Sortable for ClothingListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::Name, Column::Price, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClothingStock {
+    #[serde(flatten)]
+    pub clothing: Model,
+    pub received: i64,
+    pub shipped: i64,
+    // 老板退回的次品
+    pub returned: i64,
+    // 库存 = 进货 - 出货 + 退货
+    pub stock: i64,
+}
+
 impl ClothingService {
+    // 当前工坊的服装id子查询
+    pub(crate) fn workshop_clothing_ids(
+        workshop_id: i32,
+        boss_ids: Option<Vec<i32>>,
+    ) -> SelectStatement {
+        Query::select()
+            .column(Column::Id)
+            .from(Entity)
+            .and_where(
+                Column::BossId.in_subquery(
+                    Query::select()
+                        .column(boss::Column::Id)
+                        .and_where(boss::Column::WorkshopId.eq(workshop_id))
+                        .and_where_option(boss_ids.map(|ids| boss::Column::Id.is_in(ids)))
+                        .from(boss::Entity)
+                        .to_owned(),
+                ),
+            )
+            .to_owned()
+    }
+
+    // 服装必须属于当前工坊
+    pub(crate) async fn find_by_ids<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        ids: impl IntoIterator<Item = i32>,
+    ) -> Result<HashMap<i32, Model>, DbErr> {
+        let ids = ids.into_iter().collect::<HashSet<_>>();
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let clothings = Entity::find()
+            .filter(Column::Id.in_subquery(Self::workshop_clothing_ids(workshop_id, None)))
+            .filter(Column::Id.is_in(ids.iter().copied()))
+            .all(db)
+            .await?;
+        if clothings.len() != ids.len() {
+            return Err(DbErr::RecordNotFound("clothing not found".into()));
+        }
+        Ok(clothings.into_iter().map(|c| (c.id, c)).collect())
+    }
+
     pub async fn create(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         params: CreateClothingParams,
     ) -> Result<Model, DbErr> {
-        BossService::find_by_id(db, user_id, params.boss_id).await?;
+        BossService::find_by_id(db, workshop_id, params.boss_id).await?;
 
         let model = ActiveModel {
+            pinyin: sea_orm::ActiveValue::Set(pinyin_initials(&params.name)),
             name: sea_orm::ActiveValue::Set(params.name),
             price: sea_orm::ActiveValue::Set(params.price),
             description: sea_orm::ActiveValue::Set(params.description),
@@ -54,8 +131,71 @@ impl ClothingService {
         model.insert(db).await
     }
 
-    pub async fn delete(db: &DbConn, user_id: i32, id: i32) -> Result<Model, DbErr> {
-        let model = Self::find_by_id(db, user_id, id).await?;
+    // 删除服装会级联删除生产, 出货, 进货和退货明细
+    pub(crate) async fn check_cascade_open<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        clothing_ids: SelectStatement,
+    ) -> Result<(), DbErr> {
+        AccountingPeriodService::check_cascade_open(
+            db,
+            workshop_id,
+            production_item::Entity::find()
+                .join(
+                    JoinType::InnerJoin,
+                    production_item::Relation::Production.def(),
+                )
+                .filter(production_item::Column::ClothingId.in_subquery(clothing_ids.clone())),
+            production::Column::CreateAt,
+        )
+        .await?;
+        AccountingPeriodService::check_cascade_open(
+            db,
+            workshop_id,
+            shipment_item::Entity::find()
+                .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+                .filter(shipment_item::Column::ClothingId.in_subquery(clothing_ids.clone())),
+            shipment::Column::DispatchedAt,
+        )
+        .await?;
+        AccountingPeriodService::check_cascade_open(
+            db,
+            workshop_id,
+            procurement_item::Entity::find()
+                .join(
+                    JoinType::InnerJoin,
+                    procurement_item::Relation::Procurement.def(),
+                )
+                .filter(procurement_item::Column::ClothingId.in_subquery(clothing_ids.clone())),
+            procurement::Column::CreateAt,
+        )
+        .await?;
+        AccountingPeriodService::check_cascade_open(
+            db,
+            workshop_id,
+            shipment_return_item::Entity::find()
+                .join(
+                    JoinType::InnerJoin,
+                    shipment_return_item::Relation::ShipmentReturn.def(),
+                )
+                .filter(shipment_return_item::Column::ClothingId.in_subquery(clothing_ids)),
+            shipment_return::Column::CreateAt,
+        )
+        .await
+    }
+
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        Self::check_cascade_open(
+            db,
+            workshop_id,
+            Query::select()
+                .column(Column::Id)
+                .from(Entity)
+                .and_where(Column::Id.eq(model.id))
+                .to_owned(),
+        )
+        .await?;
 
         let model_clone = model.clone();
 
@@ -63,34 +203,63 @@ impl ClothingService {
         Ok(model_clone)
     }
 
+    // 上传图片后保存图片和缩略图地址
+    pub async fn set_image(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        image: Option<String>,
+        thumbnail: Option<String>,
+    ) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let mut model = model.into_active_model();
+        model.image = sea_orm::ActiveValue::Set(image);
+        model.thumbnail = sea_orm::ActiveValue::Set(thumbnail);
+
+        model.update(db).await
+    }
+
     // 只能修改一些基本信息不能修改价格
     pub async fn update(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         id: i32,
         params: UpdateClothingParams,
     ) -> Result<Model, DbErr> {
-        let model = Self::find_by_id(db, user_id, id).await?;
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        // 未传的字段保持不变
+        let image = params
+            .image
+            .filter(|image| model.image.as_ref() != Some(image));
 
         let mut model = model.into_active_model();
 
         if let Some(name) = params.name {
+            model.pinyin = sea_orm::ActiveValue::Set(pinyin_initials(&name));
             model.name = sea_orm::ActiveValue::Set(name);
         }
 
-        model.description = sea_orm::ActiveValue::Set(params.description);
-        model.image = sea_orm::ActiveValue::Set(params.image);
+        if let Some(description) = params.description {
+            model.description = sea_orm::ActiveValue::Set(Some(description));
+        }
+
+        // 图片被替换后缩略图不再对应
+        if let Some(image) = image {
+            model.image = sea_orm::ActiveValue::Set(Some(image));
+            model.thumbnail = sea_orm::ActiveValue::Set(None);
+        }
 
         model.update(db).await
     }
 
-    pub async fn find_by_id(db: &DbConn, user_id: i32, id: i32) -> Result<Model, DbErr> {
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
         Entity::find_by_id(id)
             .filter(
                 Column::BossId.in_subquery(
                     Query::select()
                         .column(boss::Column::Id)
-                        .and_where(boss::Column::UserId.eq(user_id))
+                        .and_where(boss::Column::WorkshopId.eq(workshop_id))
                         .from(boss::Entity)
                         .to_owned(),
                 ),
@@ -100,30 +269,53 @@ impl ClothingService {
             .ok_or(DbErr::RecordNotFound("Cannot find clothing".into()))
     }
 
-    pub async fn find_by_user_id(
+    pub async fn find_by_boss_id(
+        db: &DbConn,
+        workshop_id: i32,
+        boss_id: i32,
+    ) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Id.in_subquery(Self::workshop_clothing_ids(
+                workshop_id,
+                Some(vec![boss_id]),
+            )))
+            .all(db)
+            .await
+    }
+
+    // 工坊的所有服装, 删除工坊时清理图片用
+    pub async fn find_all(db: &DbConn, workshop_id: i32) -> Result<Vec<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Id.in_subquery(Self::workshop_clothing_ids(workshop_id, None)))
+            .all(db)
+            .await
+    }
+
+    pub async fn find_by_workshop_id(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         params: ListQueryParams,
         boss_ids: Option<Vec<i32>>,
     ) -> Result<ListResult<Model>, DbErr> {
-        let mut select = Entity::find().order_by_desc(Column::CreateAt);
+        let mut select = Entity::find();
 
         select = select.filter(
             Column::BossId.in_subquery(
                 Query::select()
                     .column(boss::Column::Id)
-                    .and_where(boss::Column::UserId.eq(user_id))
+                    .and_where(boss::Column::WorkshopId.eq(workshop_id))
                     .and_where_option(boss_ids.map(|ids| boss::Column::Id.is_in(ids)))
                     .from(boss::Entity)
                     .to_owned(),
             ),
         );
 
-        if let Some(search) = params.search {
+        if let Some(search) = &params.search {
             select = select.filter(
                 Column::Name
-                    .contains(&search)
-                    .or(Column::Description.contains(&search)),
+                    .contains(search)
+                    .or(Column::Description.contains(search))
+                    .or(Column::Pinyin.contains(search.to_lowercase())),
             );
         }
 
@@ -135,10 +327,99 @@ impl ClothingService {
             select = select.filter(Column::CreateAt.lt(end_time));
         }
 
-        let total = select.clone().count(db).await?;
-        let (page, page_size) = (params.page.unwrap(), params.page_size.unwrap());
-        let data = select.paginate(db, page_size).fetch_page(page).await?;
+        paginate(
+            db,
+            select,
+            &params,
+            ClothingListQueryParams::SORTABLE,
+            (Column::CreateAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+
+    // 按进货和出货数量计算库存
+    pub async fn stock(
+        db: &DbConn,
+        workshop_id: i32,
+        boss_ids: Option<Vec<i32>>,
+    ) -> Result<Vec<ClothingStock>, DbErr> {
+        let clothings = Entity::find()
+            .filter(Column::Id.in_subquery(Self::workshop_clothing_ids(workshop_id, boss_ids)))
+            .order_by_desc(Column::CreateAt)
+            .all(db)
+            .await?;
+        Self::stock_of(db, clothings).await
+    }
+
+    pub(crate) async fn stock_of<C: ConnectionTrait>(
+        db: &C,
+        clothings: Vec<Model>,
+    ) -> Result<Vec<ClothingStock>, DbErr> {
+        let ids = clothings.iter().map(|c| c.id).collect::<Vec<_>>();
+
+        let received: HashMap<i32, i64> = procurement_item::Entity::find()
+            .select_only()
+            .column(procurement_item::Column::ClothingId)
+            .column_as(
+                Expr::col(procurement_item::Column::Amount).sum(),
+                "received",
+            )
+            .filter(procurement_item::Column::ClothingId.is_in(ids.clone()))
+            .group_by(procurement_item::Column::ClothingId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        // 只有已发货的出货单扣减库存
+        let shipped: HashMap<i32, i64> = shipment_item::Entity::find()
+            .select_only()
+            .column(shipment_item::Column::ClothingId)
+            .column_as(
+                Expr::col((shipment_item::Entity, shipment_item::Column::Amount)).sum(),
+                "shipped",
+            )
+            .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+            .filter(shipment_item::Column::ClothingId.is_in(ids.clone()))
+            .filter(shipment::Column::Status.is_in(SHIPPED))
+            .group_by(shipment_item::Column::ClothingId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        let returned: HashMap<i32, i64> = shipment_return_item::Entity::find()
+            .select_only()
+            .column(shipment_return_item::Column::ClothingId)
+            .column_as(
+                Expr::col(shipment_return_item::Column::Amount).sum(),
+                "returned",
+            )
+            .filter(shipment_return_item::Column::ClothingId.is_in(ids))
+            .group_by(shipment_return_item::Column::ClothingId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
 
-        Ok(ListResult { total, data })
+        Ok(clothings
+            .into_iter()
+            .map(|clothing| {
+                let received = received.get(&clothing.id).copied().unwrap_or_default();
+                let shipped = shipped.get(&clothing.id).copied().unwrap_or_default();
+                let returned = returned.get(&clothing.id).copied().unwrap_or_default();
+                ClothingStock {
+                    clothing,
+                    received,
+                    shipped,
+                    returned,
+                    stock: received - shipped + returned,
+                }
+            })
+            .collect())
     }
 }
@@ -0,0 +1,174 @@
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    digest::{digest, SHA256},
+    rand::{SecureRandom, SystemRandom},
+};
+use sea_orm::DbErr;
+
+// 密钥至少 32 个字符, 例如 openssl rand -base64 32 生成的随机字符串
+const MIN_KEY_LEN: usize = 32;
+
+// 敏感字段的加密, 密文格式为 base64(nonce + 密文)
+pub struct Cipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cipher").finish_non_exhaustive()
+    }
+}
+
+impl Cipher {
+    // 由密钥字符串派生 AES-256-GCM 密钥
+    pub fn new(secret: &str) -> Result<Self, DbErr> {
+        if secret.trim().chars().count() < MIN_KEY_LEN {
+            return Err(DbErr::Custom(format!(
+                "encryption key must be at least {MIN_KEY_LEN} characters"
+            )));
+        }
+        let hash = digest(&SHA256, format!("staff-profile:{secret}").as_bytes());
+        let key = UnboundKey::new(&AES_256_GCM, hash.as_ref())
+            .map_err(|_| DbErr::Custom("invalid encryption key".into()))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    pub fn encrypt(&self, plain: &str) -> Result<String, DbErr> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| DbErr::Custom("failed to generate nonce".into()))?;
+
+        let mut data = plain.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| DbErr::Custom("failed to encrypt".into()))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend(data);
+        Ok(STANDARD.encode(encrypted))
+    }
+
+    pub fn decrypt(&self, encrypted: &str) -> Result<String, DbErr> {
+        let data = STANDARD
+            .decode(encrypted)
+            .map_err(|_| DbErr::Custom("failed to decrypt".into()))?;
+        if data.len() < NONCE_LEN {
+            return Err(DbErr::Custom("failed to decrypt".into()));
+        }
+
+        let (nonce, data) = data.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| DbErr::Custom("failed to decrypt".into()))?;
+        let mut data = data.to_vec();
+        let plain = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut data)
+            .map_err(|_| DbErr::Custom("failed to decrypt".into()))?;
+
+        String::from_utf8(plain.to_vec()).map_err(|_| DbErr::Custom("failed to decrypt".into()))
+    }
+}
+
+// 列表中只显示前3位和后4位, 较短的只显示首尾各1位
+pub fn mask(value: &str) -> String {
+    let chars = value.chars().collect::<Vec<_>>();
+    let (head, tail) = match chars.len() {
+        0..=2 => (0, 0),
+        3..=8 => (1, 1),
+        _ => (3, 4),
+    };
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if i < head || i >= chars.len() - tail {
+                *c
+            } else {
+                '*'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "q0bZ0n1yJm5mX1Yd2Sx7oV9kq3Wc8F2tL4rP6uE8hA0=";
+    const OTHER_KEY: &str = "Hc3nV8pR1tY6uI0oP2aS4dF6gJ8kL0zX2cV4bN6mQ8w=";
+
+    #[test]
+    fn round_trip() {
+        let cipher = Cipher::new(KEY).unwrap();
+        let encrypted = cipher.encrypt("110101199003071234").unwrap();
+        assert_ne!(encrypted, "110101199003071234");
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), "110101199003071234");
+    }
+
+    #[test]
+    fn nonce_is_random() {
+        let cipher = Cipher::new(KEY).unwrap();
+        assert_ne!(
+            cipher.encrypt("6222").unwrap(),
+            cipher.encrypt("6222").unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_ciphertext() {
+        let cipher = Cipher::new(KEY).unwrap();
+        let mut data = STANDARD.decode(cipher.encrypt("6222").unwrap()).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        assert!(cipher.decrypt(&STANDARD.encode(data)).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_key_and_malformed_input() {
+        let encrypted = Cipher::new(KEY).unwrap().encrypt("6222").unwrap();
+        let other = Cipher::new(OTHER_KEY).unwrap();
+        assert!(other.decrypt(&encrypted).is_err());
+        assert!(other.decrypt("not base64!").is_err());
+        assert!(other.decrypt(&STANDARD.encode([0u8; 4])).is_err());
+    }
+
+    #[test]
+    fn rejects_short_key() {
+        assert!(Cipher::new("").is_err());
+        assert!(Cipher::new("secret").is_err());
+        assert!(Cipher::new(&" ".repeat(MIN_KEY_LEN)).is_err());
+        assert!(Cipher::new(&"k".repeat(MIN_KEY_LEN)).is_ok());
+    }
+
+    #[test]
+    fn mask_short_values() {
+        assert_eq!(mask(""), "");
+        assert_eq!(mask("1"), "*");
+        assert_eq!(mask("12"), "**");
+    }
+
+    #[test]
+    fn mask_medium_values() {
+        assert_eq!(mask("123"), "1*3");
+        assert_eq!(mask("12345678"), "1******8");
+    }
+
+    #[test]
+    fn mask_long_values() {
+        assert_eq!(mask("123456789"), "123**6789");
+        assert_eq!(mask("110101199003071234"), "110***********1234");
+    }
+
+    #[test]
+    fn mask_counts_chars() {
+        assert_eq!(mask("张三丰"), "张*丰");
+    }
+}
@@ -0,0 +1,233 @@
+use ::entity::{
+    attendance, clothing, procurement, procurement_item, production, production_item, shipment,
+    shipment_item,
+};
+use chrono::{Datelike, FixedOffset, Utc};
+use sea_orm::{prelude::DateTimeWithTimeZone, *};
+use sea_query::{Expr, Func, SimpleExpr};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clothing::{ClothingService, ClothingStock},
+    shipment::SHIPPED,
+    staff::StaffService,
+};
+pub struct DashboardService;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardQueryParams {
+    // 库存低于该值时预警, 默认10件
+    pub low_stock: Option<i64>,
+
+    // 时区偏移分钟数, 用于计算今天和本月的起点, 默认东八区
+    pub utc_offset: Option<i32>,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct TopClothing {
+    pub clothing_id: i32,
+    pub name: String,
+    pub image: Option<String>,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Dashboard {
+    pub produced_today: i64,
+    pub produced_this_month: i64,
+    // 未结算工资, 计件 + 计日
+    pub unsettled_salary: f32,
+    pub received_this_month: i64,
+    pub shipped_this_month: i64,
+    // 本月出货最多的服装
+    pub top_clothing: Vec<TopClothing>,
+    pub low_stock_clothing: Vec<ClothingStock>,
+    pub recent_procurements: Vec<procurement::Model>,
+    pub recent_shipments: Vec<shipment::Model>,
+    pub recent_productions: Vec<production::Model>,
+}
+
+const LIMIT: u64 = 5;
+
+impl DashboardService {
+    async fn produced_since(
+        db: &DbConn,
+        workshop_id: i32,
+        since: DateTimeWithTimeZone,
+    ) -> Result<i64, DbErr> {
+        let produced = production_item::Entity::find()
+            .select_only()
+            .column_as(
+                Expr::col((production_item::Entity, production_item::Column::Count)).sum(),
+                "count",
+            )
+            .join(
+                JoinType::InnerJoin,
+                production_item::Relation::Production.def(),
+            )
+            .filter(
+                production::Column::StaffId
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .filter(production::Column::CreateAt.gte(since))
+            .into_tuple::<Option<i64>>()
+            .one(db)
+            .await?;
+
+        Ok(produced.flatten().unwrap_or_default())
+    }
+
+    pub async fn summary(
+        db: &DbConn,
+        workshop_id: i32,
+        params: DashboardQueryParams,
+    ) -> Result<Dashboard, DbErr> {
+        let offset = FixedOffset::east_opt(params.utc_offset.unwrap_or(8 * 60) * 60)
+            .ok_or(DbErr::Custom("invalid utc offset".into()))?;
+        let now = Utc::now().with_timezone(&offset);
+        let today = now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(offset).single())
+            .ok_or(DbErr::Custom("invalid date".into()))?;
+        let month = today
+            .with_day(1)
+            .ok_or(DbErr::Custom("invalid date".into()))?;
+
+        let produced_today = Self::produced_since(db, workshop_id, today).await?;
+        let produced_this_month = Self::produced_since(db, workshop_id, month).await?;
+
+        // 部分结算的生产记录只计算未结算的部分
+        let piece_salary = production::Entity::find()
+            .select_only()
+            .column_as(
+                SimpleExpr::from(Func::sum(
+                    Expr::col(production::Column::TotalSalary)
+                        .sub(Expr::col(production::Column::SettledAmount)),
+                )),
+                "salary",
+            )
+            .filter(
+                production::Column::StaffId
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .filter(production::Column::Settled.eq(false))
+            .into_tuple::<Option<f32>>()
+            .one(db)
+            .await?;
+        let day_salary = attendance::Entity::find()
+            .select_only()
+            .column_as(Expr::col(attendance::Column::DayRate).sum(), "salary")
+            .filter(
+                attendance::Column::StaffId
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .filter(attendance::Column::Settled.eq(false))
+            .into_tuple::<Option<f32>>()
+            .one(db)
+            .await?;
+        let unsettled_salary =
+            piece_salary.flatten().unwrap_or_default() + day_salary.flatten().unwrap_or_default();
+
+        let received_this_month = procurement_item::Entity::find()
+            .select_only()
+            .column_as(
+                Expr::col((procurement_item::Entity, procurement_item::Column::Amount)).sum(),
+                "amount",
+            )
+            .join(
+                JoinType::InnerJoin,
+                procurement_item::Relation::Procurement.def(),
+            )
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
+            .filter(procurement::Column::CreateAt.gte(month))
+            .into_tuple::<Option<i64>>()
+            .one(db)
+            .await?
+            .flatten()
+            .unwrap_or_default();
+
+        let shipped_this_month = shipment_item::Entity::find()
+            .select_only()
+            .column_as(
+                Expr::col((shipment_item::Entity, shipment_item::Column::Amount)).sum(),
+                "amount",
+            )
+            .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .filter(shipment::Column::Status.is_in(SHIPPED))
+            .filter(shipment::Column::DispatchedAt.gte(month))
+            .into_tuple::<Option<i64>>()
+            .one(db)
+            .await?
+            .flatten()
+            .unwrap_or_default();
+
+        let quantity = Expr::col((shipment_item::Entity, shipment_item::Column::Amount)).sum();
+        let top_clothing = shipment_item::Entity::find()
+            .select_only()
+            .column_as(clothing::Column::Id, "clothing_id")
+            .column(clothing::Column::Name)
+            .column(clothing::Column::Image)
+            .column_as(quantity.clone(), "quantity")
+            .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+            .join(JoinType::InnerJoin, shipment_item::Relation::Clothing.def())
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .filter(shipment::Column::Status.is_in(SHIPPED))
+            .filter(shipment::Column::DispatchedAt.gte(month))
+            .group_by(clothing::Column::Id)
+            .group_by(clothing::Column::Name)
+            .group_by(clothing::Column::Image)
+            .order_by_desc(quantity)
+            .limit(LIMIT)
+            .into_model::<TopClothing>()
+            .all(db)
+            .await?;
+
+        let low_stock = params.low_stock.unwrap_or(10);
+        let mut low_stock_clothing = ClothingService::stock(db, workshop_id, None)
+            .await?
+            .into_iter()
+            .filter(|c| c.received > 0 && c.stock <= low_stock)
+            .collect::<Vec<_>>();
+        low_stock_clothing.sort_by_key(|c| c.stock);
+
+        let recent_procurements = procurement::Entity::find()
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
+            .order_by_desc(procurement::Column::CreateAt)
+            .limit(LIMIT)
+            .all(db)
+            .await?;
+
+        let recent_shipments = shipment::Entity::find()
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .order_by_desc(shipment::Column::CreateAt)
+            .limit(LIMIT)
+            .all(db)
+            .await?;
+
+        let recent_productions = production::Entity::find()
+            .filter(
+                production::Column::StaffId
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .order_by_desc(production::Column::CreateAt)
+            .limit(LIMIT)
+            .all(db)
+            .await?;
+
+        Ok(Dashboard {
+            produced_today,
+            produced_this_month,
+            unsettled_salary,
+            received_this_month,
+            shipped_this_month,
+            top_clothing,
+            low_stock_clothing,
+            recent_procurements,
+            recent_shipments,
+            recent_productions,
+        })
+    }
+}
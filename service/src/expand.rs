@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use ::entity::{
+    boss, clothing, procurement_item, production_item, shipment_item, shipment_return_item,
+};
+use sea_orm::*;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+use crate::clothing::ClothingService;
+
+// 单据中需要一并返回的关联数据, 例如 expand=clothing,staff,boss
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Expand {
+    pub clothing: bool,
+    pub staff: bool,
+    // 服装所属的老板, 会同时返回服装
+    pub boss: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ExpandQueryParams {
+    #[serde(deserialize_with = "parse_expand", default)]
+    pub expand: Expand,
+}
+
+fn parse_expand<'de, D>(deserializer: D) -> std::result::Result<Expand, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+
+    let mut expand = Expand::default();
+    for name in s.iter().flat_map(|s| s.split(',')).map(str::trim) {
+        match name {
+            "clothing" => expand.clothing = true,
+            "staff" => expand.staff = true,
+            "boss" => expand.boss = true,
+            "" => {}
+            _ => return Err(D::Error::custom(format!("invalid expand: {name}"))),
+        }
+    }
+    Ok(expand)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExpandedClothing {
+    #[serde(flatten)]
+    pub clothing: clothing::Model,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boss: Option<boss::Model>,
+}
+
+// 单据明细, 展开时附带服装信息
+#[derive(Debug, Serialize, Clone)]
+pub struct Item<T> {
+    #[serde(flatten)]
+    pub item: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clothing: Option<ExpandedClothing>,
+}
+
+impl<T> From<T> for Item<T> {
+    fn from(item: T) -> Self {
+        Item {
+            item,
+            clothing: None,
+        }
+    }
+}
+
+pub trait ClothingItem {
+    fn clothing_id(&self) -> i32;
+}
+
+impl ClothingItem for production_item::Model {
+    fn clothing_id(&self) -> i32 {
+        self.clothing_id
+    }
+}
+
+impl ClothingItem for shipment_item::Model {
+    fn clothing_id(&self) -> i32 {
+        self.clothing_id
+    }
+}
+
+impl ClothingItem for procurement_item::Model {
+    fn clothing_id(&self) -> i32 {
+        self.clothing_id
+    }
+}
+
+impl ClothingItem for shipment_return_item::Model {
+    fn clothing_id(&self) -> i32 {
+        self.clothing_id
+    }
+}
+
+// 一次查询补全所有明细的服装 (和老板) 信息, 只返回当前工坊的服装
+pub(crate) async fn expand_items<'a, T>(
+    db: &DbConn,
+    workshop_id: i32,
+    expand: Expand,
+    items: impl IntoIterator<Item = &'a mut Item<T>>,
+) -> Result<(), DbErr>
+where
+    T: ClothingItem + 'a,
+{
+    if !expand.clothing && !expand.boss {
+        return Ok(());
+    }
+
+    let mut items = items.into_iter().collect::<Vec<_>>();
+
+    let clothings = clothing::Entity::find()
+        .filter(clothing::Column::Id.is_in(items.iter().map(|i| i.item.clothing_id())))
+        .filter(
+            clothing::Column::Id
+                .in_subquery(ClothingService::workshop_clothing_ids(workshop_id, None)),
+        )
+        .all(db)
+        .await?;
+    let bosses = if expand.boss {
+        clothings.load_one(boss::Entity, db).await?
+    } else {
+        vec![None; clothings.len()]
+    };
+
+    let clothings: HashMap<i32, ExpandedClothing> = clothings
+        .into_iter()
+        .zip(bosses)
+        .map(|(clothing, boss)| (clothing.id, ExpandedClothing { clothing, boss }))
+        .collect();
+
+    for item in items.iter_mut() {
+        item.clothing = clothings.get(&item.item.clothing_id()).cloned();
+    }
+
+    Ok(())
+}
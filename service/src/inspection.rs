@@ -0,0 +1,227 @@
+use ::entity::inspection::{ActiveModel, Column, Entity, Model};
+use ::entity::{production, production_item};
+use sea_orm::*;
+use sea_query::Query;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    accounting_period::AccountingPeriodService, pagination::paginate,
+    production::ProductionService, workshop::WorkshopService, ListQueryParams, ListResult,
+    SortOrder, Sortable,
+};
+pub struct InspectionService;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateInspectionParams {
+    pub production_item_id: i32,
+    #[validate(range(min = 0, message = "passed must not be negative"))]
+    pub passed: i32,
+    #[validate(range(min = 0, message = "rejected must not be negative"))]
+    pub rejected: i32,
+    // 次品原因
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateInspectionParams {
+    #[validate(range(min = 0, message = "passed must not be negative"))]
+    pub passed: Option<i32>,
+    #[validate(range(min = 0, message = "rejected must not be negative"))]
+    pub rejected: Option<i32>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectionListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub production_ids: Option<Vec<i32>>,
+}
+
+impl Sortable for InspectionListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::Passed, Column::Rejected, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+impl InspectionService {
+    // 合格数和次品数之和必须等于生产件数
+    fn check_count(item: &production_item::Model, passed: i32, rejected: i32) -> Result<(), DbErr> {
+        if passed + rejected != item.count {
+            return Err(DbErr::Custom(
+                "passed and rejected must add up to the produced count".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    // 已结账期间的生产记录不能再改动质检结果, 按合格件数计薪时已结算的明细也不能
+    async fn pay_passed_only(
+        db: &DbConn,
+        workshop_id: i32,
+        production: &production::Model,
+        item: &production_item::Model,
+    ) -> Result<bool, DbErr> {
+        AccountingPeriodService::check_open(db, workshop_id, production.create_at).await?;
+
+        let pay_passed_only = WorkshopService::find_by_id(db, workshop_id)
+            .await?
+            .pay_passed_only;
+        if pay_passed_only && item.settled {
+            return Err(DbErr::Custom("production item has been settled".into()));
+        }
+        Ok(pay_passed_only)
+    }
+
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        user_id: i32,
+        params: CreateInspectionParams,
+    ) -> Result<Model, DbErr> {
+        let (item, production) =
+            ProductionService::find_item(db, workshop_id, params.production_item_id).await?;
+        Self::check_count(&item, params.passed, params.rejected)?;
+
+        let inspected = Entity::find()
+            .filter(Column::ProductionItemId.eq(item.id))
+            .count(db)
+            .await?;
+        if inspected > 0 {
+            return Err(DbErr::Custom("production item has been inspected".into()));
+        }
+
+        let pay_passed_only = Self::pay_passed_only(db, workshop_id, &production, &item).await?;
+
+        let txn = db.begin().await?;
+
+        let model = ActiveModel {
+            passed: Set(params.passed),
+            rejected: Set(params.rejected),
+            reason: Set(params.reason),
+            production_item_id: Set(item.id),
+            inspector_id: Set(Some(user_id)),
+            workshop_id: Set(workshop_id),
+            ..Default::default()
+        };
+        let model = model.insert(&txn).await?;
+
+        if pay_passed_only {
+            ProductionService::recalculate_salary(&txn, item, Some(model.passed)).await?;
+        }
+
+        txn.commit().await?;
+        Ok(model)
+    }
+
+    // 删除质检记录后未结算明细的计件工资恢复按生产件数计算
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        let (item, production) =
+            ProductionService::find_item(db, workshop_id, model.production_item_id).await?;
+        Self::pay_passed_only(db, workshop_id, &production, &item).await?;
+
+        let txn = db.begin().await?;
+        model.clone().delete(&txn).await?;
+        if !item.settled {
+            ProductionService::recalculate_salary(&txn, item, None).await?;
+        }
+        txn.commit().await?;
+
+        Ok(model)
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateInspectionParams,
+    ) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        let (item, production) =
+            ProductionService::find_item(db, workshop_id, model.production_item_id).await?;
+
+        let passed = params.passed.unwrap_or(model.passed);
+        let rejected = params.rejected.unwrap_or(model.rejected);
+        Self::check_count(&item, passed, rejected)?;
+
+        let pay_passed_only = Self::pay_passed_only(db, workshop_id, &production, &item).await?;
+
+        let txn = db.begin().await?;
+
+        let mut model = model.into_active_model();
+        model.passed = Set(passed);
+        model.rejected = Set(rejected);
+        model.reason = Set(params.reason);
+        let model = model.update(&txn).await?;
+
+        if pay_passed_only {
+            ProductionService::recalculate_salary(&txn, item, Some(model.passed)).await?;
+        }
+
+        txn.commit().await?;
+        Ok(model)
+    }
+
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("inspection not found".into()))
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+        production_ids: Option<Vec<i32>>,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(Column::WorkshopId.eq(workshop_id));
+
+        if let Some(production_ids) = production_ids {
+            select = select.filter(
+                Column::ProductionItemId.in_subquery(
+                    Query::select()
+                        .column(production_item::Column::Id)
+                        .and_where(production_item::Column::ProductionId.is_in(production_ids))
+                        .from(production_item::Entity)
+                        .to_owned(),
+                ),
+            );
+        }
+
+        if let Some(search) = &params.search {
+            select = select.filter(Column::Reason.contains(search));
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::CreateAt.lt(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            InspectionListQueryParams::SORTABLE,
+            (Column::CreateAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+}
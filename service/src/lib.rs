@@ -1,18 +1,39 @@
-use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{prelude::DateTimeWithTimeZone, ColumnTrait, IdenStatic};
 use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError, ValidationErrors};
 
+pub mod accounting_period;
+pub mod analytics;
+pub mod attendance;
 pub mod boss;
 pub mod clothing;
+pub mod crypto;
+pub mod dashboard;
+pub mod expand;
+pub mod inspection;
+pub mod login_event;
+pub mod material;
+pub mod payroll;
+pub mod process_step;
 pub mod procurement;
+pub mod sales_order;
+pub mod search;
 pub mod staff;
+pub mod staff_adjustment;
+pub mod supplier;
+pub mod supplier_payment;
 pub mod user;
+pub mod workshop;
 pub mod shipment;
+pub mod shipment_return;
 pub mod production;
 
+mod pagination;
 mod utils;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 #[serde(rename_all = "camelCase")]
+#[validate(schema(function = "utils::validate_time_range"))]
 pub struct ListQueryParams {
     #[serde(
         default = "utils::default_page",
@@ -23,6 +44,7 @@ pub struct ListQueryParams {
         default = "utils::default_page_size",
         deserialize_with = "utils::parse_option_u64"
     )]
+    #[validate(range(min = 1, max = 100, message = "page_size must be between 1 and 100"))]
     pub page_size: Option<u64>,
 
     pub search: Option<String>,
@@ -30,6 +52,53 @@ pub struct ListQueryParams {
     pub start_time: Option<DateTimeWithTimeZone>,
 
     pub end_time: Option<DateTimeWithTimeZone>,
+
+    // 排序字段, 每个列表支持的字段不同
+    pub sort_by: Option<String>,
+
+    pub sort_order: Option<SortOrder>,
+
+    // 游标分页, 值为上一页返回的 next_cursor
+    pub cursor: Option<String>,
+}
+
+// 列表查询参数允许排序的列, 解析查询参数时据此校验 sort_by
+pub trait Sortable {
+    type Column: ColumnTrait;
+
+    const SORTABLE: &'static [Self::Column];
+
+    fn list_query(&self) -> &ListQueryParams;
+
+    fn validate_sort_by(&self) -> Result<(), ValidationErrors> {
+        let Some(sort_by) = &self.list_query().sort_by else {
+            return Ok(());
+        };
+        if Self::SORTABLE.iter().any(|c| c.as_str() == sort_by) {
+            return Ok(());
+        }
+
+        let allowed = Self::SORTABLE
+            .iter()
+            .map(|c| c.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut errors = ValidationErrors::new();
+        errors.add(
+            "sort_by",
+            ValidationError::new("sort_by")
+                .with_message(format!("sort_by must be one of: {allowed}").into()),
+        );
+        Err(errors)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
 }
 
 #[derive(Debug, Serialize)]
@@ -39,4 +108,6 @@ where
 {
     pub total: u64,
     pub data: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
@@ -0,0 +1,80 @@
+use ::entity::login_event::{ActiveModel, Column, Entity, Model};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{pagination::paginate, ListQueryParams, ListResult, SortOrder, Sortable};
+pub struct LoginEventService;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateLoginEventParams {
+    pub phone_number: String,
+    pub user_id: Option<i32>,
+    pub ip: String,
+    pub success: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginEventListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+}
+
+impl Sortable for LoginEventListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+impl LoginEventService {
+    pub async fn create(db: &DbConn, params: CreateLoginEventParams) -> Result<Model, DbErr> {
+        let model = ActiveModel {
+            phone_number: Set(params.phone_number),
+            user_id: Set(params.user_id),
+            ip: Set(params.ip),
+            success: Set(params.success),
+            reason: Set(params.reason),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    pub async fn find_by_user_id(
+        db: &DbConn,
+        user_id: i32,
+        params: ListQueryParams,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(Column::UserId.eq(user_id));
+
+        if let Some(search) = &params.search {
+            select = select.filter(Column::Ip.contains(search));
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::CreateAt.lt(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            LoginEventListQueryParams::SORTABLE,
+            (Column::CreateAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+}
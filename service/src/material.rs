@@ -0,0 +1,505 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ::entity::{
+    clothing_material,
+    material::{ActiveModel, Column, Entity, Model},
+    material_consumption,
+};
+use sea_orm::*;
+use sea_query::{Expr, Query};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    clothing::ClothingService, pagination::paginate, ListQueryParams, ListResult, SortOrder,
+    Sortable,
+};
+pub struct MaterialService;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateMaterialParams {
+    #[validate(length(min = 1, max = 64, message = "name must be 1 to 64 characters"))]
+    pub name: String,
+    // 计量单位, 例如 米, 卷, 个
+    #[validate(length(min = 1, max = 16, message = "unit must be 1 to 16 characters"))]
+    pub unit: String,
+    #[validate(range(min = 0.0, message = "stock must not be negative"))]
+    pub stock: Option<f32>,
+    #[validate(range(min = 0.0, message = "cost must not be negative"))]
+    pub cost: Option<f32>,
+    pub description: Option<String>,
+}
+
+// stock 用于盘点后直接修正库存
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateMaterialParams {
+    pub name: Option<String>,
+    pub unit: Option<String>,
+    pub stock: Option<f32>,
+    pub cost: Option<f32>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct BomItem {
+    pub material_id: i32,
+    // 每件服装的用量
+    #[validate(range(exclusive_min = 0.0, message = "usage must be greater than 0"))]
+    pub usage: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct SetBomParams {
+    #[validate(nested)]
+    pub materials: Vec<BomItem>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Bom {
+    #[serde(flatten)]
+    pub item: clothing_material::Model,
+    pub material: Option<Model>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct RequirementItem {
+    pub clothing_id: i32,
+    #[validate(range(min = 1, message = "count must be at least 1"))]
+    pub count: i32,
+}
+
+// 计划生产的服装和数量
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct RequirementParams {
+    #[validate(length(min = 1, message = "items must not be empty"), nested)]
+    pub items: Vec<RequirementItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MaterialRequirement {
+    #[serde(flatten)]
+    pub material: Model,
+    pub required: f32,
+    // 缺料数量 = 需要 - 库存, 库存足够时为 0
+    pub shortage: f32,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+}
+
+impl Sortable for MaterialListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] =
+        &[Column::Name, Column::Stock, Column::Cost, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialConsumptionListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+}
+
+impl Sortable for MaterialConsumptionListQueryParams {
+    type Column = material_consumption::Column;
+
+    const SORTABLE: &'static [material_consumption::Column] = &[
+        material_consumption::Column::CreateAt,
+        material_consumption::Column::Amount,
+    ];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+impl MaterialService {
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        params: CreateMaterialParams,
+    ) -> Result<Model, DbErr> {
+        let model = ActiveModel {
+            name: Set(params.name),
+            unit: Set(params.unit),
+            stock: Set(params.stock.unwrap_or_default()),
+            cost: Set(params.cost.unwrap_or_default()),
+            description: Set(params.description),
+            workshop_id: Set(workshop_id),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let model_clone = model.clone();
+        model.delete(db).await?;
+        Ok(model_clone)
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateMaterialParams,
+    ) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let mut model = model.into_active_model();
+        if let Some(name) = params.name {
+            model.name = Set(name);
+        }
+        if let Some(unit) = params.unit {
+            model.unit = Set(unit);
+        }
+        if let Some(stock) = params.stock {
+            model.stock = Set(stock);
+        }
+        if let Some(cost) = params.cost {
+            model.cost = Set(cost);
+        }
+        model.description = Set(params.description);
+
+        model.update(db).await
+    }
+
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("material not found".into()))
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find().filter(Column::WorkshopId.eq(workshop_id));
+
+        if let Some(search) = &params.search {
+            select = select.filter(
+                Condition::any()
+                    .add(Column::Name.contains(search))
+                    .add(Column::Description.contains(search)),
+            );
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::CreateAt.lt(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            MaterialListQueryParams::SORTABLE,
+            (Column::CreateAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+
+    // 服装的物料清单
+    pub async fn find_bom(
+        db: &DbConn,
+        workshop_id: i32,
+        clothing_id: i32,
+    ) -> Result<Vec<Bom>, DbErr> {
+        let clothing = ClothingService::find_by_id(db, workshop_id, clothing_id).await?;
+
+        Ok(clothing_material::Entity::find()
+            .filter(clothing_material::Column::ClothingId.eq(clothing.id))
+            .order_by_asc(clothing_material::Column::Id)
+            .find_also_related(Entity)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|(item, material)| Bom { item, material })
+            .collect())
+    }
+
+    // 整体替换服装的物料清单
+    pub async fn set_bom(
+        db: &DbConn,
+        workshop_id: i32,
+        clothing_id: i32,
+        params: SetBomParams,
+    ) -> Result<Vec<Bom>, DbErr> {
+        let clothing = ClothingService::find_by_id(db, workshop_id, clothing_id).await?;
+
+        let ids = params
+            .materials
+            .iter()
+            .map(|item| item.material_id)
+            .collect::<HashSet<_>>();
+        if ids.len() != params.materials.len() {
+            return Err(DbErr::Custom("duplicate material in bom".into()));
+        }
+        Self::find_by_ids(db, workshop_id, ids).await?;
+
+        let txn = db.begin().await?;
+
+        clothing_material::Entity::delete_many()
+            .filter(clothing_material::Column::ClothingId.eq(clothing.id))
+            .exec(&txn)
+            .await?;
+
+        if !params.materials.is_empty() {
+            let items = params
+                .materials
+                .into_iter()
+                .map(|item| clothing_material::ActiveModel {
+                    usage: Set(item.usage),
+                    clothing_id: Set(clothing.id),
+                    material_id: Set(item.material_id),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            clothing_material::Entity::insert_many(items)
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Self::find_bom(db, workshop_id, clothing.id).await
+    }
+
+    // 按物料清单计算计划生产需要的物料, 列出库存不足的数量
+    pub async fn requirement(
+        db: &DbConn,
+        workshop_id: i32,
+        params: RequirementParams,
+    ) -> Result<Vec<MaterialRequirement>, DbErr> {
+        let items = params
+            .items
+            .iter()
+            .map(|item| (item.clothing_id, item.count))
+            .collect::<Vec<_>>();
+        let required = Self::required(db, workshop_id, &items).await?;
+
+        let materials = Entity::find()
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .filter(Column::Id.is_in(required.keys().copied()))
+            .all(db)
+            .await?;
+
+        let mut requirements = materials
+            .into_iter()
+            .map(|material| {
+                let required = required.get(&material.id).copied().unwrap_or_default();
+                MaterialRequirement {
+                    shortage: (required - material.stock).max(0.0),
+                    required,
+                    material,
+                }
+            })
+            .collect::<Vec<_>>();
+        requirements.sort_by(|a, b| b.shortage.total_cmp(&a.shortage));
+
+        Ok(requirements)
+    }
+
+    // 物料的消耗记录
+    pub async fn find_consumption(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: ListQueryParams,
+    ) -> Result<ListResult<material_consumption::Model>, DbErr> {
+        let material = Self::find_by_id(db, workshop_id, id).await?;
+
+        let mut select = material_consumption::Entity::find()
+            .filter(material_consumption::Column::MaterialId.eq(material.id));
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(material_consumption::Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(material_consumption::Column::CreateAt.lt(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            MaterialConsumptionListQueryParams::SORTABLE,
+            (material_consumption::Column::CreateAt, SortOrder::Desc),
+            material_consumption::Column::Id,
+        )
+        .await
+    }
+
+    // 物料必须属于当前工坊
+    pub(crate) async fn find_by_ids<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        ids: impl IntoIterator<Item = i32>,
+    ) -> Result<HashMap<i32, Model>, DbErr> {
+        let ids = ids.into_iter().collect::<HashSet<_>>();
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let materials = Entity::find()
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .filter(Column::Id.is_in(ids.iter().copied()))
+            .all(db)
+            .await?;
+        if materials.len() != ids.len() {
+            return Err(DbErr::RecordNotFound("material not found".into()));
+        }
+        Ok(materials.into_iter().map(|m| (m.id, m)).collect())
+    }
+
+    // 增加或扣减库存, amount 为负数时扣减
+    pub(crate) async fn add_stock<C: ConnectionTrait>(
+        db: &C,
+        id: i32,
+        amount: f32,
+    ) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(Column::Stock, Expr::col(Column::Stock).add(amount))
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    // 扣减库存, 库存不足时拒绝, 不会扣成负数
+    pub(crate) async fn take_stock<C: ConnectionTrait>(
+        db: &C,
+        id: i32,
+        amount: f32,
+    ) -> Result<(), DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::Stock, Expr::col(Column::Stock).sub(amount))
+            .filter(Column::Id.eq(id))
+            .filter(Column::Stock.gte(amount))
+            .exec(db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(DbErr::Custom(format!(
+                "material {id} does not have {amount} in stock"
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn set_cost<C: ConnectionTrait>(
+        db: &C,
+        id: i32,
+        cost: f32,
+    ) -> Result<(), DbErr> {
+        Entity::update_many()
+            .col_expr(Column::Cost, Expr::value(cost))
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    // (服装id, 件数) 需要的各物料数量, 只计算当前工坊的物料
+    async fn required<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        items: &[(i32, i32)],
+    ) -> Result<BTreeMap<i32, f32>, DbErr> {
+        let bom = clothing_material::Entity::find()
+            .filter(
+                clothing_material::Column::ClothingId
+                    .is_in(items.iter().map(|(clothing_id, _)| *clothing_id)),
+            )
+            .filter(
+                clothing_material::Column::MaterialId.in_subquery(
+                    Query::select()
+                        .column(Column::Id)
+                        .from(Entity)
+                        .and_where(Column::WorkshopId.eq(workshop_id))
+                        .to_owned(),
+                ),
+            )
+            .all(db)
+            .await?;
+
+        let mut counts: HashMap<i32, i32> = HashMap::new();
+        for (clothing_id, count) in items {
+            *counts.entry(*clothing_id).or_default() += count;
+        }
+
+        let mut required = BTreeMap::new();
+        for item in bom {
+            let count = counts.get(&item.clothing_id).copied().unwrap_or_default();
+            *required.entry(item.material_id).or_default() += item.usage * count as f32;
+        }
+        Ok(required)
+    }
+
+    // 生产时按物料清单扣减库存并记录消耗, 库存不足也允许扣减成负数
+    pub(crate) async fn consume<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        production_id: i32,
+        items: &[(i32, i32)],
+    ) -> Result<(), DbErr> {
+        let required = Self::required(db, workshop_id, items).await?;
+        if required.is_empty() {
+            return Ok(());
+        }
+
+        for (&material_id, &amount) in required.iter() {
+            Self::add_stock(db, material_id, -amount).await?;
+        }
+
+        let consumption = required
+            .into_iter()
+            .map(|(material_id, amount)| material_consumption::ActiveModel {
+                amount: Set(amount),
+                production_id: Set(production_id),
+                material_id: Set(material_id),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        material_consumption::Entity::insert_many(consumption)
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    // 删除生产记录前归还消耗的物料, 消耗记录随生产记录级联删除
+    pub(crate) async fn restore<C: ConnectionTrait>(
+        db: &C,
+        production_id: i32,
+    ) -> Result<(), DbErr> {
+        let consumption = material_consumption::Entity::find()
+            .filter(material_consumption::Column::ProductionId.eq(production_id))
+            .all(db)
+            .await?;
+
+        for item in consumption {
+            Self::add_stock(db, item.material_id, item.amount).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sea_orm::{
+    prelude::{Date, DateTimeWithTimeZone},
+    sea_query::Value,
+    *,
+};
+use serde::Serialize;
+
+use crate::{ListQueryParams, ListResult, SortOrder};
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        }
+    }
+}
+
+// 排序并分页, 传了 cursor 时使用游标分页, 否则使用 page/page_size 分页
+// 排序字段只能是 sortable 中的列, 相同值再按 id 排序保证顺序稳定
+pub(crate) async fn paginate<E>(
+    db: &DbConn,
+    select: Select<E>,
+    params: &ListQueryParams,
+    sortable: &[E::Column],
+    default_sort: (E::Column, SortOrder),
+    id: E::Column,
+) -> Result<ListResult<E::Model>, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Serialize + Sync,
+{
+    let (column, order) = match &params.sort_by {
+        Some(sort_by) => {
+            let column = sortable
+                .iter()
+                .find(|c| c.as_str() == sort_by)
+                .copied()
+                .ok_or(DbErr::Custom(format!("invalid sort_by: {sort_by}")))?;
+            (column, params.sort_order.unwrap_or_default())
+        }
+        None => (default_sort.0, params.sort_order.unwrap_or(default_sort.1)),
+    };
+
+    let total = select.clone().count(db).await?;
+    let page_size = params.page_size.unwrap_or(10);
+
+    let select = select
+        .order_by(column, order.into())
+        .order_by(id, order.into());
+
+    let data = match &params.cursor {
+        Some(cursor) => {
+            let (value, last_id) = decode_cursor(cursor, column)?;
+            let (after, after_id) = match order {
+                SortOrder::Asc => (column.gt(value.clone()), id.gt(last_id)),
+                SortOrder::Desc => (column.lt(value.clone()), id.lt(last_id)),
+            };
+
+            select
+                .filter(
+                    Condition::any()
+                        .add(after)
+                        .add(Condition::all().add(column.eq(value)).add(after_id)),
+                )
+                .limit(page_size)
+                .all(db)
+                .await?
+        }
+        None => {
+            select
+                .paginate(db, page_size)
+                .fetch_page(params.page.unwrap_or(0))
+                .await?
+        }
+    };
+
+    let next_cursor = match data.last() {
+        Some(last) if data.len() as u64 == page_size => Some(encode_cursor(last, column, id)?),
+        _ => None,
+    };
+
+    Ok(ListResult {
+        total,
+        data,
+        next_cursor,
+    })
+}
+
+fn encode_cursor<M: Serialize, C: ColumnTrait>(
+    model: &M,
+    column: C,
+    id: C,
+) -> Result<String, DbErr> {
+    let json = serde_json::to_value(model).map_err(|e| DbErr::Custom(e.to_string()))?;
+    let cursor = serde_json::json!([json[column.as_str()], json[id.as_str()]]);
+
+    Ok(URL_SAFE_NO_PAD.encode(cursor.to_string()))
+}
+
+fn decode_cursor<C: ColumnTrait>(cursor: &str, column: C) -> Result<(Value, i32), DbErr> {
+    let invalid = || DbErr::Custom(format!("invalid cursor: {cursor}"));
+
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let (value, id): (serde_json::Value, i32) =
+        serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+
+    let value = match column.def().get_column_type() {
+        ColumnType::Float => value.as_f64().map(|v| Value::from(v as f32)),
+        ColumnType::Double => value.as_f64().map(Value::from),
+        ColumnType::Integer => value.as_i64().map(|v| Value::from(v as i32)),
+        ColumnType::String(_) | ColumnType::Text => value.as_str().map(Value::from),
+        ColumnType::TimestampWithTimeZone => serde_json::from_value::<DateTimeWithTimeZone>(value)
+            .ok()
+            .map(Value::from),
+        ColumnType::Date => serde_json::from_value::<Date>(value).ok().map(Value::from),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+
+    Ok((value, id))
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use ::entity::{
+    attendance, production, sea_orm_active_enums::AdjustmentKind, staff, staff_adjustment,
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, *};
+use sea_query::{Expr, Func};
+use serde::{Deserialize, Serialize};
+
+use crate::staff::StaffService;
+pub struct PayrollService;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollQueryParams {
+    pub start_time: Option<DateTimeWithTimeZone>,
+    pub end_time: Option<DateTimeWithTimeZone>,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub staff_ids: Option<Vec<i32>>,
+
+    // 为空时统计全部, 否则只统计已结算或未结算的记录
+    pub settled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffPayroll {
+    pub staff: staff::Model,
+    // 计件工资
+    pub piece_salary: f32,
+    // 计日工资
+    pub day_salary: f32,
+    pub total_salary: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffBalance {
+    #[serde(flatten)]
+    pub payroll: StaffPayroll,
+    pub bonus: f32,
+    pub advance: f32,
+    pub fine: f32,
+    pub deduction: f32,
+    // 应发余额 = 未结算工资 + 奖金 - 预支 - 罚款 - 扣款
+    pub balance: f32,
+}
+
+impl PayrollService {
+    // 按员工汇总计件和计日工资
+    pub async fn summary(
+        db: &DbConn,
+        workshop_id: i32,
+        params: PayrollQueryParams,
+    ) -> Result<Vec<StaffPayroll>, DbErr> {
+        let staffs = staff::Entity::find()
+            .filter(
+                staff::Column::Id.in_subquery(StaffService::workshop_staff_ids(
+                    workshop_id,
+                    params.staff_ids,
+                )),
+            )
+            .order_by_asc(staff::Column::Id)
+            .all(db)
+            .await?;
+        let ids = staffs.iter().map(|s| s.id).collect::<Vec<_>>();
+
+        // 部分结算的生产记录分别计入已结算和未结算
+        let piece_salary = match params.settled {
+            None => Expr::col(production::Column::TotalSalary).sum(),
+            Some(true) => Expr::col(production::Column::SettledAmount).sum(),
+            Some(false) => Func::sum(
+                Expr::col(production::Column::TotalSalary)
+                    .sub(Expr::col(production::Column::SettledAmount)),
+            )
+            .into(),
+        };
+        let mut piece_select = production::Entity::find()
+            .select_only()
+            .column(production::Column::StaffId)
+            .column_as(piece_salary, "salary")
+            .filter(production::Column::StaffId.is_in(ids.clone()))
+            .group_by(production::Column::StaffId);
+
+        let mut day_select = attendance::Entity::find()
+            .select_only()
+            .column(attendance::Column::StaffId)
+            .column_as(Expr::col(attendance::Column::DayRate).sum(), "salary")
+            .filter(attendance::Column::StaffId.is_in(ids))
+            .group_by(attendance::Column::StaffId);
+
+        if let Some(settled) = params.settled {
+            piece_select = if settled {
+                piece_select.filter(production::Column::SettledAmount.gt(0.0))
+            } else {
+                piece_select.filter(production::Column::Settled.eq(false))
+            };
+            day_select = day_select.filter(attendance::Column::Settled.eq(settled));
+        }
+
+        if let Some(start_time) = params.start_time {
+            piece_select = piece_select.filter(production::Column::CreateAt.gt(start_time));
+            day_select =
+                day_select.filter(attendance::Column::WorkDate.gte(start_time.date_naive()));
+        }
+
+        if let Some(end_time) = params.end_time {
+            piece_select = piece_select.filter(production::Column::CreateAt.lt(end_time));
+            day_select = day_select.filter(attendance::Column::WorkDate.lte(end_time.date_naive()));
+        }
+
+        let piece_salary: HashMap<i32, f32> = piece_select
+            .into_tuple::<(i32, f32)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+        let day_salary: HashMap<i32, f32> = day_select
+            .into_tuple::<(i32, f32)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(staffs
+            .into_iter()
+            .map(|staff| {
+                let piece_salary = piece_salary.get(&staff.id).copied().unwrap_or_default();
+                let day_salary = day_salary.get(&staff.id).copied().unwrap_or_default();
+                StaffPayroll {
+                    staff,
+                    piece_salary,
+                    day_salary,
+                    total_salary: piece_salary + day_salary,
+                }
+            })
+            .collect())
+    }
+
+    // 员工未结算余额
+    pub async fn balance(
+        db: &DbConn,
+        workshop_id: i32,
+        staff_id: i32,
+    ) -> Result<StaffBalance, DbErr> {
+        let staff = StaffService::find_model(db, workshop_id, staff_id).await?;
+
+        let payroll = Self::summary(
+            db,
+            workshop_id,
+            PayrollQueryParams {
+                start_time: None,
+                end_time: None,
+                staff_ids: Some(vec![staff.id]),
+                settled: Some(false),
+            },
+        )
+        .await?
+        .pop()
+        .ok_or(DbErr::RecordNotFound("Cannot find staff".into()))?;
+
+        let adjustments = staff_adjustment::Entity::find()
+            .select_only()
+            .column(staff_adjustment::Column::Kind)
+            .column_as(Expr::col(staff_adjustment::Column::Amount).sum(), "amount")
+            .filter(staff_adjustment::Column::StaffId.eq(staff.id))
+            .filter(staff_adjustment::Column::Settled.eq(false))
+            .group_by(staff_adjustment::Column::Kind)
+            .into_tuple::<(AdjustmentKind, f32)>()
+            .all(db)
+            .await?;
+
+        let (mut bonus, mut advance, mut fine, mut deduction) = (0.0, 0.0, 0.0, 0.0);
+        for (kind, amount) in adjustments {
+            match kind {
+                AdjustmentKind::Bonus => bonus += amount,
+                AdjustmentKind::Advance => advance += amount,
+                AdjustmentKind::Fine => fine += amount,
+                AdjustmentKind::Deduction => deduction += amount,
+            }
+        }
+
+        Ok(StaffBalance {
+            balance: payroll.total_salary + bonus - advance - fine - deduction,
+            payroll,
+            bonus,
+            advance,
+            fine,
+            deduction,
+        })
+    }
+}
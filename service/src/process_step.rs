@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use ::entity::{
+    clothing,
+    process_step::{ActiveModel, Column, Entity, Model},
+    procurement_item, production_item,
+};
+use sea_orm::*;
+use sea_query::{Expr, SelectStatement};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    clothing::ClothingService, pagination::paginate, ListQueryParams, ListResult, SortOrder,
+    Sortable,
+};
+pub struct ProcessStepService;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateProcessStepParams {
+    pub clothing_id: i32,
+    #[validate(length(min = 1, max = 32, message = "name must be 1 to 32 characters"))]
+    pub name: String,
+    pub description: Option<String>,
+    #[validate(range(min = 1, message = "sequence must be at least 1"))]
+    pub sequence: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateProcessStepParams {
+    #[validate(length(min = 1, max = 32, message = "name must be 1 to 32 characters"))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[validate(range(min = 1, message = "sequence must be at least 1"))]
+    pub sequence: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessStepListQueryParams {
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub clothing_ids: Option<Vec<i32>>,
+
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+}
+
+impl Sortable for ProcessStepListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::Name, Column::Sequence, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressQueryParams {
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub clothing_ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StepProgress {
+    #[serde(flatten)]
+    pub process_step: Model,
+    // 已完成该工序的件数
+    pub completed: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClothingProgress {
+    pub clothing: clothing::Model,
+    // 进货收到的件数
+    pub received: i64,
+    pub steps: Vec<StepProgress>,
+}
+
+impl ProcessStepService {
+    fn workshop_clothing_ids(workshop_id: i32, clothing_ids: Option<Vec<i32>>) -> SelectStatement {
+        ClothingService::workshop_clothing_ids(workshop_id, None)
+            .and_where_option(clothing_ids.map(|ids| clothing::Column::Id.is_in(ids)))
+            .to_owned()
+    }
+
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        params: CreateProcessStepParams,
+    ) -> Result<Model, DbErr> {
+        ClothingService::find_by_id(db, workshop_id, params.clothing_id).await?;
+
+        // 不填顺序时排在该服装已有工序的最后
+        let sequence = match params.sequence {
+            Some(sequence) => sequence,
+            None => {
+                Entity::find()
+                    .select_only()
+                    .column_as(Expr::col(Column::Sequence).max(), "sequence")
+                    .filter(Column::ClothingId.eq(params.clothing_id))
+                    .into_tuple::<Option<i32>>()
+                    .one(db)
+                    .await?
+                    .flatten()
+                    .unwrap_or_default()
+                    + 1
+            }
+        };
+
+        let model = ActiveModel {
+            name: sea_orm::ActiveValue::Set(params.name),
+            description: sea_orm::ActiveValue::Set(params.description),
+            sequence: sea_orm::ActiveValue::Set(sequence),
+            clothing_id: sea_orm::ActiveValue::Set(params.clothing_id),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let model_clone = model.clone();
+        model.delete(db).await?;
+        Ok(model_clone)
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateProcessStepParams,
+    ) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let mut model = model.into_active_model();
+        if let Some(name) = params.name {
+            model.name = sea_orm::ActiveValue::Set(name);
+        }
+        if let Some(sequence) = params.sequence {
+            model.sequence = sea_orm::ActiveValue::Set(sequence);
+        }
+        if let Some(description) = params.description {
+            model.description = sea_orm::ActiveValue::Set(Some(description));
+        }
+
+        model.update(db).await
+    }
+
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(Column::ClothingId.in_subquery(Self::workshop_clothing_ids(workshop_id, None)))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("Cannot find process step".into()))
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+        clothing_ids: Option<Vec<i32>>,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(
+            Column::ClothingId.in_subquery(Self::workshop_clothing_ids(workshop_id, clothing_ids)),
+        );
+
+        if let Some(search) = &params.search {
+            select = select.filter(
+                Column::Name
+                    .contains(search)
+                    .or(Column::Description.contains(search)),
+            );
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::CreateAt.lt(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            ProcessStepListQueryParams::SORTABLE,
+            (Column::Sequence, SortOrder::Asc),
+            Column::Id,
+        )
+        .await
+    }
+
+    // 在制品报表: 每件服装各工序完成件数与进货件数对比
+    pub async fn progress(
+        db: &DbConn,
+        workshop_id: i32,
+        clothing_ids: Option<Vec<i32>>,
+    ) -> Result<Vec<ClothingProgress>, DbErr> {
+        let clothings = clothing::Entity::find()
+            .filter(
+                clothing::Column::Id
+                    .in_subquery(Self::workshop_clothing_ids(workshop_id, clothing_ids)),
+            )
+            .order_by_desc(clothing::Column::CreateAt)
+            .all(db)
+            .await?;
+        let ids = clothings.iter().map(|c| c.id).collect::<Vec<_>>();
+
+        let received: HashMap<i32, i64> = procurement_item::Entity::find()
+            .select_only()
+            .column(procurement_item::Column::ClothingId)
+            .column_as(
+                Expr::col(procurement_item::Column::Amount).sum(),
+                "received",
+            )
+            .filter(procurement_item::Column::ClothingId.is_in(ids.clone()))
+            .group_by(procurement_item::Column::ClothingId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        let steps = Entity::find()
+            .filter(Column::ClothingId.is_in(ids))
+            .order_by_asc(Column::Sequence)
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?;
+
+        let completed: HashMap<i32, i64> = production_item::Entity::find()
+            .select_only()
+            .column(production_item::Column::ProcessStepId)
+            .column_as(Expr::col(production_item::Column::Count).sum(), "completed")
+            .filter(production_item::Column::ProcessStepId.is_in(steps.iter().map(|s| s.id)))
+            .group_by(production_item::Column::ProcessStepId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut steps_by_clothing: HashMap<i32, Vec<StepProgress>> = HashMap::new();
+        for process_step in steps {
+            let completed = completed.get(&process_step.id).copied().unwrap_or_default();
+            steps_by_clothing
+                .entry(process_step.clothing_id)
+                .or_default()
+                .push(StepProgress {
+                    process_step,
+                    completed,
+                });
+        }
+
+        Ok(clothings
+            .into_iter()
+            .map(|clothing| ClothingProgress {
+                received: received.get(&clothing.id).copied().unwrap_or_default(),
+                steps: steps_by_clothing.remove(&clothing.id).unwrap_or_default(),
+                clothing,
+            })
+            .collect())
+    }
+}
@@ -1,137 +1,349 @@
-use crate::{ListQueryParams, ListResult};
-use ::entity::{procurement, procurement_item};
-use futures::{stream, StreamExt};
+use std::collections::BTreeMap;
+
+use crate::{
+    accounting_period::AccountingPeriodService,
+    clothing::ClothingService,
+    expand::{expand_items, Expand, Item},
+    material::MaterialService,
+    pagination::paginate,
+    supplier::SupplierService,
+    ListQueryParams, ListResult, SortOrder, Sortable,
+};
+use ::entity::{procurement, procurement_item, procurement_material};
 use sea_orm::DbConn;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
 
 pub struct ProcurementService;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateProcurementItem {
+    #[validate(range(min = 1, message = "amount must be at least 1"))]
     amount: i32,
     clothing_id: i32,
+    // 服装没有记录成本, 进货时必须填写单价
+    #[validate(range(min = 0.0, message = "unit_cost must not be negative"))]
+    unit_cost: f32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+// 面料, 线, 纽扣等物料
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateProcurementMaterial {
+    #[validate(range(exclusive_min = 0.0, message = "amount must be greater than 0"))]
+    amount: f32,
+    material_id: i32,
+    // 不填时使用物料当前的单价, 填写时同时更新物料单价
+    #[validate(range(min = 0.0, message = "unit_cost must not be negative"))]
+    unit_cost: Option<f32>,
+}
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+#[validate(schema(function = "validate_lines"))]
 pub struct CreateProcurementParams {
     pub description: Option<String>,
+    pub supplier_id: Option<i32>,
+    #[serde(default)]
+    #[validate(nested)]
     pub items: Vec<CreateProcurementItem>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub materials: Vec<CreateProcurementMaterial>,
+}
+
+// 服装和物料至少要有一项
+fn validate_lines(params: &CreateProcurementParams) -> Result<(), ValidationError> {
+    if params.items.is_empty() && params.materials.is_empty() {
+        Err(ValidationError::new("items")
+            .with_message("items and materials must not both be empty".into()))
+    } else {
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct UpdateProcurementParams {
     pub description: Option<String>,
+    pub supplier_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcurementListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub supplier_ids: Option<Vec<i32>>,
+}
+
+impl Sortable for ProcurementListQueryParams {
+    type Column = procurement::Column;
+
+    const SORTABLE: &'static [procurement::Column] = &[procurement::Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct Procurement {
     #[serde(flatten)]
     procurement: procurement::Model,
-    items: Vec<procurement_item::Model>,
+    items: Vec<Item<procurement_item::Model>>,
+    materials: Vec<procurement_material::Model>,
 }
 
 impl ProcurementService {
     async fn find_procurement_items(
         db: &DbConn,
         procurement_id: i32,
-    ) -> Result<Vec<procurement_item::Model>, DbErr> {
-        procurement_item::Entity::find()
+    ) -> Result<Vec<Item<procurement_item::Model>>, DbErr> {
+        Ok(procurement_item::Entity::find()
             .filter(procurement_item::Column::ProcurementId.eq(procurement_id))
             .all(db)
+            .await?
+            .into_iter()
+            .map(Item::from)
+            .collect())
+    }
+
+    async fn find_procurement_materials(
+        db: &DbConn,
+        procurement_id: i32,
+    ) -> Result<Vec<procurement_material::Model>, DbErr> {
+        procurement_material::Entity::find()
+            .filter(procurement_material::Column::ProcurementId.eq(procurement_id))
+            .all(db)
             .await
     }
 
     pub async fn create(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         params: CreateProcurementParams,
     ) -> Result<Procurement, DbErr> {
-        let procurement = procurement::ActiveModel {
-            description: Set(params.description),
-            user_id: Set(user_id),
-            ..Default::default()
-        };
-        let procurement = procurement.insert(db).await?;
+        if let Some(supplier_id) = params.supplier_id {
+            SupplierService::find_by_id(db, workshop_id, supplier_id).await?;
+        }
+        ClothingService::find_by_ids(
+            db,
+            workshop_id,
+            params.items.iter().map(|item| item.clothing_id),
+        )
+        .await?;
+        let materials = MaterialService::find_by_ids(
+            db,
+            workshop_id,
+            params.materials.iter().map(|item| item.material_id),
+        )
+        .await?;
 
-        let procurement_items = params
+        let items = params
             .items
             .into_iter()
-            .map(|item| procurement_item::ActiveModel {
-                amount: Set(item.amount),
-                clothing_id: Set(item.clothing_id),
-                procurement_id: Set(procurement.id),
-                ..Default::default()
+            .map(|item| {
+                let unit_cost = item.unit_cost;
+                (item, unit_cost)
+            })
+            .collect::<Vec<_>>();
+        let material_items = params
+            .materials
+            .into_iter()
+            .map(|item| {
+                let unit_cost = item
+                    .unit_cost
+                    .or_else(|| materials.get(&item.material_id).map(|m| m.cost))
+                    .unwrap_or_default();
+                (item, unit_cost)
             })
             .collect::<Vec<_>>();
 
-        procurement_item::Entity::insert_many(procurement_items)
-            .exec(db)
-            .await?;
+        // 进货总成本 = 服装成本 + 物料成本
+        let total_cost = items
+            .iter()
+            .map(|(item, unit_cost)| unit_cost * item.amount as f32)
+            .chain(
+                material_items
+                    .iter()
+                    .map(|(item, unit_cost)| unit_cost * item.amount),
+            )
+            .sum::<f32>();
+
+        let txn = db.begin().await?;
+
+        let procurement = procurement::ActiveModel {
+            description: Set(params.description),
+            supplier_id: Set(params.supplier_id),
+            total_cost: Set(total_cost),
+            workshop_id: Set(workshop_id),
+            ..Default::default()
+        };
+        let procurement = procurement.insert(&txn).await?;
+
+        if !items.is_empty() {
+            let procurement_items = items
+                .into_iter()
+                .map(|(item, unit_cost)| procurement_item::ActiveModel {
+                    amount: Set(item.amount),
+                    clothing_id: Set(item.clothing_id),
+                    procurement_id: Set(procurement.id),
+                    unit_cost: Set(unit_cost),
+                    total_cost: Set(unit_cost * item.amount as f32),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+
+            procurement_item::Entity::insert_many(procurement_items)
+                .exec(&txn)
+                .await?;
+        }
+
+        // 物料入库, 填写了单价的同时更新物料单价
+        for (item, _) in material_items.iter() {
+            MaterialService::add_stock(&txn, item.material_id, item.amount).await?;
+            if let Some(unit_cost) = item.unit_cost {
+                MaterialService::set_cost(&txn, item.material_id, unit_cost).await?;
+            }
+        }
+        if !material_items.is_empty() {
+            let procurement_materials = material_items
+                .into_iter()
+                .map(|(item, unit_cost)| procurement_material::ActiveModel {
+                    amount: Set(item.amount),
+                    material_id: Set(item.material_id),
+                    procurement_id: Set(procurement.id),
+                    unit_cost: Set(unit_cost),
+                    total_cost: Set(unit_cost * item.amount),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+
+            procurement_material::Entity::insert_many(procurement_materials)
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
 
         let items = Self::find_procurement_items(db, procurement.id).await?;
+        let materials = Self::find_procurement_materials(db, procurement.id).await?;
 
-        Ok(Procurement { procurement, items })
+        Ok(Procurement {
+            procurement,
+            items,
+            materials,
+        })
     }
 
-    pub async fn find_by_id(db: &DbConn, user_id: i32, id: i32) -> Result<Procurement, DbErr> {
+    pub async fn find_by_id(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        expand: Expand,
+    ) -> Result<Procurement, DbErr> {
         let procurement = procurement::Entity::find_by_id(id)
-            .filter(procurement::Column::UserId.eq(user_id))
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
             .one(db)
             .await?
             .ok_or(DbErr::RecordNotFound("procurement not found".into()))?;
 
-        let items = Self::find_procurement_items(db, procurement.id).await?;
+        let mut items = Self::find_procurement_items(db, procurement.id).await?;
+        expand_items(db, workshop_id, expand, items.iter_mut()).await?;
+        let materials = Self::find_procurement_materials(db, procurement.id).await?;
 
-        Ok(Procurement { procurement, items })
+        Ok(Procurement {
+            procurement,
+            items,
+            materials,
+        })
     }
 
-    pub async fn delete(db: &DbConn, user_id: i32, id: i32) -> Result<Procurement, DbErr> {
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Procurement, DbErr> {
         let procurement = procurement::Entity::find_by_id(id)
-            .filter(procurement::Column::UserId.eq(user_id))
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
             .one(db)
             .await?
             .ok_or(DbErr::RecordNotFound("procurement not found".into()))?;
+        AccountingPeriodService::check_open(db, workshop_id, procurement.create_at).await?;
 
         let items = Self::find_procurement_items(db, procurement.id).await?;
+        let materials = Self::find_procurement_materials(db, procurement.id).await?;
 
-        procurement.clone().delete(db).await?;
+        // 删除进货单时扣回入库的物料, 已经用掉的话库存不够扣, 拒绝删除
+        let mut amounts: BTreeMap<i32, f32> = BTreeMap::new();
+        for item in materials.iter() {
+            *amounts.entry(item.material_id).or_default() += item.amount;
+        }
+        let txn = db.begin().await?;
+        for (material_id, amount) in amounts {
+            MaterialService::take_stock(&txn, material_id, amount).await?;
+        }
+        procurement.clone().delete(&txn).await?;
+        txn.commit().await?;
 
-        Ok(Procurement { procurement, items })
+        Ok(Procurement {
+            procurement,
+            items,
+            materials,
+        })
     }
 
     pub async fn update(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         id: i32,
         params: UpdateProcurementParams,
     ) -> Result<Procurement, DbErr> {
         let procurement = procurement::Entity::find_by_id(id)
-            .filter(procurement::Column::UserId.eq(user_id))
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
             .one(db)
             .await?
             .ok_or(DbErr::RecordNotFound("procurement not found".into()))?;
+        AccountingPeriodService::check_open(db, workshop_id, procurement.create_at).await?;
+
+        if let Some(supplier_id) = params.supplier_id {
+            SupplierService::find_by_id(db, workshop_id, supplier_id).await?;
+        }
 
         let mut procurement = procurement.into_active_model();
-        procurement.description = Set(params.description);
+        if let Some(description) = params.description {
+            procurement.description = Set(Some(description));
+        }
+        if let Some(supplier_id) = params.supplier_id {
+            procurement.supplier_id = Set(Some(supplier_id));
+        }
 
         let procurement = procurement.update(db).await?;
         let items = Self::find_procurement_items(db, procurement.id).await?;
+        let materials = Self::find_procurement_materials(db, procurement.id).await?;
 
-        Ok(Procurement { procurement, items })
+        Ok(Procurement {
+            procurement,
+            items,
+            materials,
+        })
     }
 
-    pub async fn find_by_user_id(
+    pub async fn find_by_workshop_id(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         params: ListQueryParams,
+        supplier_ids: Option<Vec<i32>>,
+        expand: Expand,
     ) -> Result<ListResult<Procurement>, DbErr> {
-        let mut select = procurement::Entity::find().order_by_desc(procurement::Column::CreateAt);
+        let mut select = procurement::Entity::find();
+
+        select = select.filter(procurement::Column::WorkshopId.eq(workshop_id));
 
-        select = select.filter(procurement::Column::UserId.eq(user_id));
+        if let Some(supplier_ids) = supplier_ids {
+            select = select.filter(procurement::Column::SupplierId.is_in(supplier_ids));
+        }
 
-        if let Some(search) = params.search {
-            select = select.filter(procurement::Column::Description.contains(&search));
+        if let Some(search) = &params.search {
+            select = select.filter(procurement::Column::Description.contains(search));
         }
 
         if let Some(start_time) = params.start_time {
@@ -142,20 +354,44 @@ impl ProcurementService {
             select = select.filter(procurement::Column::CreateAt.lt(end_time));
         }
 
-        let total = select.clone().count(db).await?;
-        let (page, page_size) = (params.page.unwrap(), params.page_size.unwrap());
+        let ListResult {
+            total,
+            data,
+            next_cursor,
+        } = paginate(
+            db,
+            select,
+            &params,
+            ProcurementListQueryParams::SORTABLE,
+            (procurement::Column::CreateAt, SortOrder::Desc),
+            procurement::Column::Id,
+        )
+        .await?;
 
-        let data = stream::iter(select.paginate(db, page_size).fetch_page(page).await?)
-            .filter_map(|procurement| async {
-                if let Ok(items) = Self::find_procurement_items(db, procurement.id).await {
-                    Some(Procurement { procurement, items })
-                } else {
-                    None
-                }
+        // 一次查询当前页所有单据的明细
+        let mut items = data
+            .load_many(procurement_item::Entity, db)
+            .await?
+            .into_iter()
+            .map(|items| items.into_iter().map(Item::from).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        expand_items(db, workshop_id, expand, items.iter_mut().flatten()).await?;
+        let materials = data.load_many(procurement_material::Entity, db).await?;
+        let data = data
+            .into_iter()
+            .zip(items)
+            .zip(materials)
+            .map(|((procurement, items), materials)| Procurement {
+                procurement,
+                items,
+                materials,
             })
-            .collect::<Vec<_>>()
-            .await;
+            .collect();
 
-        Ok(ListResult { total, data })
+        Ok(ListResult {
+            total,
+            data,
+            next_cursor,
+        })
     }
 }
@@ -1,28 +1,57 @@
+use std::collections::HashMap;
+
+use ::entity::inspection;
+use ::entity::material_consumption;
+use ::entity::process_step;
 use ::entity::production;
 use ::entity::production_item;
+use ::entity::production_settlement;
+pub use ::entity::sea_orm_active_enums::ProductionKind;
+use ::entity::sea_orm_active_enums::SettlementAction;
+use ::entity::shipment_return_item;
+
+use chrono::Utc;
 
-use futures::stream;
-use futures::StreamExt;
 use sea_orm::*;
-use sea_query::Query;
+use sea_query::{Expr, Query};
 use serde::{Deserialize, Serialize};
-
+use validator::Validate;
+
+use crate::accounting_period::AccountingPeriodService;
+use crate::clothing::ClothingService;
+use crate::expand::{expand_items, Expand, Item};
+use crate::material::MaterialService;
+use crate::pagination::paginate;
+use crate::shipment_return::ShipmentReturnService;
 use crate::staff;
+use crate::workshop::WorkshopService;
 use crate::ListQueryParams;
 use crate::ListResult;
+use crate::SortOrder;
+use crate::Sortable;
 pub struct ProductionService;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateProductionItem {
+    #[validate(range(min = 0.0, message = "uint_price must not be negative"))]
     pub uint_price: f32,
+    #[validate(range(min = 1, message = "count must be at least 1"))]
     pub count: i32,
     pub clothing_id: i32,
+    pub process_step_id: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateProductionParams {
     pub description: Option<String>,
     pub staff_id: i32,
+    // 返工按工坊的返工系数计算工价, 默认为正常生产
+    pub kind: Option<ProductionKind>,
+    // 返工对应的退货单
+    pub shipment_return_id: Option<i32>,
+    // 默认不能为停用的员工录入生产记录
+    pub allow_inactive: Option<bool>,
+    #[validate(length(min = 1, message = "items must not be empty"), nested)]
     pub items: Vec<CreateProductionItem>,
 }
 
@@ -31,37 +60,84 @@ pub struct UpdateProductionParams {
     pub description: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SettleProductionParams {
+    // 为空时结算全部未结算的明细
+    pub item_ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UnsettleProductionParams {
+    // 为空时撤销全部已结算的明细
+    pub item_ids: Option<Vec<i32>>,
+    #[validate(length(min = 1, message = "reason must not be empty"))]
+    pub reason: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct Production {
     #[serde(flatten)]
     pub production: production::Model,
-    pub items: Vec<production_item::Model>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staff: Option<::entity::staff::Model>,
+    pub items: Vec<Item<production_item::Model>>,
+    // 按物料清单消耗的物料
+    pub materials: Vec<material_consumption::Model>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct ProductionListQueryParams {
     #[serde(flatten)]
+    #[validate(nested)]
     pub list_query: ListQueryParams,
 
     #[serde(deserialize_with = "crate::utils::parse_svc", default)]
     pub staff_ids: Option<Vec<i32>>,
+
+    pub kind: Option<ProductionKind>,
+}
+
+impl Sortable for ProductionListQueryParams {
+    type Column = production::Column;
+
+    const SORTABLE: &'static [production::Column] = &[
+        production::Column::CreateAt,
+        production::Column::TotalSalary,
+    ];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
 }
 
 impl ProductionService {
     async fn find_production_items(
         db: &DbConn,
         production_id: i32,
-    ) -> Result<Vec<production_item::Model>, DbErr> {
-        production_item::Entity::find()
+    ) -> Result<Vec<Item<production_item::Model>>, DbErr> {
+        Ok(production_item::Entity::find()
             .filter(production_item::Column::ProductionId.eq(production_id))
             .all(db)
+            .await?
+            .into_iter()
+            .map(Item::from)
+            .collect())
+    }
+
+    async fn find_material_consumption(
+        db: &DbConn,
+        production_id: i32,
+    ) -> Result<Vec<material_consumption::Model>, DbErr> {
+        material_consumption::Entity::find()
+            .filter(material_consumption::Column::ProductionId.eq(production_id))
+            .all(db)
             .await
     }
 
     async fn find_production(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         id: i32,
     ) -> Result<production::Model, DbErr> {
         production::Entity::find_by_id(id)
@@ -69,7 +145,7 @@ impl ProductionService {
                 production::Column::StaffId.in_subquery(
                     Query::select()
                         .column(::entity::staff::Column::Id)
-                        .and_where(::entity::staff::Column::UserId.eq(user_id))
+                        .and_where(::entity::staff::Column::WorkshopId.eq(workshop_id))
                         .from(::entity::staff::Entity)
                         .to_owned(),
                 ),
@@ -79,66 +155,284 @@ impl ProductionService {
             .ok_or(DbErr::RecordNotFound("production not found".to_string()))
     }
 
+    // 生产明细及所属的生产记录
+    pub(crate) async fn find_item(
+        db: &DbConn,
+        workshop_id: i32,
+        item_id: i32,
+    ) -> Result<(production_item::Model, production::Model), DbErr> {
+        production_item::Entity::find_by_id(item_id)
+            .find_also_related(production::Entity)
+            .filter(
+                production::Column::StaffId
+                    .in_subquery(staff::StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .one(db)
+            .await?
+            .and_then(|(item, production)| production.map(|production| (item, production)))
+            .ok_or(DbErr::RecordNotFound(
+                "production item not found".to_string(),
+            ))
+    }
+
+    // 按质检合格的件数重新计算计件工资, passed 为 None 时按生产件数计算
+    pub(crate) async fn recalculate_salary<C: ConnectionTrait>(
+        db: &C,
+        item: production_item::Model,
+        passed: Option<i32>,
+    ) -> Result<(), DbErr> {
+        let production_id = item.production_id;
+        let salary = item.uint_price * passed.unwrap_or(item.count) as f32;
+
+        let mut item = item.into_active_model();
+        item.salary = Set(salary);
+        item.update(db).await?;
+
+        let total_salary = production_item::Entity::find()
+            .select_only()
+            .column_as(Expr::col(production_item::Column::Salary).sum(), "salary")
+            .filter(production_item::Column::ProductionId.eq(production_id))
+            .into_tuple::<Option<f32>>()
+            .one(db)
+            .await?
+            .flatten()
+            .unwrap_or_default();
+
+        production::Entity::update_many()
+            .col_expr(production::Column::TotalSalary, Expr::value(total_salary))
+            .filter(production::Column::Id.eq(production_id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    // 切换是否按合格件数计薪后, 重新计算未结算明细的计件工资, 已结账期间的生产记录保持不变
+    pub(crate) async fn recalculate_unsettled<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        pay_passed_only: bool,
+    ) -> Result<(), DbErr> {
+        let items = production_item::Entity::find()
+            .find_also_related(production::Entity)
+            .filter(
+                production::Column::StaffId
+                    .in_subquery(staff::StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .filter(production_item::Column::Settled.eq(false))
+            .all(db)
+            .await?;
+
+        let passed: HashMap<i32, i32> = if pay_passed_only {
+            inspection::Entity::find()
+                .select_only()
+                .column(inspection::Column::ProductionItemId)
+                .column(inspection::Column::Passed)
+                .filter(
+                    inspection::Column::ProductionItemId
+                        .is_in(items.iter().map(|(item, _)| item.id)),
+                )
+                .into_tuple::<(i32, i32)>()
+                .all(db)
+                .await?
+                .into_iter()
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let mut closed = HashMap::new();
+        for (item, production) in items {
+            let Some(production) = production else {
+                continue;
+            };
+            let is_closed = match closed.get(&production.id) {
+                Some(is_closed) => *is_closed,
+                None => {
+                    let is_closed =
+                        AccountingPeriodService::is_closed(db, workshop_id, production.create_at)
+                            .await?;
+                    closed.insert(production.id, is_closed);
+                    is_closed
+                }
+            };
+            if is_closed {
+                continue;
+            }
+
+            let passed = passed.get(&item.id).copied();
+            Self::recalculate_salary(db, item, passed).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn create(
         db: &DatabaseConnection,
-        user_id: i32,
+        workshop_id: i32,
         params: CreateProductionParams,
     ) -> Result<Production, DbErr> {
-        let staff = staff::StaffService::find_by_id(db, user_id, params.staff_id).await?;
+        let staff = staff::StaffService::find_model(db, workshop_id, params.staff_id).await?;
+        if staff.status == staff::StaffStatus::Inactive && params.allow_inactive != Some(true) {
+            return Err(DbErr::Custom("staff is inactive".to_string()));
+        }
+
+        // 服装必须属于当前工坊, 否则会扣减其他工坊的物料
+        ClothingService::find_by_ids(
+            db,
+            workshop_id,
+            params.items.iter().map(|item| item.clothing_id),
+        )
+        .await?;
+
+        // 工序必须属于对应的服装
+        let step_ids = params
+            .items
+            .iter()
+            .filter_map(|item| item.process_step_id)
+            .collect::<Vec<_>>();
+        if !step_ids.is_empty() {
+            let steps = process_step::Entity::find()
+                .filter(process_step::Column::Id.is_in(step_ids))
+                .all(db)
+                .await?;
+            let matched = params.items.iter().all(|item| match item.process_step_id {
+                Some(step_id) => steps
+                    .iter()
+                    .any(|step| step.id == step_id && step.clothing_id == item.clothing_id),
+                None => true,
+            });
+            if !matched {
+                return Err(DbErr::RecordNotFound("process step not found".to_string()));
+            }
+        }
+
+        let kind = params.kind.unwrap_or(ProductionKind::Normal);
+        let rate = match kind {
+            ProductionKind::Normal => {
+                if params.shipment_return_id.is_some() {
+                    return Err(DbErr::Custom(
+                        "only rework production can reference a shipment return".to_string(),
+                    ));
+                }
+                1.0
+            }
+            ProductionKind::Rework => {
+                WorkshopService::find_by_id(db, workshop_id)
+                    .await?
+                    .rework_rate
+            }
+        };
+
+        // 返工的服装必须在退货单中
+        if let Some(shipment_return_id) = params.shipment_return_id {
+            let shipment_return =
+                ShipmentReturnService::find_model(db, workshop_id, shipment_return_id).await?;
+            let returned = shipment_return_item::Entity::find()
+                .filter(shipment_return_item::Column::ShipmentReturnId.eq(shipment_return.id))
+                .all(db)
+                .await?;
+            let matched = params.items.iter().all(|item| {
+                returned
+                    .iter()
+                    .any(|returned| returned.clothing_id == item.clothing_id)
+            });
+            if !matched {
+                return Err(DbErr::RecordNotFound(
+                    "clothing not found in shipment return".to_string(),
+                ));
+            }
+        }
 
         let total_salary = params
             .items
             .iter()
-            .map(|item| item.uint_price * item.count as f32)
+            .map(|item| item.uint_price * rate * item.count as f32)
             .sum::<f32>();
 
+        let txn = db.begin().await?;
+
         let production = production::ActiveModel {
             description: ActiveValue::Set(params.description),
             staff_id: ActiveValue::Set(staff.id),
             total_salary: ActiveValue::Set(total_salary),
+            kind: ActiveValue::Set(kind.clone()),
+            shipment_return_id: ActiveValue::Set(params.shipment_return_id),
             ..Default::default()
         };
 
-        let production = production.insert(db).await?;
+        let production = production.insert(&txn).await?;
+
+        // 返工不再消耗物料
+        if kind == ProductionKind::Normal {
+            let counts = params
+                .items
+                .iter()
+                .map(|item| (item.clothing_id, item.count))
+                .collect::<Vec<_>>();
+            MaterialService::consume(&txn, workshop_id, production.id, &counts).await?;
+        }
 
         let procurement_items = params
             .items
             .into_iter()
             .map(|item| production_item::ActiveModel {
-                uint_price: Set(item.uint_price),
+                uint_price: Set(item.uint_price * rate),
                 count: Set(item.count),
                 production_id: Set(production.id),
-                salary: Set(item.uint_price * item.count as f32),
+                salary: Set(item.uint_price * rate * item.count as f32),
                 clothing_id: Set(item.clothing_id),
+                process_step_id: Set(item.process_step_id),
                 ..Default::default()
             })
             .collect::<Vec<_>>();
 
         production_item::Entity::insert_many(procurement_items)
-            .exec(db)
+            .exec(&txn)
             .await?;
 
-        let items = Self::find_production_items(db, production.id).await?;
+        txn.commit().await?;
 
-        Ok(Production { production, items })
+        let items = Self::find_production_items(db, production.id).await?;
+        let materials = Self::find_material_consumption(db, production.id).await?;
+
+        Ok(Production {
+            production,
+            staff: None,
+            items,
+            materials,
+        })
     }
 
-    pub async fn delete(db: &DbConn, user_id: i32, id: i32) -> Result<Production, DbErr> {
-        let production = Self::find_production(db, user_id, id).await?;
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Production, DbErr> {
+        let production = Self::find_production(db, workshop_id, id).await?;
+        AccountingPeriodService::check_open(db, workshop_id, production.create_at).await?;
 
         let items = Self::find_production_items(db, production.id).await?;
-        production.clone().delete(db).await?;
-
-        Ok(Production { production, items })
+        let materials = Self::find_material_consumption(db, production.id).await?;
+
+        // 归还消耗的物料
+        let txn = db.begin().await?;
+        MaterialService::restore(&txn, production.id).await?;
+        production.clone().delete(&txn).await?;
+        txn.commit().await?;
+
+        Ok(Production {
+            production,
+            staff: None,
+            items,
+            materials,
+        })
     }
 
     pub async fn update(
         db: &DatabaseConnection,
-        user_id: i32,
+        workshop_id: i32,
         id: i32,
         params: UpdateProductionParams,
     ) -> Result<Production, DbErr> {
-        let production = Self::find_production(db, user_id, id).await?;
+        let production = Self::find_production(db, workshop_id, id).await?;
+        AccountingPeriodService::check_open(db, workshop_id, production.create_at).await?;
 
         let mut production = production.into_active_model();
         production.description = Set(params.description);
@@ -146,57 +440,225 @@ impl ProductionService {
         let production = production.update(db).await?;
 
         let items = Self::find_production_items(db, production.id).await?;
-
-        Ok(Production { production, items })
+        let materials = Self::find_material_consumption(db, production.id).await?;
+
+        Ok(Production {
+            production,
+            staff: None,
+            items,
+            materials,
+        })
     }
 
-    pub async fn settle(
-        db: &DatabaseConnection,
+    // 结算或撤销结算部分明细, 同时更新生产记录的已结算金额并记录操作
+    async fn set_settled(
+        db: &DbConn,
+        workshop_id: i32,
         user_id: i32,
         id: i32,
+        item_ids: Option<Vec<i32>>,
+        settled: bool,
+        reason: Option<String>,
     ) -> Result<Production, DbErr> {
-        let production = Self::find_production(db, user_id, id).await?;
+        let production = Self::find_production(db, workshop_id, id).await?;
+        // 结算金额计入生产记录所在期间, 已结账期间不能再结算或反结算
+        AccountingPeriodService::check_open(db, workshop_id, production.create_at).await?;
 
-        let mut production = production.into_active_model();
+        let items = production_item::Entity::find()
+            .filter(production_item::Column::ProductionId.eq(production.id))
+            .all(db)
+            .await?;
+        if let Some(id) = item_ids
+            .iter()
+            .flatten()
+            .find(|id| !items.iter().any(|item| item.id == **id))
+        {
+            return Err(DbErr::RecordNotFound(format!(
+                "production item {id} not found"
+            )));
+        }
+
+        let changed = items
+            .iter()
+            .filter(|item| item.settled != settled)
+            .filter(|item| item_ids.as_ref().is_none_or(|ids| ids.contains(&item.id)))
+            .map(|item| item.id)
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            return Err(DbErr::Custom(if settled {
+                "no unsettled items to settle".to_string()
+            } else {
+                "no settled items to unsettle".to_string()
+            }));
+        }
 
-        production.settled = Set(true);
+        let is_settled = |item: &production_item::Model| {
+            if changed.contains(&item.id) {
+                settled
+            } else {
+                item.settled
+            }
+        };
+        let amount = items
+            .iter()
+            .filter(|item| changed.contains(&item.id))
+            .map(|item| item.salary)
+            .sum::<f32>();
+        let settled_amount = items
+            .iter()
+            .filter(|item| is_settled(item))
+            .map(|item| item.salary)
+            .sum::<f32>();
+        let all_settled = items.iter().all(is_settled);
+        let any_settled = items.iter().any(is_settled);
 
-        let production = production.update(db).await?;
+        let txn = db.begin().await?;
 
-        let items = Self::find_production_items(db, production.id).await?;
+        production_item::Entity::update_many()
+            .col_expr(production_item::Column::Settled, Expr::value(settled))
+            .filter(production_item::Column::Id.is_in(changed))
+            .exec(&txn)
+            .await?;
 
-        Ok(Production { production, items })
-    }
+        let mut production = production.into_active_model();
+        production.settled = Set(all_settled);
+        production.settled_amount = Set(settled_amount);
+        if settled {
+            production.settled_at = Set(Some(Utc::now().into()));
+        } else if !any_settled {
+            production.settled_at = Set(None);
+        }
+        let production = production.update(&txn).await?;
+
+        let settlement = production_settlement::ActiveModel {
+            action: Set(if settled {
+                SettlementAction::Settle
+            } else {
+                SettlementAction::Unsettle
+            }),
+            amount: Set(amount),
+            reason: Set(reason),
+            production_id: Set(production.id),
+            user_id: Set(Some(user_id)),
+            ..Default::default()
+        };
+        settlement.insert(&txn).await?;
 
-    pub async fn find_by_id(db: &DbConn, user_id: i32, id: i32) -> Result<Production, DbErr> {
-        let production = Self::find_production(db, user_id, id).await?;
+        txn.commit().await?;
 
         let items = Self::find_production_items(db, production.id).await?;
+        let materials = Self::find_material_consumption(db, production.id).await?;
+
+        Ok(Production {
+            production,
+            staff: None,
+            items,
+            materials,
+        })
+    }
 
-        Ok(Production { production, items })
+    pub async fn settle(
+        db: &DbConn,
+        workshop_id: i32,
+        user_id: i32,
+        id: i32,
+        params: SettleProductionParams,
+    ) -> Result<Production, DbErr> {
+        Self::set_settled(db, workshop_id, user_id, id, params.item_ids, true, None).await
     }
 
-    pub async fn find_by_user_id(
+    // 撤销结算, 必须填写原因
+    pub async fn unsettle(
         db: &DbConn,
+        workshop_id: i32,
         user_id: i32,
+        id: i32,
+        params: UnsettleProductionParams,
+    ) -> Result<Production, DbErr> {
+        Self::set_settled(
+            db,
+            workshop_id,
+            user_id,
+            id,
+            params.item_ids,
+            false,
+            Some(params.reason),
+        )
+        .await
+    }
+
+    // 结算和撤销结算记录
+    pub async fn find_settlements(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+    ) -> Result<Vec<production_settlement::Model>, DbErr> {
+        let production = Self::find_production(db, workshop_id, id).await?;
+
+        production_settlement::Entity::find()
+            .filter(production_settlement::Column::ProductionId.eq(production.id))
+            .order_by_asc(production_settlement::Column::CreateAt)
+            .order_by_asc(production_settlement::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_by_id(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        expand: Expand,
+    ) -> Result<Production, DbErr> {
+        let production = Self::find_production(db, workshop_id, id).await?;
+
+        let mut items = Self::find_production_items(db, production.id).await?;
+        expand_items(db, workshop_id, expand, items.iter_mut()).await?;
+
+        let staff = if expand.staff {
+            ::entity::staff::Entity::find_by_id(production.staff_id)
+                .one(db)
+                .await?
+        } else {
+            None
+        };
+
+        let materials = Self::find_material_consumption(db, production.id).await?;
+
+        Ok(Production {
+            production,
+            staff,
+            items,
+            materials,
+        })
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
         params: ListQueryParams,
         staff_ids: Option<Vec<i32>>,
+        kind: Option<ProductionKind>,
+        expand: Expand,
     ) -> Result<ListResult<Production>, DbErr> {
-        let mut select = production::Entity::find().order_by_desc(production::Column::CreateAt);
+        let mut select = production::Entity::find();
 
         select = select.filter(
             production::Column::StaffId.in_subquery(
                 Query::select()
                     .column(::entity::staff::Column::Id)
-                    .and_where(::entity::staff::Column::UserId.eq(user_id))
+                    .and_where(::entity::staff::Column::WorkshopId.eq(workshop_id))
                     .and_where_option(staff_ids.map(|ids| ::entity::staff::Column::Id.is_in(ids)))
                     .from(::entity::staff::Entity)
                     .to_owned(),
             ),
         );
 
-        if let Some(search) = params.search {
-            select = select.filter(production::Column::Description.contains(&search));
+        if let Some(kind) = kind {
+            select = select.filter(production::Column::Kind.eq(kind));
+        }
+
+        if let Some(search) = &params.search {
+            select = select.filter(production::Column::Description.contains(search));
         }
 
         if let Some(start_time) = params.start_time {
@@ -207,20 +669,51 @@ impl ProductionService {
             select = select.filter(production::Column::CreateAt.lt(end_time));
         }
 
-        let total = select.clone().count(db).await?;
-        let (page, page_size) = (params.page.unwrap(), params.page_size.unwrap());
-
-        let data = stream::iter(select.paginate(db, page_size).fetch_page(page).await?)
-            .filter_map(|production| async {
-                if let Ok(items) = Self::find_production_items(db, production.id).await {
-                    Some(Production { production, items })
-                } else {
-                    None
-                }
+        let ListResult {
+            total,
+            data,
+            next_cursor,
+        } = paginate(
+            db,
+            select,
+            &params,
+            ProductionListQueryParams::SORTABLE,
+            (production::Column::CreateAt, SortOrder::Desc),
+            production::Column::Id,
+        )
+        .await?;
+
+        // 一次查询当前页所有单据的明细
+        let mut items = data
+            .load_many(production_item::Entity, db)
+            .await?
+            .into_iter()
+            .map(|items| items.into_iter().map(Item::from).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        expand_items(db, workshop_id, expand, items.iter_mut().flatten()).await?;
+        let staffs = if expand.staff {
+            data.load_one(::entity::staff::Entity, db).await?
+        } else {
+            vec![None; data.len()]
+        };
+        let materials = data.load_many(material_consumption::Entity, db).await?;
+        let data = data
+            .into_iter()
+            .zip(staffs)
+            .zip(items)
+            .zip(materials)
+            .map(|(((production, staff), items), materials)| Production {
+                production,
+                staff,
+                items,
+                materials,
             })
-            .collect::<Vec<_>>()
-            .await;
+            .collect();
 
-        Ok(ListResult { total, data })
+        Ok(ListResult {
+            total,
+            data,
+            next_cursor,
+        })
     }
 }
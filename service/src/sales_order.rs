@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+
+use crate::{
+    boss::BossService,
+    expand::{expand_items, ClothingItem, Expand, Item},
+    pagination::paginate,
+    shipment::{ShipmentStatus, SHIPPED},
+    ListQueryParams, ListResult, SortOrder, Sortable,
+};
+use ::entity::{clothing, sales_order, sales_order_item, shipment, shipment_item};
+use chrono::{FixedOffset, Utc};
+use sea_orm::{prelude::Date, *};
+use sea_query::{Expr, Query};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+pub struct SalesOrderService;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateSalesOrderItem {
+    #[validate(range(min = 1, message = "amount must be at least 1"))]
+    amount: i32,
+    clothing_id: i32,
+}
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+pub struct CreateSalesOrderParams {
+    pub boss_id: i32,
+    // 交货日期
+    pub due_date: Date,
+    pub description: Option<String>,
+    #[validate(length(min = 1, message = "items must not be empty"), nested)]
+    pub items: Vec<CreateSalesOrderItem>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UpdateSalesOrderParams {
+    pub due_date: Option<Date>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SalesOrderListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub boss_ids: Option<Vec<i32>>,
+}
+
+impl Sortable for SalesOrderListQueryParams {
+    type Column = sales_order::Column;
+
+    const SORTABLE: &'static [sales_order::Column] =
+        &[sales_order::Column::CreateAt, sales_order::Column::DueDate];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+// 订单状态由分配到订单明细的已发货数量计算, 不单独存储
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SalesOrderStatus {
+    Open,
+    PartiallyShipped,
+    Fulfilled,
+    Overdue,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SalesOrderItem {
+    #[serde(flatten)]
+    pub item: sales_order_item::Model,
+    pub shipped: i64,
+    // 未发数量, 超发时为0
+    pub remaining: i64,
+}
+
+impl ClothingItem for SalesOrderItem {
+    fn clothing_id(&self) -> i32 {
+        self.item.clothing_id
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SalesOrder {
+    #[serde(flatten)]
+    order: sales_order::Model,
+    status: SalesOrderStatus,
+    items: Vec<Item<SalesOrderItem>>,
+}
+
+// 按东八区计算当天日期, 用于判断订单是否逾期
+fn today() -> Date {
+    match FixedOffset::east_opt(8 * 60 * 60) {
+        Some(offset) => Utc::now().with_timezone(&offset).date_naive(),
+        None => Utc::now().date_naive(),
+    }
+}
+
+impl SalesOrder {
+    fn new(order: sales_order::Model, items: Vec<Item<SalesOrderItem>>, today: Date) -> Self {
+        let status = if items.iter().all(|item| item.item.remaining == 0) {
+            SalesOrderStatus::Fulfilled
+        } else if order.due_date < today {
+            SalesOrderStatus::Overdue
+        } else if items.iter().any(|item| item.item.shipped > 0) {
+            SalesOrderStatus::PartiallyShipped
+        } else {
+            SalesOrderStatus::Open
+        };
+
+        SalesOrder {
+            order,
+            status,
+            items,
+        }
+    }
+}
+
+impl SalesOrderService {
+    // 各订单明细分配到指定状态出货单的数量
+    async fn shipped<C: ConnectionTrait>(
+        db: &C,
+        item_ids: impl IntoIterator<Item = i32>,
+        statuses: impl IntoIterator<Item = ShipmentStatus>,
+    ) -> Result<HashMap<i32, i64>, DbErr> {
+        Ok(shipment_item::Entity::find()
+            .select_only()
+            .column(shipment_item::Column::SalesOrderItemId)
+            .column_as(
+                Expr::col((shipment_item::Entity, shipment_item::Column::Amount)).sum(),
+                "shipped",
+            )
+            .join(JoinType::InnerJoin, shipment_item::Relation::Shipment.def())
+            .filter(shipment_item::Column::SalesOrderItemId.is_in(item_ids))
+            .filter(shipment::Column::Status.is_in(statuses))
+            .group_by(shipment_item::Column::SalesOrderItemId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    fn with_shipped(
+        items: Vec<sales_order_item::Model>,
+        shipped: &HashMap<i32, i64>,
+    ) -> Vec<Item<SalesOrderItem>> {
+        items
+            .into_iter()
+            .map(|item| {
+                let shipped = shipped.get(&item.id).copied().unwrap_or_default();
+                let remaining = (item.amount as i64 - shipped).max(0);
+                Item::from(SalesOrderItem {
+                    item,
+                    shipped,
+                    remaining,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_sales_order_items(
+        db: &DbConn,
+        sales_order_id: i32,
+    ) -> Result<Vec<Item<SalesOrderItem>>, DbErr> {
+        let items = sales_order_item::Entity::find()
+            .filter(sales_order_item::Column::SalesOrderId.eq(sales_order_id))
+            .order_by_asc(sales_order_item::Column::Id)
+            .all(db)
+            .await?;
+
+        let shipped = Self::shipped(db, items.iter().map(|item| item.id), SHIPPED).await?;
+
+        Ok(Self::with_shipped(items, &shipped))
+    }
+
+    async fn find_model(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+    ) -> Result<sales_order::Model, DbErr> {
+        sales_order::Entity::find_by_id(id)
+            .filter(sales_order::Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("sales order not found".into()))
+    }
+
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        params: CreateSalesOrderParams,
+    ) -> Result<SalesOrder, DbErr> {
+        BossService::find_by_id(db, workshop_id, params.boss_id).await?;
+
+        // 订单中的服装必须属于下单的老板
+        let clothing_ids = params
+            .items
+            .iter()
+            .map(|item| item.clothing_id)
+            .collect::<Vec<_>>();
+        let found = clothing::Entity::find()
+            .filter(clothing::Column::BossId.eq(params.boss_id))
+            .filter(clothing::Column::Id.is_in(clothing_ids.clone()))
+            .all(db)
+            .await?;
+        if let Some(id) = clothing_ids
+            .iter()
+            .find(|id| !found.iter().any(|clothing| clothing.id == **id))
+        {
+            return Err(DbErr::RecordNotFound(format!(
+                "clothing {id} not found for boss"
+            )));
+        }
+
+        let txn = db.begin().await?;
+
+        let order = sales_order::ActiveModel {
+            boss_id: Set(params.boss_id),
+            due_date: Set(params.due_date),
+            description: Set(params.description),
+            workshop_id: Set(workshop_id),
+            ..Default::default()
+        };
+        let order = order.insert(&txn).await?;
+
+        let order_items = params
+            .items
+            .into_iter()
+            .map(|item| sales_order_item::ActiveModel {
+                amount: Set(item.amount),
+                clothing_id: Set(item.clothing_id),
+                sales_order_id: Set(order.id),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        sales_order_item::Entity::insert_many(order_items)
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        let items = Self::find_sales_order_items(db, order.id).await?;
+
+        Ok(SalesOrder::new(order, items, today()))
+    }
+
+    pub async fn find_by_id(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        expand: Expand,
+    ) -> Result<SalesOrder, DbErr> {
+        let order = Self::find_model(db, workshop_id, id).await?;
+
+        let mut items = Self::find_sales_order_items(db, order.id).await?;
+        expand_items(db, workshop_id, expand, items.iter_mut()).await?;
+
+        Ok(SalesOrder::new(order, items, today()))
+    }
+
+    // 删除订单后已分配的出货明细变为未分配
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<SalesOrder, DbErr> {
+        let order = Self::find_model(db, workshop_id, id).await?;
+
+        let items = Self::find_sales_order_items(db, order.id).await?;
+
+        order.clone().delete(db).await?;
+
+        Ok(SalesOrder::new(order, items, today()))
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateSalesOrderParams,
+    ) -> Result<SalesOrder, DbErr> {
+        let order = Self::find_model(db, workshop_id, id).await?;
+
+        let mut order = order.into_active_model();
+        if let Some(due_date) = params.due_date {
+            order.due_date = Set(due_date);
+        }
+        order.description = Set(params.description);
+
+        let order = order.update(db).await?;
+        let items = Self::find_sales_order_items(db, order.id).await?;
+
+        Ok(SalesOrder::new(order, items, today()))
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+        boss_ids: Option<Vec<i32>>,
+        expand: Expand,
+    ) -> Result<ListResult<SalesOrder>, DbErr> {
+        let mut select = sales_order::Entity::find();
+
+        select = select.filter(sales_order::Column::WorkshopId.eq(workshop_id));
+
+        if let Some(boss_ids) = boss_ids {
+            select = select.filter(sales_order::Column::BossId.is_in(boss_ids));
+        }
+
+        if let Some(search) = &params.search {
+            select = select.filter(sales_order::Column::Description.contains(search));
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(sales_order::Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(sales_order::Column::CreateAt.lt(end_time));
+        }
+
+        let ListResult {
+            total,
+            data,
+            next_cursor,
+        } = paginate(
+            db,
+            select,
+            &params,
+            SalesOrderListQueryParams::SORTABLE,
+            (sales_order::Column::CreateAt, SortOrder::Desc),
+            sales_order::Column::Id,
+        )
+        .await?;
+
+        // 一次查询当前页所有订单的明细和出货数量
+        let order_items = data.load_many(sales_order_item::Entity, db).await?;
+        let shipped = Self::shipped(
+            db,
+            order_items.iter().flatten().map(|item| item.id),
+            SHIPPED,
+        )
+        .await?;
+        let mut items = order_items
+            .into_iter()
+            .map(|items| Self::with_shipped(items, &shipped))
+            .collect::<Vec<_>>();
+        expand_items(db, workshop_id, expand, items.iter_mut().flatten()).await?;
+
+        let today = today();
+        let data = data
+            .into_iter()
+            .zip(items)
+            .map(|(order, items)| SalesOrder::new(order, items, today))
+            .collect();
+
+        Ok(ListResult {
+            total,
+            data,
+            next_cursor,
+        })
+    }
+
+    // 检查出货明细能否分配到订单明细: 订单属于当前工坊, 服装一致, 且不超过未发数量
+    pub(crate) async fn check_allocation<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        allocations: impl IntoIterator<Item = (i32, i32, i32)>,
+    ) -> Result<(), DbErr> {
+        let mut amounts: HashMap<i32, (i32, i64)> = HashMap::new();
+        for (sales_order_item_id, clothing_id, amount) in allocations {
+            let entry = amounts
+                .entry(sales_order_item_id)
+                .or_insert((clothing_id, 0));
+            if entry.0 != clothing_id {
+                return Err(DbErr::Custom(format!(
+                    "clothing does not match sales order item {sales_order_item_id}"
+                )));
+            }
+            entry.1 += amount as i64;
+        }
+        if amounts.is_empty() {
+            return Ok(());
+        }
+
+        let items = sales_order_item::Entity::find()
+            .filter(sales_order_item::Column::Id.is_in(amounts.keys().copied()))
+            .filter(
+                sales_order_item::Column::SalesOrderId.in_subquery(
+                    Query::select()
+                        .column(sales_order::Column::Id)
+                        .from(sales_order::Entity)
+                        .and_where(sales_order::Column::WorkshopId.eq(workshop_id))
+                        .to_owned(),
+                ),
+            )
+            .all(db)
+            .await?;
+        // 未发货的出货单也占用订单数量, 退回的不占用
+        let allocated = Self::shipped(
+            db,
+            items.iter().map(|item| item.id),
+            [
+                ShipmentStatus::Draft,
+                ShipmentStatus::Packed,
+                ShipmentStatus::Dispatched,
+                ShipmentStatus::Delivered,
+            ],
+        )
+        .await?;
+
+        for (id, (clothing_id, amount)) in amounts {
+            let item = items
+                .iter()
+                .find(|item| item.id == id)
+                .ok_or(DbErr::RecordNotFound(format!(
+                    "sales order item {id} not found"
+                )))?;
+            if item.clothing_id != clothing_id {
+                return Err(DbErr::Custom(format!(
+                    "clothing does not match sales order item {id}"
+                )));
+            }
+            let remaining = item.amount as i64 - allocated.get(&id).copied().unwrap_or_default();
+            if amount > remaining {
+                return Err(DbErr::Custom(format!(
+                    "amount exceeds remaining quantity of sales order item {id}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
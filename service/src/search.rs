@@ -0,0 +1,155 @@
+use ::entity::{boss, clothing, procurement, production, shipment, staff};
+use sea_orm::*;
+use sea_query::{extension::postgres::PgExpr, Expr, SimpleExpr};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{clothing::ClothingService, staff::StaffService};
+pub struct SearchService;
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQueryParams {
+    #[validate(length(min = 1, message = "q must not be empty"))]
+    pub q: String,
+
+    // 每类最多返回的条数
+    #[serde(
+        default = "crate::utils::default_page_size",
+        deserialize_with = "crate::utils::parse_option_u64"
+    )]
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct SearchResult {
+    pub bosses: Vec<boss::Model>,
+    pub staffs: Vec<staff::Model>,
+    pub clothings: Vec<clothing::Model>,
+    pub procurements: Vec<procurement::Model>,
+    pub shipments: Vec<shipment::Model>,
+    pub productions: Vec<production::Model>,
+}
+
+// 拼音首字母, 例如 "连衣裙" => "lyq", 字母数字转小写, 其余字符忽略
+pub(crate) fn pinyin_initials(s: &str) -> String {
+    s.chars()
+        .filter_map(|c| match c {
+            c if c.is_ascii_alphanumeric() => Some(c.to_ascii_lowercase()),
+            c if c.is_ascii() => None,
+            c => deunicode::deunicode_char(c)?
+                .chars()
+                .next()
+                .filter(char::is_ascii_alphanumeric)
+                .map(|c| c.to_ascii_lowercase()),
+        })
+        .collect()
+}
+
+// 不区分大小写的包含匹配, 可以用上 pg_trgm 索引
+fn matches<C: ColumnTrait>(column: C, pattern: &str) -> SimpleExpr {
+    Expr::col(column).ilike(pattern)
+}
+
+impl SearchService {
+    pub async fn search(
+        db: &DbConn,
+        workshop_id: i32,
+        q: &str,
+        limit: u64,
+    ) -> Result<SearchResult, DbErr> {
+        let q = q.trim();
+        // 只有空白字符时没有可匹配的内容
+        if q.is_empty() {
+            return Ok(SearchResult::default());
+        }
+
+        let pattern = format!(
+            "%{}%",
+            q.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let pinyin = pattern.to_lowercase();
+
+        let bosses = boss::Entity::find()
+            .filter(boss::Column::WorkshopId.eq(workshop_id))
+            .filter(
+                Condition::any()
+                    .add(matches(boss::Column::Name, &pattern))
+                    .add(matches(boss::Column::PhoneNumber, &pattern))
+                    .add(matches(boss::Column::Description, &pattern))
+                    .add(boss::Column::Pinyin.like(&pinyin)),
+            )
+            .order_by_desc(boss::Column::CreateAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        let staffs = staff::Entity::find()
+            .filter(staff::Column::WorkshopId.eq(workshop_id))
+            .filter(
+                Condition::any()
+                    .add(matches(staff::Column::Name, &pattern))
+                    .add(matches(staff::Column::PhoneNumber, &pattern))
+                    .add(matches(staff::Column::Description, &pattern))
+                    .add(staff::Column::Pinyin.like(&pinyin)),
+            )
+            .order_by_desc(staff::Column::CreateAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        let clothings = clothing::Entity::find()
+            .filter(
+                clothing::Column::Id
+                    .in_subquery(ClothingService::workshop_clothing_ids(workshop_id, None)),
+            )
+            .filter(
+                Condition::any()
+                    .add(matches(clothing::Column::Name, &pattern))
+                    .add(matches(clothing::Column::Description, &pattern))
+                    .add(clothing::Column::Pinyin.like(&pinyin)),
+            )
+            .order_by_desc(clothing::Column::CreateAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        let procurements = procurement::Entity::find()
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
+            .filter(matches(procurement::Column::Description, &pattern))
+            .order_by_desc(procurement::Column::CreateAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        let shipments = shipment::Entity::find()
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .filter(matches(shipment::Column::Description, &pattern))
+            .order_by_desc(shipment::Column::CreateAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        let productions = production::Entity::find()
+            .filter(
+                production::Column::StaffId
+                    .in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .filter(matches(production::Column::Description, &pattern))
+            .order_by_desc(production::Column::CreateAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        Ok(SearchResult {
+            bosses,
+            staffs,
+            clothings,
+            procurements,
+            shipments,
+            productions,
+        })
+    }
+}
@@ -1,21 +1,55 @@
-use crate::{ListQueryParams, ListResult};
-use ::entity::{shipment, shipment_item};
-use futures::{stream, StreamExt};
+use std::collections::HashMap;
+
+use crate::{
+    accounting_period::AccountingPeriodService,
+    clothing::ClothingService,
+    expand::{expand_items, Expand, Item},
+    pagination::paginate,
+    sales_order::SalesOrderService,
+    ListQueryParams, ListResult, SortOrder, Sortable,
+};
+pub use ::entity::sea_orm_active_enums::ShipmentStatus;
+use ::entity::{clothing, shipment, shipment_event, shipment_item, shipment_return};
 use sea_orm::DbConn;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
 pub struct ShipmentService;
 
-#[derive(Debug, Deserialize, Clone)]
+// 已发货和已签收的出货单计入出货数量并扣减库存
+pub(crate) const SHIPPED: [ShipmentStatus; 2] =
+    [ShipmentStatus::Dispatched, ShipmentStatus::Delivered];
+
+// 允许的状态变更, 签收需要通过签收确认
+fn can_transition(from: &ShipmentStatus, to: &ShipmentStatus) -> bool {
+    use ShipmentStatus::*;
+
+    matches!(
+        (from, to),
+        (Draft, Packed)
+            | (Draft, Dispatched)
+            | (Packed, Draft)
+            | (Packed, Dispatched)
+            | (Dispatched, Delivered)
+            | (Dispatched, Returned)
+            | (Delivered, Returned)
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateShipmentItem {
+    #[validate(range(min = 1, message = "amount must be at least 1"))]
     amount: i32,
     clothing_id: i32,
+    // 分配到的订单明细
+    sales_order_item_id: Option<i32>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Validate)]
 pub struct CreateShipmentParams {
     pub description: Option<String>,
+    #[validate(length(min = 1, message = "items must not be empty"), nested)]
     pub items: Vec<CreateShipmentItem>,
 }
 
@@ -24,35 +58,133 @@ pub struct UpdateShipmentParams {
     pub description: Option<String>,
 }
 
+// 修改出货明细分配的订单明细, 为空时取消分配
+#[derive(Debug, Deserialize, Clone)]
+pub struct AllocateShipmentItemParams {
+    pub sales_order_item_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UpdateShipmentStatusParams {
+    pub status: ShipmentStatus,
+}
+
+// 签收确认, 记录签收人和签名图片
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConfirmDeliveryParams {
+    pub receiver: String,
+    pub signature: Option<String>,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct Shipment {
     #[serde(flatten)]
     shipment: shipment::Model,
-    items: Vec<shipment_item::Model>,
+    items: Vec<Item<shipment_item::Model>>,
+    events: Vec<shipment_event::Model>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipmentListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+}
+
+impl Sortable for ShipmentListQueryParams {
+    type Column = shipment::Column;
+
+    const SORTABLE: &'static [shipment::Column] = &[shipment::Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
 }
 
 impl ShipmentService {
     async fn find_shipment_items(
         db: &DbConn,
         shipment_id: i32,
-    ) -> Result<Vec<shipment_item::Model>, DbErr> {
-        shipment_item::Entity::find()
+    ) -> Result<Vec<Item<shipment_item::Model>>, DbErr> {
+        Ok(shipment_item::Entity::find()
             .filter(shipment_item::Column::ShipmentId.eq(shipment_id))
             .all(db)
+            .await?
+            .into_iter()
+            .map(Item::from)
+            .collect())
+    }
+
+    async fn find_shipment_events(
+        db: &DbConn,
+        shipment_id: i32,
+    ) -> Result<Vec<shipment_event::Model>, DbErr> {
+        shipment_event::Entity::find()
+            .filter(shipment_event::Column::ShipmentId.eq(shipment_id))
+            .order_by_asc(shipment_event::Column::CreateAt)
+            .order_by_asc(shipment_event::Column::Id)
+            .all(db)
             .await
     }
 
+    // 不属于当前工坊时返回 RecordNotFound
+    pub async fn find_model(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+    ) -> Result<shipment::Model, DbErr> {
+        shipment::Entity::find_by_id(id)
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("shipment not found".into()))
+    }
+
+    // 出货单按发货时间归属结账期间, 未发货的出货单不影响任何期间
+    async fn check_open(
+        db: &DbConn,
+        workshop_id: i32,
+        shipment: &shipment::Model,
+    ) -> Result<(), DbErr> {
+        if let Some(dispatched_at) = shipment.dispatched_at {
+            AccountingPeriodService::check_open(db, workshop_id, dispatched_at).await?;
+        }
+        Ok(())
+    }
+
+    // 新建的出货单为草稿状态, 不扣减库存
     pub async fn create(
         db: &DbConn,
+        workshop_id: i32,
         user_id: i32,
         params: CreateShipmentParams,
     ) -> Result<Shipment, DbErr> {
+        ClothingService::find_by_ids(
+            db,
+            workshop_id,
+            params.items.iter().map(|item| item.clothing_id),
+        )
+        .await?;
+        SalesOrderService::check_allocation(
+            db,
+            workshop_id,
+            params.items.iter().filter_map(|item| {
+                item.sales_order_item_id
+                    .map(|id| (id, item.clothing_id, item.amount))
+            }),
+        )
+        .await?;
+
         let shipment = shipment::ActiveModel {
             description: Set(params.description),
-            user_id: Set(user_id),
+            workshop_id: Set(workshop_id),
+            status: Set(ShipmentStatus::Draft),
             ..Default::default()
         };
-        let shipment = shipment.insert(db).await?;
+
+        let txn = db.begin().await?;
+        let shipment = shipment.insert(&txn).await?;
 
         let shipment_items = params
             .items
@@ -61,65 +193,87 @@ impl ShipmentService {
                 amount: Set(item.amount),
                 clothing_id: Set(item.clothing_id),
                 shipment_id: Set(shipment.id),
+                sales_order_item_id: Set(item.sales_order_item_id),
                 ..Default::default()
             })
             .collect::<Vec<_>>();
 
         shipment_item::Entity::insert_many(shipment_items)
-            .exec(db)
+            .exec(&txn)
             .await?;
 
+        shipment_event::ActiveModel {
+            status: Set(ShipmentStatus::Draft),
+            shipment_id: Set(shipment.id),
+            user_id: Set(Some(user_id)),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+        txn.commit().await?;
+
         let items = Self::find_shipment_items(db, shipment.id).await?;
 
+        let events = Self::find_shipment_events(db, shipment.id).await?;
+
         Ok(Shipment {
             shipment,
             items,
+            events,
         })
     }
 
-    pub async fn find_by_id(db: &DbConn, user_id: i32, id: i32) -> Result<Shipment, DbErr> {
-        let shipment = shipment::Entity::find_by_id(id)
-            .filter(shipment::Column::UserId.eq(user_id))
-            .one(db)
-            .await?
-            .ok_or(DbErr::RecordNotFound("shipment not found".into()))?;
+    pub async fn find_by_id(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        expand: Expand,
+    ) -> Result<Shipment, DbErr> {
+        let shipment = Self::find_model(db, workshop_id, id).await?;
 
-        let items = Self::find_shipment_items(db, shipment.id).await?;
+        let mut items = Self::find_shipment_items(db, shipment.id).await?;
+        expand_items(db, workshop_id, expand, items.iter_mut()).await?;
+
+        let events = Self::find_shipment_events(db, shipment.id).await?;
 
         Ok(Shipment {
             shipment,
             items,
+            events,
         })
     }
 
-    pub async fn delete(db: &DbConn, user_id: i32, id: i32) -> Result<Shipment, DbErr> {
-        let shipment = shipment::Entity::find_by_id(id)
-            .filter(shipment::Column::UserId.eq(user_id))
-            .one(db)
-            .await?
-            .ok_or(DbErr::RecordNotFound("shipment not found".into()))?;
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Shipment, DbErr> {
+        let shipment = Self::find_model(db, workshop_id, id).await?;
+        Self::check_open(db, workshop_id, &shipment).await?;
+
+        // 已发货的出货单已经扣减库存, 只能退回不能删除
+        if SHIPPED.contains(&shipment.status) {
+            return Err(DbErr::Custom(
+                "dispatched shipment cannot be deleted, return it instead".into(),
+            ));
+        }
 
         let items = Self::find_shipment_items(db, shipment.id).await?;
+        let events = Self::find_shipment_events(db, shipment.id).await?;
 
         shipment.clone().delete(db).await?;
 
         Ok(Shipment {
             shipment,
             items,
+            events,
         })
     }
 
     pub async fn update(
         db: &DbConn,
-        user_id: i32,
+        workshop_id: i32,
         id: i32,
         params: UpdateShipmentParams,
     ) -> Result<Shipment, DbErr> {
-        let shipment = shipment::Entity::find_by_id(id)
-            .filter(shipment::Column::UserId.eq(user_id))
-            .one(db)
-            .await?
-            .ok_or(DbErr::RecordNotFound("shipment not found".into()))?;
+        let shipment = Self::find_model(db, workshop_id, id).await?;
+        Self::check_open(db, workshop_id, &shipment).await?;
 
         let mut shipment = shipment.into_active_model();
         shipment.description = Set(params.description);
@@ -127,20 +281,224 @@ impl ShipmentService {
         let shipment = shipment.update(db).await?;
         let items = Self::find_shipment_items(db, shipment.id).await?;
 
-        Ok(Shipment { shipment, items })
+        let events = Self::find_shipment_events(db, shipment.id).await?;
+
+        Ok(Shipment {
+            shipment,
+            items,
+            events,
+        })
+    }
+
+    // 变更状态并记录操作人
+    // 发货前检查库存, 锁住涉及的服装, 同时发货的出货单不会一起超卖
+    async fn check_stock<C: ConnectionTrait>(db: &C, shipment_id: i32) -> Result<(), DbErr> {
+        let mut required: HashMap<i32, i64> = HashMap::new();
+        for item in shipment_item::Entity::find()
+            .filter(shipment_item::Column::ShipmentId.eq(shipment_id))
+            .all(db)
+            .await?
+        {
+            *required.entry(item.clothing_id).or_default() += item.amount as i64;
+        }
+
+        let clothings = clothing::Entity::find()
+            .filter(clothing::Column::Id.is_in(required.keys().copied()))
+            .order_by_asc(clothing::Column::Id)
+            .lock_exclusive()
+            .all(db)
+            .await?;
+        for stock in ClothingService::stock_of(db, clothings).await? {
+            let amount = required[&stock.clothing.id];
+            if stock.stock < amount {
+                return Err(DbErr::Custom(format!(
+                    "{} is out of stock, {} left but {} to ship",
+                    stock.clothing.name, stock.stock, amount
+                )));
+            }
+        }
+        Ok(())
     }
 
-    pub async fn find_by_user_id(
+    async fn transition(
         db: &DbConn,
+        shipment: shipment::Model,
         user_id: i32,
+        status: ShipmentStatus,
+        delivery: Option<ConfirmDeliveryParams>,
+    ) -> Result<shipment::Model, DbErr> {
+        if !can_transition(&shipment.status, &status) {
+            return Err(DbErr::Custom(format!(
+                "cannot change shipment status from {:?} to {:?}",
+                shipment.status, status
+            )));
+        }
+
+        let txn = db.begin().await?;
+
+        if SHIPPED.contains(&status) && !SHIPPED.contains(&shipment.status) {
+            Self::check_stock(&txn, shipment.id).await?;
+        }
+
+        // 第一次发货时记录发货时间
+        let dispatched = shipment.dispatched_at.is_some();
+        let mut shipment = shipment.into_active_model();
+        if SHIPPED.contains(&status) && !dispatched {
+            shipment.dispatched_at = Set(Some(chrono::Utc::now().fixed_offset()));
+        }
+        shipment.status = Set(status.clone());
+        if let Some(delivery) = delivery {
+            shipment.receiver = Set(Some(delivery.receiver));
+            shipment.signature = Set(delivery.signature);
+        }
+        let shipment = shipment.update(&txn).await?;
+
+        shipment_event::ActiveModel {
+            status: Set(status),
+            shipment_id: Set(shipment.id),
+            user_id: Set(Some(user_id)),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+        Ok(shipment)
+    }
+
+    pub async fn set_status(
+        db: &DbConn,
+        workshop_id: i32,
+        user_id: i32,
+        id: i32,
+        params: UpdateShipmentStatusParams,
+    ) -> Result<Shipment, DbErr> {
+        if params.status == ShipmentStatus::Delivered {
+            return Err(DbErr::Custom(
+                "delivered status requires delivery confirmation".into(),
+            ));
+        }
+
+        let shipment = Self::find_model(db, workshop_id, id).await?;
+
+        // 已结账期间发货的出货单不能再退回
+        if SHIPPED.contains(&shipment.status) && !SHIPPED.contains(&params.status) {
+            Self::check_open(db, workshop_id, &shipment).await?;
+        }
+
+        // 整单退回会把全部出货加回库存, 已有退货单的出货单再整单退回会重复加回
+        if params.status == ShipmentStatus::Returned {
+            let returns = shipment_return::Entity::find()
+                .filter(shipment_return::Column::ShipmentId.eq(shipment.id))
+                .count(db)
+                .await?;
+            if returns > 0 {
+                return Err(DbErr::Custom(
+                    "shipment with return documents cannot be returned".into(),
+                ));
+            }
+        }
+
+        let shipment = Self::transition(db, shipment, user_id, params.status, None).await?;
+        let items = Self::find_shipment_items(db, shipment.id).await?;
+        let events = Self::find_shipment_events(db, shipment.id).await?;
+
+        Ok(Shipment {
+            shipment,
+            items,
+            events,
+        })
+    }
+
+    pub async fn confirm_delivery(
+        db: &DbConn,
+        workshop_id: i32,
+        user_id: i32,
+        id: i32,
+        params: ConfirmDeliveryParams,
+    ) -> Result<Shipment, DbErr> {
+        if params.receiver.trim().is_empty() {
+            return Err(DbErr::Custom("receiver must not be empty".into()));
+        }
+
+        let shipment = Self::find_model(db, workshop_id, id).await?;
+
+        // 签收不改变出货数量, 已结账期间的出货单也可以确认签收
+        let shipment = Self::transition(
+            db,
+            shipment,
+            user_id,
+            ShipmentStatus::Delivered,
+            Some(params),
+        )
+        .await?;
+        let items = Self::find_shipment_items(db, shipment.id).await?;
+        let events = Self::find_shipment_events(db, shipment.id).await?;
+
+        Ok(Shipment {
+            shipment,
+            items,
+            events,
+        })
+    }
+
+    pub async fn allocate(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        item_id: i32,
+        params: AllocateShipmentItemParams,
+    ) -> Result<Shipment, DbErr> {
+        let shipment = Self::find_model(db, workshop_id, id).await?;
+        Self::check_open(db, workshop_id, &shipment).await?;
+
+        let item = shipment_item::Entity::find_by_id(item_id)
+            .filter(shipment_item::Column::ShipmentId.eq(shipment.id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("shipment item not found".into()))?;
+
+        // 重新分配到同一订单明细时不重复计算本条出货数量
+        let txn = db.begin().await?;
+        let mut item = item.into_active_model();
+        item.sales_order_item_id = Set(None);
+        let item = item.update(&txn).await?;
+        if let Some(sales_order_item_id) = params.sales_order_item_id {
+            SalesOrderService::check_allocation(
+                &txn,
+                workshop_id,
+                [(sales_order_item_id, item.clothing_id, item.amount)],
+            )
+            .await?;
+        }
+        let mut item = item.into_active_model();
+        item.sales_order_item_id = Set(params.sales_order_item_id);
+        item.update(&txn).await?;
+        txn.commit().await?;
+
+        let items = Self::find_shipment_items(db, shipment.id).await?;
+
+        let events = Self::find_shipment_events(db, shipment.id).await?;
+
+        Ok(Shipment {
+            shipment,
+            items,
+            events,
+        })
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
         params: ListQueryParams,
+        expand: Expand,
     ) -> Result<ListResult<Shipment>, DbErr> {
-        let mut select = shipment::Entity::find().order_by_desc(shipment::Column::CreateAt);
+        let mut select = shipment::Entity::find();
 
-        select = select.filter(shipment::Column::UserId.eq(user_id));
+        select = select.filter(shipment::Column::WorkshopId.eq(workshop_id));
 
-        if let Some(search) = params.search {
-            select = select.filter(shipment::Column::Description.contains(&search));
+        if let Some(search) = &params.search {
+            select = select.filter(shipment::Column::Description.contains(search));
         }
 
         if let Some(start_time) = params.start_time {
@@ -151,20 +509,51 @@ impl ShipmentService {
             select = select.filter(shipment::Column::CreateAt.lt(end_time));
         }
 
-        let total = select.clone().count(db).await?;
-        let (page, page_size) = (params.page.unwrap(), params.page_size.unwrap());
+        let ListResult {
+            total,
+            data,
+            next_cursor,
+        } = paginate(
+            db,
+            select,
+            &params,
+            ShipmentListQueryParams::SORTABLE,
+            (shipment::Column::CreateAt, SortOrder::Desc),
+            shipment::Column::Id,
+        )
+        .await?;
 
-        let data = stream::iter(select.paginate(db, page_size).fetch_page(page).await?)
-            .filter_map(|shipment| async {
-                if let Ok(items) = Self::find_shipment_items(db, shipment.id).await {
-                    Some(Shipment { shipment, items })
-                } else {
-                    None
-                }
+        // 一次查询当前页所有单据的明细
+        let mut items = data
+            .load_many(shipment_item::Entity, db)
+            .await?
+            .into_iter()
+            .map(|items| items.into_iter().map(Item::from).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        expand_items(db, workshop_id, expand, items.iter_mut().flatten()).await?;
+        let events = data
+            .load_many(
+                shipment_event::Entity::find()
+                    .order_by_asc(shipment_event::Column::CreateAt)
+                    .order_by_asc(shipment_event::Column::Id),
+                db,
+            )
+            .await?;
+        let data = data
+            .into_iter()
+            .zip(items)
+            .zip(events)
+            .map(|((shipment, items), events)| Shipment {
+                shipment,
+                items,
+                events,
             })
-            .collect::<Vec<_>>()
-            .await;
+            .collect();
 
-        Ok(ListResult { total, data })
+        Ok(ListResult {
+            total,
+            data,
+            next_cursor,
+        })
     }
 }
@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+
+use crate::{
+    accounting_period::AccountingPeriodService,
+    clothing::ClothingService,
+    expand::{expand_items, Expand, Item},
+    pagination::paginate,
+    shipment::SHIPPED,
+    staff::StaffService,
+    ListQueryParams, ListResult, SortOrder, Sortable,
+};
+use ::entity::{
+    sea_orm_active_enums::AdjustmentKind, shipment, shipment_item, shipment_return,
+    shipment_return_item, staff, staff_adjustment,
+};
+use sea_orm::*;
+use sea_query::Expr;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+pub struct ShipmentReturnService;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateShipmentReturnItem {
+    shipment_item_id: i32,
+    #[validate(range(min = 1, message = "amount must be at least 1"))]
+    amount: i32,
+    // 责任员工, 填写扣款时必填
+    staff_id: Option<i32>,
+    #[validate(range(min = 0.0, message = "deduction must not be negative"))]
+    deduction: Option<f32>,
+}
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+pub struct CreateShipmentReturnParams {
+    pub shipment_id: i32,
+    pub description: Option<String>,
+    #[validate(length(min = 1, message = "items must not be empty"), nested)]
+    pub items: Vec<CreateShipmentReturnItem>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UpdateShipmentReturnParams {
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipmentReturnListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub shipment_ids: Option<Vec<i32>>,
+}
+
+impl Sortable for ShipmentReturnListQueryParams {
+    type Column = shipment_return::Column;
+
+    const SORTABLE: &'static [shipment_return::Column] = &[shipment_return::Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ShipmentReturn {
+    #[serde(flatten)]
+    shipment_return: shipment_return::Model,
+    items: Vec<Item<shipment_return_item::Model>>,
+}
+
+impl ShipmentReturnService {
+    async fn find_shipment_return_items(
+        db: &DbConn,
+        shipment_return_id: i32,
+    ) -> Result<Vec<Item<shipment_return_item::Model>>, DbErr> {
+        Ok(shipment_return_item::Entity::find()
+            .filter(shipment_return_item::Column::ShipmentReturnId.eq(shipment_return_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Item::from)
+            .collect())
+    }
+
+    pub(crate) async fn find_model(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+    ) -> Result<shipment_return::Model, DbErr> {
+        shipment_return::Entity::find_by_id(id)
+            .filter(shipment_return::Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("shipment return not found".into()))
+    }
+
+    // 各出货明细已退回的数量
+    async fn returned(
+        db: &DbConn,
+        shipment_item_ids: impl IntoIterator<Item = i32>,
+    ) -> Result<HashMap<i32, i64>, DbErr> {
+        Ok(shipment_return_item::Entity::find()
+            .select_only()
+            .column(shipment_return_item::Column::ShipmentItemId)
+            .column_as(
+                Expr::col(shipment_return_item::Column::Amount).sum(),
+                "returned",
+            )
+            .filter(shipment_return_item::Column::ShipmentItemId.is_in(shipment_item_ids))
+            .group_by(shipment_return_item::Column::ShipmentItemId)
+            .into_tuple::<(i32, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    // 退回的服装重新计入库存, 扣款记入责任员工的扣款
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        params: CreateShipmentReturnParams,
+    ) -> Result<ShipmentReturn, DbErr> {
+        let shipment = shipment::Entity::find_by_id(params.shipment_id)
+            .filter(shipment::Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("shipment not found".into()))?;
+        if !SHIPPED.contains(&shipment.status) {
+            return Err(DbErr::Custom(
+                "only dispatched shipments can be returned".into(),
+            ));
+        }
+
+        let shipment_items = shipment_item::Entity::find()
+            .filter(shipment_item::Column::ShipmentId.eq(shipment.id))
+            .all(db)
+            .await?;
+        ClothingService::find_by_ids(
+            db,
+            workshop_id,
+            shipment_items.iter().map(|item| item.clothing_id),
+        )
+        .await?;
+        let returned = Self::returned(db, shipment_items.iter().map(|item| item.id)).await?;
+
+        let mut amounts: HashMap<i32, i64> = HashMap::new();
+        for item in params.items.iter() {
+            *amounts.entry(item.shipment_item_id).or_default() += item.amount as i64;
+        }
+        for (id, amount) in amounts {
+            let shipment_item =
+                shipment_items
+                    .iter()
+                    .find(|item| item.id == id)
+                    .ok_or(DbErr::RecordNotFound(format!(
+                        "shipment item {id} not found"
+                    )))?;
+            let remaining =
+                shipment_item.amount as i64 - returned.get(&id).copied().unwrap_or_default();
+            if amount > remaining {
+                return Err(DbErr::Custom(format!(
+                    "amount exceeds shipped quantity of shipment item {id}"
+                )));
+            }
+        }
+
+        let staff_ids = params
+            .items
+            .iter()
+            .filter_map(|item| item.staff_id)
+            .collect::<Vec<_>>();
+        let staffs = staff::Entity::find()
+            .filter(
+                staff::Column::Id.in_subquery(StaffService::workshop_staff_ids(
+                    workshop_id,
+                    Some(staff_ids.clone()),
+                )),
+            )
+            .all(db)
+            .await?;
+        if let Some(id) = staff_ids
+            .iter()
+            .find(|id| !staffs.iter().any(|staff| staff.id == **id))
+        {
+            return Err(DbErr::RecordNotFound(format!("staff {id} not found")));
+        }
+        if params
+            .items
+            .iter()
+            .any(|item| item.deduction.unwrap_or_default() > 0.0 && item.staff_id.is_none())
+        {
+            return Err(DbErr::Custom(
+                "deduction requires a responsible staff".into(),
+            ));
+        }
+
+        let txn = db.begin().await?;
+
+        let shipment_return = shipment_return::ActiveModel {
+            description: Set(params.description),
+            shipment_id: Set(shipment.id),
+            workshop_id: Set(workshop_id),
+            ..Default::default()
+        };
+        let shipment_return = shipment_return.insert(&txn).await?;
+
+        let mut return_items = Vec::with_capacity(params.items.len());
+        for item in params.items {
+            let clothing_id = shipment_items
+                .iter()
+                .find(|shipment_item| shipment_item.id == item.shipment_item_id)
+                .map(|shipment_item| shipment_item.clothing_id)
+                .unwrap_or_default();
+            let deduction = item.deduction.unwrap_or_default();
+
+            let staff_adjustment_id = match item.staff_id {
+                Some(staff_id) if deduction > 0.0 => {
+                    let adjustment = staff_adjustment::ActiveModel {
+                        kind: Set(AdjustmentKind::Deduction),
+                        amount: Set(deduction),
+                        description: Set(Some(format!("退货扣款, 退货单 #{}", shipment_return.id))),
+                        staff_id: Set(staff_id),
+                        ..Default::default()
+                    };
+                    Some(adjustment.insert(&txn).await?.id)
+                }
+                _ => None,
+            };
+
+            return_items.push(shipment_return_item::ActiveModel {
+                amount: Set(item.amount),
+                deduction: Set(deduction),
+                shipment_return_id: Set(shipment_return.id),
+                shipment_item_id: Set(item.shipment_item_id),
+                clothing_id: Set(clothing_id),
+                staff_id: Set(item.staff_id),
+                staff_adjustment_id: Set(staff_adjustment_id),
+                ..Default::default()
+            });
+        }
+
+        shipment_return_item::Entity::insert_many(return_items)
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        let items = Self::find_shipment_return_items(db, shipment_return.id).await?;
+
+        Ok(ShipmentReturn {
+            shipment_return,
+            items,
+        })
+    }
+
+    pub async fn find_by_id(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        expand: Expand,
+    ) -> Result<ShipmentReturn, DbErr> {
+        let shipment_return = Self::find_model(db, workshop_id, id).await?;
+
+        let mut items = Self::find_shipment_return_items(db, shipment_return.id).await?;
+        expand_items(db, workshop_id, expand, items.iter_mut()).await?;
+
+        Ok(ShipmentReturn {
+            shipment_return,
+            items,
+        })
+    }
+
+    // 删除退货单同时删除未结算的扣款
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<ShipmentReturn, DbErr> {
+        let shipment_return = Self::find_model(db, workshop_id, id).await?;
+        AccountingPeriodService::check_open(db, workshop_id, shipment_return.create_at).await?;
+
+        let items = Self::find_shipment_return_items(db, shipment_return.id).await?;
+
+        let adjustment_ids = items
+            .iter()
+            .filter_map(|item| item.item.staff_adjustment_id)
+            .collect::<Vec<_>>();
+        let settled = staff_adjustment::Entity::find()
+            .filter(staff_adjustment::Column::Id.is_in(adjustment_ids.clone()))
+            .filter(staff_adjustment::Column::Settled.eq(true))
+            .count(db)
+            .await?;
+        if settled > 0 {
+            return Err(DbErr::Custom(
+                "deduction of this return has been settled".into(),
+            ));
+        }
+
+        let txn = db.begin().await?;
+        staff_adjustment::Entity::delete_many()
+            .filter(staff_adjustment::Column::Id.is_in(adjustment_ids))
+            .exec(&txn)
+            .await?;
+        shipment_return.clone().delete(&txn).await?;
+        txn.commit().await?;
+
+        Ok(ShipmentReturn {
+            shipment_return,
+            items,
+        })
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateShipmentReturnParams,
+    ) -> Result<ShipmentReturn, DbErr> {
+        let shipment_return = Self::find_model(db, workshop_id, id).await?;
+        AccountingPeriodService::check_open(db, workshop_id, shipment_return.create_at).await?;
+
+        let mut shipment_return = shipment_return.into_active_model();
+        shipment_return.description = Set(params.description);
+
+        let shipment_return = shipment_return.update(db).await?;
+        let items = Self::find_shipment_return_items(db, shipment_return.id).await?;
+
+        Ok(ShipmentReturn {
+            shipment_return,
+            items,
+        })
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+        shipment_ids: Option<Vec<i32>>,
+        expand: Expand,
+    ) -> Result<ListResult<ShipmentReturn>, DbErr> {
+        let mut select = shipment_return::Entity::find();
+
+        select = select.filter(shipment_return::Column::WorkshopId.eq(workshop_id));
+
+        if let Some(shipment_ids) = shipment_ids {
+            select = select.filter(shipment_return::Column::ShipmentId.is_in(shipment_ids));
+        }
+
+        if let Some(search) = &params.search {
+            select = select.filter(shipment_return::Column::Description.contains(search));
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(shipment_return::Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(shipment_return::Column::CreateAt.lt(end_time));
+        }
+
+        let ListResult {
+            total,
+            data,
+            next_cursor,
+        } = paginate(
+            db,
+            select,
+            &params,
+            ShipmentReturnListQueryParams::SORTABLE,
+            (shipment_return::Column::CreateAt, SortOrder::Desc),
+            shipment_return::Column::Id,
+        )
+        .await?;
+
+        // 一次查询当前页所有单据的明细
+        let mut items = data
+            .load_many(shipment_return_item::Entity, db)
+            .await?
+            .into_iter()
+            .map(|items| items.into_iter().map(Item::from).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        expand_items(db, workshop_id, expand, items.iter_mut().flatten()).await?;
+        let data = data
+            .into_iter()
+            .zip(items)
+            .map(|(shipment_return, items)| ShipmentReturn {
+                shipment_return,
+                items,
+            })
+            .collect();
+
+        Ok(ListResult {
+            total,
+            data,
+            next_cursor,
+        })
+    }
+}
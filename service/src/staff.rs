@@ -1,58 +1,263 @@
+pub use ::entity::sea_orm_active_enums::StaffStatus;
 use ::entity::staff::{ActiveModel, Column, Entity, Model};
-use sea_orm::*;
+use ::entity::{attendance, production, staff_adjustment};
+use sea_orm::{prelude::Date, *};
+use sea_query::{Query, SelectStatement};
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-use crate::{ListQueryParams, ListResult};
+use crate::{
+    accounting_period::AccountingPeriodService,
+    crypto::{mask, Cipher},
+    pagination::paginate,
+    search::pinyin_initials,
+    ListQueryParams, ListResult, SortOrder, Sortable,
+};
 pub struct StaffService;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateStaffParams {
+    #[validate(length(min = 1, max = 32, message = "name must be 1 to 32 characters"))]
     pub name: String,
+    #[validate(custom(function = "crate::utils::validate_phone_number"))]
     pub phone_number: String,
     pub description: Option<String>,
+    #[validate(custom(function = "crate::utils::validate_id_card"))]
+    pub id_card: Option<String>,
+    // 银行卡号或微信收款账号
+    #[validate(length(max = 64, message = "bank_account must be at most 64 characters"))]
+    pub bank_account: Option<String>,
+    pub hire_date: Option<Date>,
+    pub leave_date: Option<Date>,
+    pub status: Option<StaffStatus>,
 }
 
+// id_card 和 bank_account 传空字符串时清空
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateStaffParams {
     pub name: Option<String>,
     pub phone_number: Option<String>,
     pub description: Option<String>,
+    pub id_card: Option<String>,
+    pub bank_account: Option<String>,
+    pub hire_date: Option<Date>,
+    pub leave_date: Option<Date>,
+    pub status: Option<StaffStatus>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct StaffListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+
+    pub status: Option<StaffStatus>,
+}
+
+impl Sortable for StaffListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::Name, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+// 解密后的员工信息, 列表中敏感字段打码显示
+#[derive(Debug, Serialize)]
+pub struct Staff {
+    #[serde(flatten)]
+    pub staff: Model,
+    pub id_card: Option<String>,
+    pub bank_account: Option<String>,
+}
+
+impl Staff {
+    fn decrypt(cipher: &Cipher, staff: Model) -> Result<Self, DbErr> {
+        let id_card = staff
+            .id_card
+            .as_deref()
+            .map(|id_card| cipher.decrypt(id_card))
+            .transpose()?;
+        let bank_account = staff
+            .bank_account
+            .as_deref()
+            .map(|bank_account| cipher.decrypt(bank_account))
+            .transpose()?;
+        Ok(Staff {
+            staff,
+            id_card,
+            bank_account,
+        })
+    }
+
+    // 列表中单个员工解密失败时只隐藏该字段, 不影响整个列表
+    fn masked(cipher: &Cipher, staff: Model) -> Self {
+        let masked = |field: &str, value: Option<&str>| {
+            value.and_then(|value| match cipher.decrypt(value) {
+                Ok(value) => Some(mask(&value)),
+                Err(err) => {
+                    tracing::warn!("failed to decrypt {field} of staff {}: {err}", staff.id);
+                    None
+                }
+            })
+        };
+        let id_card = masked("id_card", staff.id_card.as_deref());
+        let bank_account = masked("bank_account", staff.bank_account.as_deref());
+        Staff {
+            staff,
+            id_card,
+            bank_account,
+        }
+    }
+}
+
+fn check_dates(hire_date: Option<Date>, leave_date: Option<Date>) -> Result<(), DbErr> {
+    match (hire_date, leave_date) {
+        (Some(hire_date), Some(leave_date)) if leave_date < hire_date => Err(DbErr::Custom(
+            "leave_date must not be earlier than hire_date".into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// 空字符串视为清空
+fn encrypt(cipher: &Cipher, value: String) -> Result<Option<String>, DbErr> {
+    if value.trim().is_empty() {
+        Ok(None)
+    } else {
+        cipher.encrypt(value.trim()).map(Some)
+    }
 }
 
 impl StaffService {
+    // 当前工坊的员工id子查询
+    pub(crate) fn workshop_staff_ids(
+        workshop_id: i32,
+        staff_ids: Option<Vec<i32>>,
+    ) -> SelectStatement {
+        Query::select()
+            .column(Column::Id)
+            .and_where(Column::WorkshopId.eq(workshop_id))
+            .and_where_option(staff_ids.map(|ids| Column::Id.is_in(ids)))
+            .from(Entity)
+            .to_owned()
+    }
+
+    pub(crate) async fn find_model(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("Cannot find staff".into()))
+    }
+
     pub async fn create(
         db: &DbConn,
-        user_id: i32,
+        cipher: &Cipher,
+        workshop_id: i32,
         params: CreateStaffParams,
-    ) -> Result<Model, DbErr> {
+    ) -> Result<Staff, DbErr> {
+        check_dates(params.hire_date, params.leave_date)?;
+
         let model = ActiveModel {
+            pinyin: sea_orm::ActiveValue::Set(pinyin_initials(&params.name)),
             name: sea_orm::ActiveValue::Set(params.name),
             phone_number: sea_orm::ActiveValue::Set(params.phone_number),
             description: sea_orm::ActiveValue::Set(params.description),
-            user_id: sea_orm::ActiveValue::Set(user_id),
+            workshop_id: sea_orm::ActiveValue::Set(workshop_id),
+            id_card: sea_orm::ActiveValue::Set(
+                params
+                    .id_card
+                    .map(|id_card| encrypt(cipher, id_card))
+                    .transpose()?
+                    .flatten(),
+            ),
+            bank_account: sea_orm::ActiveValue::Set(
+                params
+                    .bank_account
+                    .map(|bank_account| encrypt(cipher, bank_account))
+                    .transpose()?
+                    .flatten(),
+            ),
+            hire_date: sea_orm::ActiveValue::Set(params.hire_date),
+            leave_date: sea_orm::ActiveValue::Set(params.leave_date),
+            status: sea_orm::ActiveValue::Set(params.status.unwrap_or(StaffStatus::Active)),
             ..Default::default()
         };
-        model.insert(db).await
+        Staff::decrypt(cipher, model.insert(db).await?)
+    }
+
+    // 删除员工会级联删除生产记录, 出勤和奖惩记录
+    pub(crate) async fn check_cascade_open<C: ConnectionTrait>(
+        db: &C,
+        workshop_id: i32,
+        staff_ids: SelectStatement,
+    ) -> Result<(), DbErr> {
+        AccountingPeriodService::check_cascade_open(
+            db,
+            workshop_id,
+            production::Entity::find()
+                .filter(production::Column::StaffId.in_subquery(staff_ids.clone())),
+            production::Column::CreateAt,
+        )
+        .await?;
+        AccountingPeriodService::check_cascade_date_open(
+            db,
+            workshop_id,
+            attendance::Entity::find()
+                .filter(attendance::Column::StaffId.in_subquery(staff_ids.clone())),
+            attendance::Column::WorkDate,
+        )
+        .await?;
+        AccountingPeriodService::check_cascade_open(
+            db,
+            workshop_id,
+            staff_adjustment::Entity::find()
+                .filter(staff_adjustment::Column::StaffId.in_subquery(staff_ids)),
+            staff_adjustment::Column::CreateAt,
+        )
+        .await
     }
 
-    pub async fn delete(db: &DbConn, user_id: i32, id: i32) -> Result<Model, DbErr> {
-        let model = Self::find_by_id(db, user_id, id).await?;
+    pub async fn delete(
+        db: &DbConn,
+        cipher: &Cipher,
+        workshop_id: i32,
+        id: i32,
+    ) -> Result<Staff, DbErr> {
+        let model = Self::find_model(db, workshop_id, id).await?;
+        Self::check_cascade_open(
+            db,
+            workshop_id,
+            Self::workshop_staff_ids(workshop_id, Some(vec![model.id])),
+        )
+        .await?;
 
         let model_clone = model.clone();
         model.delete(db).await?;
-        Ok(model_clone)
+        Staff::decrypt(cipher, model_clone)
     }
 
     pub async fn update(
         db: &DbConn,
-        user_id: i32,
+        cipher: &Cipher,
+        workshop_id: i32,
         id: i32,
         params: UpdateStaffParams,
-    ) -> Result<Model, DbErr> {
-        let model = Self::find_by_id(db, user_id, id).await?;
+    ) -> Result<Staff, DbErr> {
+        let model = Self::find_model(db, workshop_id, id).await?;
+
+        check_dates(
+            params.hire_date.or(model.hire_date),
+            params.leave_date.or(model.leave_date),
+        )?;
 
         let mut model = model.into_active_model();
         if let Some(name) = params.name {
+            model.pinyin = sea_orm::ActiveValue::Set(pinyin_initials(&name));
             model.name = sea_orm::ActiveValue::Set(name);
         }
 
@@ -62,31 +267,64 @@ impl StaffService {
 
         model.description = sea_orm::ActiveValue::Set(params.description);
 
-        model.update(db).await
+        if let Some(id_card) = params.id_card {
+            if !id_card.trim().is_empty() {
+                crate::utils::validate_id_card(id_card.trim())
+                    .map_err(|_| DbErr::Custom("invalid id card number".into()))?;
+            }
+            model.id_card = sea_orm::ActiveValue::Set(encrypt(cipher, id_card)?);
+        }
+
+        if let Some(bank_account) = params.bank_account {
+            model.bank_account = sea_orm::ActiveValue::Set(encrypt(cipher, bank_account)?);
+        }
+
+        if let Some(hire_date) = params.hire_date {
+            model.hire_date = sea_orm::ActiveValue::Set(Some(hire_date));
+        }
+
+        if let Some(leave_date) = params.leave_date {
+            model.leave_date = sea_orm::ActiveValue::Set(Some(leave_date));
+        }
+
+        if let Some(status) = params.status {
+            model.status = sea_orm::ActiveValue::Set(status);
+        }
+
+        Staff::decrypt(cipher, model.update(db).await?)
     }
 
-    pub async fn find_by_id(db: &DbConn, user_id: i32, id: i32) -> Result<Model, DbErr> {
-        Entity::find_by_id(id)
-            .filter(Column::UserId.eq(user_id))
-            .one(db)
-            .await?
-            .ok_or(DbErr::RecordNotFound("Cannot find staff".into()))
+    pub async fn find_by_id(
+        db: &DbConn,
+        cipher: &Cipher,
+        workshop_id: i32,
+        id: i32,
+    ) -> Result<Staff, DbErr> {
+        Staff::decrypt(cipher, Self::find_model(db, workshop_id, id).await?)
     }
 
-    pub async fn find_by_user_id(
+    pub async fn find_by_workshop_id(
         db: &DbConn,
-        user_id: i32,
+        cipher: &Cipher,
+        workshop_id: i32,
         params: ListQueryParams,
-    ) -> Result<ListResult<Model>, DbErr> {
-        let mut select = Entity::find().order_by_desc(Column::CreateAt);
+        status: Option<StaffStatus>,
+    ) -> Result<ListResult<Staff>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(Column::WorkshopId.eq(workshop_id));
 
-        select = select.filter(Column::UserId.eq(user_id));
+        if let Some(status) = status {
+            select = select.filter(Column::Status.eq(status));
+        }
 
-        if let Some(search) = params.search {
+        if let Some(search) = &params.search {
             select = select.filter(
                 Column::Name
-                    .contains(&search)
-                    .or(Column::Description.contains(&search)),
+                    .contains(search)
+                    .or(Column::Description.contains(search))
+                    .or(Column::PhoneNumber.contains(search))
+                    .or(Column::Pinyin.contains(search.to_lowercase())),
             );
         }
 
@@ -98,10 +336,27 @@ impl StaffService {
             select = select.filter(Column::CreateAt.lt(end_time));
         }
 
-        let total = select.clone().count(db).await?;
-        let (page, page_size) = (params.page.unwrap(), params.page_size.unwrap());
-        let data = select.paginate(db, page_size).fetch_page(page).await?;
+        let ListResult {
+            total,
+            data,
+            next_cursor,
+        } = paginate(
+            db,
+            select,
+            &params,
+            StaffListQueryParams::SORTABLE,
+            (Column::CreateAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await?;
 
-        Ok(ListResult { total, data })
+        Ok(ListResult {
+            total,
+            data: data
+                .into_iter()
+                .map(|staff| Staff::masked(cipher, staff))
+                .collect(),
+            next_cursor,
+        })
     }
 }
@@ -0,0 +1,165 @@
+use ::entity::{
+    sea_orm_active_enums::AdjustmentKind,
+    staff_adjustment::{ActiveModel, Column, Entity, Model},
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    accounting_period::AccountingPeriodService, pagination::paginate, staff::StaffService,
+    ListQueryParams, ListResult, SortOrder, Sortable,
+};
+pub struct StaffAdjustmentService;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateStaffAdjustmentParams {
+    pub staff_id: i32,
+    pub kind: AdjustmentKind,
+    // 奖励和扣款都填正数, 方向由 kind 决定
+    #[validate(range(exclusive_min = 0.0, message = "amount must be greater than 0"))]
+    pub amount: f32,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateStaffAdjustmentParams {
+    pub kind: Option<AdjustmentKind>,
+    #[validate(range(exclusive_min = 0.0, message = "amount must be greater than 0"))]
+    pub amount: Option<f32>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct StaffAdjustmentListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub staff_ids: Option<Vec<i32>>,
+
+    pub kind: Option<AdjustmentKind>,
+}
+
+impl Sortable for StaffAdjustmentListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::Amount, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+impl StaffAdjustmentService {
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        params: CreateStaffAdjustmentParams,
+    ) -> Result<Model, DbErr> {
+        let staff = StaffService::find_model(db, workshop_id, params.staff_id).await?;
+
+        let model = ActiveModel {
+            kind: Set(params.kind),
+            amount: Set(params.amount),
+            description: Set(params.description),
+            staff_id: Set(staff.id),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        AccountingPeriodService::check_open(db, workshop_id, model.create_at).await?;
+
+        let model_clone = model.clone();
+        model.delete(db).await?;
+        Ok(model_clone)
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateStaffAdjustmentParams,
+    ) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        AccountingPeriodService::check_open(db, workshop_id, model.create_at).await?;
+
+        let mut model = model.into_active_model();
+        if let Some(kind) = params.kind {
+            model.kind = Set(kind);
+        }
+        if let Some(amount) = params.amount {
+            model.amount = Set(amount);
+        }
+        if let Some(description) = params.description {
+            model.description = Set(Some(description));
+        }
+
+        model.update(db).await
+    }
+
+    // 结算后不再计入员工余额
+    pub async fn settle(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let mut model = model.into_active_model();
+        model.settled = Set(true);
+
+        model.update(db).await
+    }
+
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(
+                Column::StaffId.in_subquery(StaffService::workshop_staff_ids(workshop_id, None)),
+            )
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("staff adjustment not found".into()))
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+        staff_ids: Option<Vec<i32>>,
+        kind: Option<AdjustmentKind>,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(
+            Column::StaffId.in_subquery(StaffService::workshop_staff_ids(workshop_id, staff_ids)),
+        );
+
+        if let Some(kind) = kind {
+            select = select.filter(Column::Kind.eq(kind));
+        }
+
+        if let Some(search) = &params.search {
+            select = select.filter(Column::Description.contains(search));
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::CreateAt.lt(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            StaffAdjustmentListQueryParams::SORTABLE,
+            (Column::CreateAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+}
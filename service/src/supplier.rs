@@ -0,0 +1,343 @@
+pub struct SupplierService;
+use std::collections::HashMap;
+
+use ::entity::{
+    procurement, procurement_item, procurement_material,
+    supplier::{ActiveModel, Column, Entity, Model},
+    supplier_payment,
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, *};
+use sea_query::Expr;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    pagination::paginate, search::pinyin_initials, ListQueryParams, ListResult, SortOrder, Sortable,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateSupplierParams {
+    #[validate(length(min = 1, max = 64, message = "name must be 1 to 64 characters"))]
+    pub name: String,
+    // 联系人
+    pub contact: Option<String>,
+    #[validate(custom(function = "crate::utils::validate_phone_number"))]
+    pub phone_number: Option<String>,
+    pub address: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateSupplierParams {
+    #[validate(length(min = 1, max = 64, message = "name must be 1 to 64 characters"))]
+    pub name: Option<String>,
+    pub contact: Option<String>,
+    #[validate(custom(function = "crate::utils::validate_phone_number"))]
+    pub phone_number: Option<String>,
+    pub address: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct PurchasedClothing {
+    pub clothing_id: i32,
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct PurchasedMaterial {
+    pub material_id: i32,
+    pub amount: f32,
+}
+
+// 供应商的进货汇总
+#[derive(Debug, Serialize)]
+pub struct SupplierHistory {
+    #[serde(flatten)]
+    pub supplier: Model,
+    pub procurement_count: i64,
+    #[serde(flatten)]
+    pub payable: Payable,
+    pub first_procurement_at: Option<DateTimeWithTimeZone>,
+    pub last_procurement_at: Option<DateTimeWithTimeZone>,
+    pub clothings: Vec<PurchasedClothing>,
+    pub materials: Vec<PurchasedMaterial>,
+}
+
+// 应付账款 = 进货成本 - 已付款
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+pub struct Payable {
+    pub purchased: f32,
+    pub paid: f32,
+    pub payable: f32,
+}
+
+impl Payable {
+    fn new(purchased: f32, paid: f32) -> Self {
+        Payable {
+            purchased,
+            paid,
+            payable: purchased - paid,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupplierPayable {
+    #[serde(flatten)]
+    pub supplier: Model,
+    #[serde(flatten)]
+    pub payable: Payable,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SupplierListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+}
+
+impl Sortable for SupplierListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::Name, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+impl SupplierService {
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        params: CreateSupplierParams,
+    ) -> Result<Model, DbErr> {
+        let model = ActiveModel {
+            pinyin: Set(pinyin_initials(&params.name)),
+            name: Set(params.name),
+            contact: Set(params.contact),
+            phone_number: Set(params.phone_number),
+            address: Set(params.address),
+            description: Set(params.description),
+            workshop_id: Set(workshop_id),
+            ..Default::default()
+        };
+        model.insert(db).await
+    }
+
+    // 删除供应商后进货单保留, 供应商置空
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let model_clone = model.clone();
+        model.delete(db).await?;
+        Ok(model_clone)
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateSupplierParams,
+    ) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+
+        let mut model = model.into_active_model();
+        if let Some(name) = params.name {
+            model.pinyin = Set(pinyin_initials(&name));
+            model.name = Set(name);
+        }
+        model.contact = Set(params.contact);
+        model.phone_number = Set(params.phone_number);
+        model.address = Set(params.address);
+        model.description = Set(params.description);
+
+        model.update(db).await
+    }
+
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("Cannot find supplier".into()))
+    }
+
+    // 查找工坊关联的model
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(Column::WorkshopId.eq(workshop_id));
+
+        if let Some(search) = &params.search {
+            select = select.filter(
+                Column::Name
+                    .contains(search)
+                    .or(Column::Contact.contains(search))
+                    .or(Column::Description.contains(search))
+                    .or(Column::PhoneNumber.contains(search))
+                    .or(Column::Pinyin.contains(search.to_lowercase())),
+            );
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::CreateAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::CreateAt.lt(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            SupplierListQueryParams::SORTABLE,
+            (Column::CreateAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+
+    // 进货次数, 首次和最近进货时间, 以及各服装和物料的累计进货数量
+    pub async fn history(db: &DbConn, workshop_id: i32, id: i32) -> Result<SupplierHistory, DbErr> {
+        let supplier = Self::find_by_id(db, workshop_id, id).await?;
+
+        let procurements = procurement::Entity::find()
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
+            .filter(procurement::Column::SupplierId.eq(supplier.id));
+
+        let (procurement_count, first_procurement_at, last_procurement_at) = procurements
+            .clone()
+            .select_only()
+            .column_as(procurement::Column::Id.count(), "count")
+            .column_as(procurement::Column::CreateAt.min(), "first")
+            .column_as(procurement::Column::CreateAt.max(), "last")
+            .into_tuple::<(
+                i64,
+                Option<DateTimeWithTimeZone>,
+                Option<DateTimeWithTimeZone>,
+            )>()
+            .one(db)
+            .await?
+            .unwrap_or_default();
+
+        let clothings = procurement_item::Entity::find()
+            .select_only()
+            .column(procurement_item::Column::ClothingId)
+            .column_as(Expr::col(procurement_item::Column::Amount).sum(), "amount")
+            .filter(
+                procurement_item::Column::ProcurementId.in_subquery(
+                    procurements
+                        .clone()
+                        .select_only()
+                        .column(procurement::Column::Id)
+                        .into_query(),
+                ),
+            )
+            .group_by(procurement_item::Column::ClothingId)
+            .order_by_asc(procurement_item::Column::ClothingId)
+            .into_model::<PurchasedClothing>()
+            .all(db)
+            .await?;
+
+        let materials = procurement_material::Entity::find()
+            .select_only()
+            .column(procurement_material::Column::MaterialId)
+            .column_as(
+                Expr::col(procurement_material::Column::Amount).sum(),
+                "amount",
+            )
+            .filter(
+                procurement_material::Column::ProcurementId.in_subquery(
+                    procurements
+                        .select_only()
+                        .column(procurement::Column::Id)
+                        .into_query(),
+                ),
+            )
+            .group_by(procurement_material::Column::MaterialId)
+            .order_by_asc(procurement_material::Column::MaterialId)
+            .into_model::<PurchasedMaterial>()
+            .all(db)
+            .await?;
+
+        let purchased = Self::purchased(db, workshop_id).await?;
+        let paid = Self::paid(db, workshop_id).await?;
+        let payable = Payable::new(
+            purchased.get(&supplier.id).copied().unwrap_or_default(),
+            paid.get(&supplier.id).copied().unwrap_or_default(),
+        );
+
+        Ok(SupplierHistory {
+            supplier,
+            procurement_count,
+            payable,
+            first_procurement_at,
+            last_procurement_at,
+            clothings,
+            materials,
+        })
+    }
+
+    // 各供应商的进货成本
+    async fn purchased(db: &DbConn, workshop_id: i32) -> Result<HashMap<i32, f32>, DbErr> {
+        Ok(procurement::Entity::find()
+            .select_only()
+            .column(procurement::Column::SupplierId)
+            .column_as(Expr::col(procurement::Column::TotalCost).sum(), "purchased")
+            .filter(procurement::Column::WorkshopId.eq(workshop_id))
+            .filter(procurement::Column::SupplierId.is_not_null())
+            .group_by(procurement::Column::SupplierId)
+            .into_tuple::<(i32, f32)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    // 各供应商的已付款
+    async fn paid(db: &DbConn, workshop_id: i32) -> Result<HashMap<i32, f32>, DbErr> {
+        Ok(supplier_payment::Entity::find()
+            .select_only()
+            .column(supplier_payment::Column::SupplierId)
+            .column_as(Expr::col(supplier_payment::Column::Amount).sum(), "paid")
+            .filter(supplier_payment::Column::WorkshopId.eq(workshop_id))
+            .group_by(supplier_payment::Column::SupplierId)
+            .into_tuple::<(i32, f32)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    // 应付账款报表, 按应付金额从高到低排序
+    pub async fn payables(db: &DbConn, workshop_id: i32) -> Result<Vec<SupplierPayable>, DbErr> {
+        let suppliers = Entity::find()
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .all(db)
+            .await?;
+        let purchased = Self::purchased(db, workshop_id).await?;
+        let paid = Self::paid(db, workshop_id).await?;
+
+        let mut payables = suppliers
+            .into_iter()
+            .map(|supplier| SupplierPayable {
+                payable: Payable::new(
+                    purchased.get(&supplier.id).copied().unwrap_or_default(),
+                    paid.get(&supplier.id).copied().unwrap_or_default(),
+                ),
+                supplier,
+            })
+            .collect::<Vec<_>>();
+        payables.sort_by(|a, b| b.payable.payable.total_cmp(&a.payable.payable));
+
+        Ok(payables)
+    }
+}
@@ -0,0 +1,148 @@
+use ::entity::supplier_payment::{ActiveModel, Column, Entity, Model};
+use sea_orm::{prelude::DateTimeWithTimeZone, *};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    accounting_period::AccountingPeriodService, pagination::paginate, supplier::SupplierService,
+    ListQueryParams, ListResult, SortOrder, Sortable,
+};
+pub struct SupplierPaymentService;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateSupplierPaymentParams {
+    pub supplier_id: i32,
+    #[validate(range(exclusive_min = 0.0, message = "amount must be greater than 0"))]
+    pub amount: f32,
+    // 付款时间, 默认为当前时间
+    pub paid_at: Option<DateTimeWithTimeZone>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateSupplierPaymentParams {
+    #[validate(range(exclusive_min = 0.0, message = "amount must be greater than 0"))]
+    pub amount: Option<f32>,
+    pub paid_at: Option<DateTimeWithTimeZone>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SupplierPaymentListQueryParams {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub list_query: ListQueryParams,
+
+    #[serde(deserialize_with = "crate::utils::parse_svc", default)]
+    pub supplier_ids: Option<Vec<i32>>,
+}
+
+impl Sortable for SupplierPaymentListQueryParams {
+    type Column = Column;
+
+    const SORTABLE: &'static [Column] = &[Column::Amount, Column::PaidAt, Column::CreateAt];
+
+    fn list_query(&self) -> &ListQueryParams {
+        &self.list_query
+    }
+}
+
+impl SupplierPaymentService {
+    pub async fn create(
+        db: &DbConn,
+        workshop_id: i32,
+        params: CreateSupplierPaymentParams,
+    ) -> Result<Model, DbErr> {
+        let supplier = SupplierService::find_by_id(db, workshop_id, params.supplier_id).await?;
+
+        let mut model = ActiveModel {
+            amount: Set(params.amount),
+            description: Set(params.description),
+            supplier_id: Set(supplier.id),
+            workshop_id: Set(workshop_id),
+            ..Default::default()
+        };
+        if let Some(paid_at) = params.paid_at {
+            AccountingPeriodService::check_open(db, workshop_id, paid_at).await?;
+            model.paid_at = Set(paid_at);
+        }
+        model.insert(db).await
+    }
+
+    pub async fn delete(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        AccountingPeriodService::check_open(db, workshop_id, model.paid_at).await?;
+
+        let model_clone = model.clone();
+        model.delete(db).await?;
+        Ok(model_clone)
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        workshop_id: i32,
+        id: i32,
+        params: UpdateSupplierPaymentParams,
+    ) -> Result<Model, DbErr> {
+        let model = Self::find_by_id(db, workshop_id, id).await?;
+        AccountingPeriodService::check_open(db, workshop_id, model.paid_at).await?;
+
+        let mut model = model.into_active_model();
+        if let Some(amount) = params.amount {
+            model.amount = Set(amount);
+        }
+        if let Some(paid_at) = params.paid_at {
+            AccountingPeriodService::check_open(db, workshop_id, paid_at).await?;
+            model.paid_at = Set(paid_at);
+        }
+        model.description = Set(params.description);
+
+        model.update(db).await
+    }
+
+    pub async fn find_by_id(db: &DbConn, workshop_id: i32, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .filter(Column::WorkshopId.eq(workshop_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("supplier payment not found".into()))
+    }
+
+    pub async fn find_by_workshop_id(
+        db: &DbConn,
+        workshop_id: i32,
+        params: ListQueryParams,
+        supplier_ids: Option<Vec<i32>>,
+    ) -> Result<ListResult<Model>, DbErr> {
+        let mut select = Entity::find();
+
+        select = select.filter(Column::WorkshopId.eq(workshop_id));
+
+        if let Some(supplier_ids) = supplier_ids {
+            select = select.filter(Column::SupplierId.is_in(supplier_ids));
+        }
+
+        if let Some(search) = &params.search {
+            select = select.filter(Column::Description.contains(search));
+        }
+
+        if let Some(start_time) = params.start_time {
+            select = select.filter(Column::PaidAt.gt(start_time));
+        }
+
+        if let Some(end_time) = params.end_time {
+            select = select.filter(Column::PaidAt.lt(end_time));
+        }
+
+        paginate(
+            db,
+            select,
+            &params,
+            SupplierPaymentListQueryParams::SORTABLE,
+            (Column::PaidAt, SortOrder::Desc),
+            Column::Id,
+        )
+        .await
+    }
+}
@@ -1,31 +1,103 @@
 pub struct UserService;
-use ::entity::user::{ActiveModel, Column, Entity, Model};
-use sea_orm::*;
+use ::entity::{
+    password_reset,
+    user::{ActiveModel, Column, Entity, Model},
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, sea_query::Expr, *};
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+use crate::workshop::{CreateWorkshopParams, WorkshopService};
+
+// 重置码最多可以输错的次数
+const MAX_RESET_ATTEMPTS: i32 = 5;
+
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
 pub struct CreateUserParams {
+    #[validate(length(min = 3, max = 32, message = "username must be 3 to 32 characters"))]
     pub username: String,
+    #[validate(custom(function = "crate::utils::validate_password"))]
     pub password: String,
+    #[validate(custom(function = "crate::utils::validate_phone_number"))]
     pub phone_number: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
 pub struct UpdateUserParams {
+    #[validate(length(min = 3, max = 32, message = "username must be 3 to 32 characters"))]
     pub username: Option<String>,
-    pub password: Option<String>,
+    #[validate(custom(function = "crate::utils::validate_phone_number"))]
     pub phone_number: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ChangePasswordParams {
+    pub old_password: String,
+    #[validate(custom(function = "crate::utils::validate_password"))]
+    pub new_password: String,
+}
+
+// 管理员为忘记密码的用户生成重置码
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResetCodeParams {
+    pub phone_number: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ResetPasswordParams {
+    pub phone_number: String,
+    pub code: String,
+    #[validate(custom(function = "crate::utils::validate_password"))]
+    pub new_password: String,
+}
+
+// 返回给前端的用户信息, 不包含密码
+#[derive(Debug, Serialize)]
+pub struct UserInfo {
+    pub id: i32,
+    pub username: String,
+    pub phone_number: String,
+    pub is_admin: bool,
+    pub create_at: DateTimeWithTimeZone,
+}
+
+impl From<Model> for UserInfo {
+    fn from(user: Model) -> Self {
+        UserInfo {
+            id: user.id,
+            username: user.username,
+            phone_number: user.phone_number,
+            is_admin: user.is_admin,
+            create_at: user.create_at,
+        }
+    }
+}
+
 impl UserService {
+    // 注册时创建一个默认工坊
     pub async fn create(db: &DbConn, params: CreateUserParams) -> Result<Model, DbErr> {
+        let txn = db.begin().await?;
+
         let user = ActiveModel {
             username: sea_orm::ActiveValue::Set(params.username),
             password: sea_orm::ActiveValue::Set(params.password),
             phone_number: sea_orm::ActiveValue::Set(params.phone_number),
             ..Default::default()
         };
-        user.insert(db).await
+        let user = user.insert(&txn).await?;
+
+        WorkshopService::create(
+            &txn,
+            user.id,
+            CreateWorkshopParams {
+                name: user.username.clone(),
+                description: None,
+            },
+        )
+        .await?;
+
+        txn.commit().await?;
+        Ok(user)
     }
 
     pub async fn delete(db: &DbConn, id: i32) -> Result<Model, DbErr> {
@@ -34,9 +106,14 @@ impl UserService {
             .await?
             .ok_or(DbErr::RecordNotFound("Cannot find user".into()))?;
 
+        let txn = db.begin().await?;
+
+        WorkshopService::leave_all(&txn, user.id).await?;
+
         let user_clone = user.clone();
-        user.delete(db).await?;
+        user.delete(&txn).await?;
 
+        txn.commit().await?;
         Ok(user_clone)
     }
 
@@ -52,10 +129,6 @@ impl UserService {
             user.username = sea_orm::ActiveValue::Set(username);
         }
 
-        if let Some(password) = params.password {
-            user.password = sea_orm::ActiveValue::Set(password);
-        }
-
         if let Some(phone_number) = params.phone_number {
             user.phone_number = sea_orm::ActiveValue::Set(phone_number);
         }
@@ -63,6 +136,114 @@ impl UserService {
         user.update(db).await
     }
 
+    pub async fn find_by_id(db: &DbConn, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("Cannot find user".into()))
+    }
+
+    // password 为加密后的密码
+    pub async fn set_password(db: &DbConn, id: i32, password: String) -> Result<Model, DbErr> {
+        let user = Self::find_by_id(db, id).await?;
+
+        let mut user = user.into_active_model();
+        user.password = sea_orm::ActiveValue::Set(password);
+
+        user.update(db).await
+    }
+
+    // code 为加密后的重置码, 生成新的重置码时旧的作废
+    pub async fn create_reset_code(
+        db: &DbConn,
+        user_id: i32,
+        issued_by: i32,
+        code: String,
+        expire_at: DateTimeWithTimeZone,
+    ) -> Result<password_reset::Model, DbErr> {
+        password_reset::Entity::update_many()
+            .col_expr(password_reset::Column::Used, Expr::value(true))
+            .filter(password_reset::Column::UserId.eq(user_id))
+            .filter(password_reset::Column::Used.eq(false))
+            .exec(db)
+            .await?;
+
+        let reset = password_reset::ActiveModel {
+            code: sea_orm::ActiveValue::Set(code),
+            expire_at: sea_orm::ActiveValue::Set(expire_at),
+            user_id: sea_orm::ActiveValue::Set(user_id),
+            issued_by: sea_orm::ActiveValue::Set(issued_by),
+            ..Default::default()
+        };
+        reset.insert(db).await
+    }
+
+    // 未使用且未过期的重置码
+    pub async fn find_reset_code(
+        db: &DbConn,
+        user_id: i32,
+        now: DateTimeWithTimeZone,
+    ) -> Result<password_reset::Model, DbErr> {
+        password_reset::Entity::find()
+            .filter(password_reset::Column::UserId.eq(user_id))
+            .filter(password_reset::Column::Used.eq(false))
+            .filter(password_reset::Column::ExpireAt.gt(now))
+            .order_by_desc(password_reset::Column::CreateAt)
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("Cannot find reset code".into()))
+    }
+
+    // 重置码输错一次记一次, 达到上限后作废, 需要重新生成
+    pub async fn fail_reset_code(db: &DbConn, id: i32) -> Result<(), DbErr> {
+        // SET 中的列都是更新前的值
+        password_reset::Entity::update_many()
+            .col_expr(
+                password_reset::Column::Attempts,
+                Expr::col(password_reset::Column::Attempts).add(1),
+            )
+            .col_expr(
+                password_reset::Column::Used,
+                Expr::col(password_reset::Column::Attempts).gte(MAX_RESET_ATTEMPTS - 1),
+            )
+            .filter(password_reset::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    // 重置码只能使用一次
+    pub async fn reset_password(
+        db: &DbConn,
+        reset: password_reset::Model,
+        password: String,
+    ) -> Result<Model, DbErr> {
+        let user = Self::find_by_id(db, reset.user_id).await?;
+
+        let txn = db.begin().await?;
+
+        let mut reset = reset.into_active_model();
+        reset.used = sea_orm::ActiveValue::Set(true);
+        reset.update(&txn).await?;
+
+        let mut user = user.into_active_model();
+        user.password = sea_orm::ActiveValue::Set(password);
+        let user = user.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(user)
+    }
+
+    // 把手机号对应的用户设为管理员, 返回是否找到该用户
+    pub async fn grant_admin(db: &DbConn, phone_number: String) -> Result<bool, DbErr> {
+        let result = Entity::update_many()
+            .col_expr(Column::IsAdmin, Expr::value(true))
+            .filter(Column::PhoneNumber.eq(phone_number))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
     pub async fn find_by_phone_number(db: &DbConn, phone_number: String) -> Result<Model, DbErr> {
         Entity::find()
             .filter(Column::PhoneNumber.eq(phone_number))
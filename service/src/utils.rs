@@ -1,4 +1,7 @@
 use serde::{Deserialize, Deserializer};
+use validator::ValidationError;
+
+use crate::ListQueryParams;
 
 pub fn default_page_size() -> Option<u64> {
     Some(10)
@@ -8,6 +11,66 @@ pub fn default_page() -> Option<u64> {
     Some(0)
 }
 
+pub fn default_horizon() -> Option<u64> {
+    Some(3)
+}
+
+// 手机号 (1 开头 11 位) 或座机号 (数字和 -, 7 到 20 位)
+pub fn validate_phone_number(phone_number: &str) -> Result<(), ValidationError> {
+    let mobile = phone_number.len() == 11
+        && phone_number.starts_with('1')
+        && phone_number.chars().all(|c| c.is_ascii_digit());
+    let landline = (7..=20).contains(&phone_number.len())
+        && phone_number.chars().all(|c| c.is_ascii_digit() || c == '-')
+        && !phone_number.starts_with('-');
+
+    if mobile || landline {
+        Ok(())
+    } else {
+        Err(ValidationError::new("phone_number").with_message("invalid phone number".into()))
+    }
+}
+
+// 密码 8 到 64 位, 至少包含一个字母和一个数字
+pub fn validate_password(password: &str) -> Result<(), ValidationError> {
+    if (8..=64).contains(&password.chars().count())
+        && password.chars().any(|c| c.is_ascii_alphabetic())
+        && password.chars().any(|c| c.is_ascii_digit())
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("password").with_message(
+            "password must be 8 to 64 characters with at least one letter and one digit".into(),
+        ))
+    }
+}
+
+// 18 位身份证号, 最后一位可以是 X
+pub fn validate_id_card(id_card: &str) -> Result<(), ValidationError> {
+    let valid = id_card.len() == 18
+        && id_card.chars().take(17).all(|c| c.is_ascii_digit())
+        && id_card
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_ascii_digit() || c == 'X' || c == 'x');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("id_card").with_message("invalid id card number".into()))
+    }
+}
+
+pub fn validate_time_range(params: &ListQueryParams) -> Result<(), ValidationError> {
+    match (params.start_time, params.end_time) {
+        (Some(start_time), Some(end_time)) if start_time > end_time => {
+            Err(ValidationError::new("time_range")
+                .with_message("start_time must not be later than end_time".into()))
+        }
+        _ => Ok(()),
+    }
+}
+
 pub fn parse_option_u64<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
 where
     D: Deserializer<'de>,
@@ -31,4 +94,24 @@ where
         )),
         None => Ok(None),
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_id_cards() {
+        assert!(validate_id_card("110101199003071234").is_ok());
+        assert!(validate_id_card("11010119900307123X").is_ok());
+        assert!(validate_id_card("11010119900307123x").is_ok());
+    }
+
+    #[test]
+    fn invalid_id_cards() {
+        assert!(validate_id_card("").is_err());
+        assert!(validate_id_card("11010119900307123").is_err());
+        assert!(validate_id_card("1101011990030712345").is_err());
+        assert!(validate_id_card("1101011990030712X4").is_err());
+        assert!(validate_id_card("11010119900307123Y").is_err());
+    }
+}
@@ -0,0 +1,347 @@
+pub struct WorkshopService;
+pub use ::entity::sea_orm_active_enums::WorkshopRole;
+use ::entity::{
+    procurement, shipment, shipment_return, supplier_payment, user,
+    workshop::{ActiveModel, Entity, Model},
+    workshop_member,
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    accounting_period::AccountingPeriodService, clothing::ClothingService,
+    production::ProductionService, staff::StaffService, user::UserInfo,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateWorkshopParams {
+    #[validate(length(min = 1, max = 32, message = "name must be 1 to 32 characters"))]
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateWorkshopParams {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    // 返工工价系数, 例如 0.5 表示返工按半价计算
+    pub rework_rate: Option<f32>,
+    // 开启后计件工资只按质检合格的件数计算
+    pub pay_passed_only: Option<bool>,
+}
+
+// 通过手机号邀请已注册的用户加入工坊
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AddMemberParams {
+    pub phone_number: String,
+    pub role: Option<WorkshopRole>,
+}
+
+// 当前用户所在的工坊, 附带自己的角色
+#[derive(Debug, Serialize, Clone)]
+pub struct Workshop {
+    #[serde(flatten)]
+    pub workshop: Model,
+    pub role: WorkshopRole,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Member {
+    pub role: WorkshopRole,
+    pub user: UserInfo,
+}
+
+impl WorkshopService {
+    // 创建工坊, 创建者成为工坊主
+    pub async fn create<C>(
+        db: &C,
+        user_id: i32,
+        params: CreateWorkshopParams,
+    ) -> Result<Workshop, DbErr>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let txn = db.begin().await?;
+
+        let workshop = ActiveModel {
+            name: Set(params.name),
+            description: Set(params.description),
+            ..Default::default()
+        };
+        let workshop = workshop.insert(&txn).await?;
+
+        let member = workshop_member::ActiveModel {
+            role: Set(WorkshopRole::Owner),
+            workshop_id: Set(workshop.id),
+            user_id: Set(user_id),
+            ..Default::default()
+        };
+        let member = member.insert(&txn).await?;
+
+        txn.commit().await?;
+        Ok(Workshop {
+            workshop,
+            role: member.role,
+        })
+    }
+
+    pub async fn update(
+        db: &DbConn,
+        id: i32,
+        params: UpdateWorkshopParams,
+    ) -> Result<Model, DbErr> {
+        let workshop = Self::find_by_id(db, id).await?;
+        // 计薪方式变化时才需要重新计算工资
+        let recalculate = params
+            .pay_passed_only
+            .filter(|pay_passed_only| *pay_passed_only != workshop.pay_passed_only);
+
+        let mut workshop = workshop.into_active_model();
+
+        if let Some(name) = params.name {
+            workshop.name = Set(name);
+        }
+
+        if let Some(description) = params.description {
+            workshop.description = Set(Some(description));
+        }
+
+        if let Some(rework_rate) = params.rework_rate {
+            if rework_rate < 0.0 {
+                return Err(DbErr::Custom("rework rate must not be negative".into()));
+            }
+            workshop.rework_rate = Set(rework_rate);
+        }
+
+        if let Some(pay_passed_only) = params.pay_passed_only {
+            workshop.pay_passed_only = Set(pay_passed_only);
+        }
+
+        let txn = db.begin().await?;
+        let workshop = workshop.update(&txn).await?;
+        if let Some(pay_passed_only) = recalculate {
+            ProductionService::recalculate_unsettled(&txn, id, pay_passed_only).await?;
+        }
+        txn.commit().await?;
+
+        Ok(workshop)
+    }
+
+    // 工坊下有单据在已结账期间时不能删除工坊
+    async fn check_cascade_open<C: ConnectionTrait>(db: &C, id: i32) -> Result<(), DbErr> {
+        ClothingService::check_cascade_open(
+            db,
+            id,
+            ClothingService::workshop_clothing_ids(id, None),
+        )
+        .await?;
+        StaffService::check_cascade_open(db, id, StaffService::workshop_staff_ids(id, None))
+            .await?;
+        AccountingPeriodService::check_cascade_open(
+            db,
+            id,
+            shipment::Entity::find().filter(shipment::Column::WorkshopId.eq(id)),
+            shipment::Column::DispatchedAt,
+        )
+        .await?;
+        AccountingPeriodService::check_cascade_open(
+            db,
+            id,
+            procurement::Entity::find().filter(procurement::Column::WorkshopId.eq(id)),
+            procurement::Column::CreateAt,
+        )
+        .await?;
+        AccountingPeriodService::check_cascade_open(
+            db,
+            id,
+            shipment_return::Entity::find().filter(shipment_return::Column::WorkshopId.eq(id)),
+            shipment_return::Column::CreateAt,
+        )
+        .await?;
+        AccountingPeriodService::check_cascade_open(
+            db,
+            id,
+            supplier_payment::Entity::find().filter(supplier_payment::Column::WorkshopId.eq(id)),
+            supplier_payment::Column::PaidAt,
+        )
+        .await
+    }
+
+    // 删除工坊会级联删除工坊下的所有数据
+    pub async fn delete(db: &DbConn, id: i32) -> Result<Model, DbErr> {
+        let workshop = Self::find_by_id(db, id).await?;
+        Self::check_cascade_open(db, workshop.id).await?;
+
+        let workshop_clone = workshop.clone();
+        workshop.delete(db).await?;
+        Ok(workshop_clone)
+    }
+
+    // 用户加入的所有工坊, 按加入时间排序
+    pub async fn find_by_user_id(db: &DbConn, user_id: i32) -> Result<Vec<Workshop>, DbErr> {
+        Ok(workshop_member::Entity::find()
+            .filter(workshop_member::Column::UserId.eq(user_id))
+            .order_by_asc(workshop_member::Column::CreateAt)
+            .order_by_asc(workshop_member::Column::Id)
+            .find_also_related(Entity)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|(member, workshop)| {
+                workshop.map(|workshop| Workshop {
+                    workshop,
+                    role: member.role,
+                })
+            })
+            .collect())
+    }
+
+    // 用户在工坊中的成员身份, 不是成员时返回 RecordNotFound
+    pub async fn membership(
+        db: &DbConn,
+        workshop_id: i32,
+        user_id: i32,
+    ) -> Result<workshop_member::Model, DbErr> {
+        workshop_member::Entity::find()
+            .filter(workshop_member::Column::WorkshopId.eq(workshop_id))
+            .filter(workshop_member::Column::UserId.eq(user_id))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("workshop member not found".into()))
+    }
+
+    // 没有指定工坊时使用的默认工坊, 即最早加入的工坊
+    pub async fn default_membership(
+        db: &DbConn,
+        user_id: i32,
+    ) -> Result<workshop_member::Model, DbErr> {
+        workshop_member::Entity::find()
+            .filter(workshop_member::Column::UserId.eq(user_id))
+            .order_by_asc(workshop_member::Column::CreateAt)
+            .order_by_asc(workshop_member::Column::Id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("workshop member not found".into()))
+    }
+
+    pub async fn find_members(db: &DbConn, workshop_id: i32) -> Result<Vec<Member>, DbErr> {
+        Ok(workshop_member::Entity::find()
+            .filter(workshop_member::Column::WorkshopId.eq(workshop_id))
+            .order_by_asc(workshop_member::Column::CreateAt)
+            .order_by_asc(workshop_member::Column::Id)
+            .find_also_related(user::Entity)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|(member, user)| {
+                user.map(|user| Member {
+                    role: member.role,
+                    user: UserInfo::from(user),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn add_member(
+        db: &DbConn,
+        workshop_id: i32,
+        params: AddMemberParams,
+    ) -> Result<Member, DbErr> {
+        let user = user::Entity::find()
+            .filter(user::Column::PhoneNumber.eq(params.phone_number))
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("Cannot find user".into()))?;
+
+        if Self::membership(db, workshop_id, user.id).await.is_ok() {
+            return Err(DbErr::Custom("user is already a workshop member".into()));
+        }
+
+        let member = workshop_member::ActiveModel {
+            role: Set(params.role.unwrap_or(WorkshopRole::Member)),
+            workshop_id: Set(workshop_id),
+            user_id: Set(user.id),
+            ..Default::default()
+        };
+        let member = member.insert(db).await?;
+
+        Ok(Member {
+            role: member.role,
+            user: UserInfo::from(user),
+        })
+    }
+
+    // 工坊至少保留一个工坊主
+    pub async fn remove_member(
+        db: &DbConn,
+        workshop_id: i32,
+        user_id: i32,
+    ) -> Result<workshop_member::Model, DbErr> {
+        let member = Self::membership(db, workshop_id, user_id).await?;
+
+        if member.role == WorkshopRole::Owner {
+            let owners = workshop_member::Entity::find()
+                .filter(workshop_member::Column::WorkshopId.eq(workshop_id))
+                .filter(workshop_member::Column::Role.eq(WorkshopRole::Owner))
+                .count(db)
+                .await?;
+            if owners <= 1 {
+                return Err(DbErr::Custom(
+                    "cannot remove the last workshop owner".into(),
+                ));
+            }
+        }
+
+        let member_clone = member.clone();
+        member.delete(db).await?;
+        Ok(member_clone)
+    }
+
+    // 用户注销前处理其担任工坊主的工坊: 还有其他工坊主的不变,
+    // 有其他成员的转给最早加入的成员, 没有成员的连同数据一起删除
+    pub(crate) async fn leave_all<C: ConnectionTrait>(db: &C, user_id: i32) -> Result<(), DbErr> {
+        let owned = workshop_member::Entity::find()
+            .filter(workshop_member::Column::UserId.eq(user_id))
+            .filter(workshop_member::Column::Role.eq(WorkshopRole::Owner))
+            .all(db)
+            .await?;
+
+        for membership in owned {
+            let others = workshop_member::Entity::find()
+                .filter(workshop_member::Column::WorkshopId.eq(membership.workshop_id))
+                .filter(workshop_member::Column::UserId.ne(user_id))
+                .order_by_asc(workshop_member::Column::CreateAt)
+                .order_by_asc(workshop_member::Column::Id)
+                .all(db)
+                .await?;
+
+            if others.iter().any(|m| m.role == WorkshopRole::Owner) {
+                continue;
+            }
+
+            match others.into_iter().next() {
+                Some(member) => {
+                    let mut member = member.into_active_model();
+                    member.role = Set(WorkshopRole::Owner);
+                    member.update(db).await?;
+                }
+                None => {
+                    Self::check_cascade_open(db, membership.workshop_id).await?;
+                    Entity::delete_by_id(membership.workshop_id)
+                        .exec(db)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn find_by_id(db: &DbConn, id: i32) -> Result<Model, DbErr> {
+        Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::RecordNotFound("workshop not found".into()))
+    }
+}
@@ -1,9 +1,43 @@
+use std::net::SocketAddr;
+
+use axum::Router;
 use sea_orm::SqlxPostgresConnector;
+use shuttle_runtime::{CustomError, DeploymentMetadata, Environment, Error, SecretStore};
 use sqlx::PgPool;
+use tokio::net::TcpListener;
+
+// 和 shuttle_axum 一样启动 axum, 但带上连接的对端地址, 登录限流按客户端 IP 计数
+struct AxumService(Router);
+
+#[shuttle_runtime::async_trait]
+impl shuttle_runtime::Service for AxumService {
+    async fn bind(self, addr: SocketAddr) -> Result<(), Error> {
+        let listener = TcpListener::bind(addr).await.map_err(CustomError::new)?;
+        axum::serve(
+            listener,
+            self.0.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .map_err(CustomError::new)?;
+
+        Ok(())
+    }
+}
 
 #[shuttle_runtime::main]
-async fn main(#[shuttle_shared_db::Postgres] pool: PgPool) -> shuttle_axum::ShuttleAxum {
+async fn main(
+    #[shuttle_shared_db::Postgres] pool: PgPool,
+    #[shuttle_runtime::Secrets] secrets: SecretStore,
+    #[shuttle_runtime::Metadata] metadata: DeploymentMetadata,
+) -> Result<AxumService, Error> {
+    // 员工身份证号和银行卡号的加密密钥, 在 Secrets.toml 中配置
+    let staff_encryption_key = secrets.get("STAFF_ENCRYPTION_KEY").ok_or(CustomError::msg(
+        "STAFF_ENCRYPTION_KEY is missing in Secrets.toml, generate one with `openssl rand -base64 32`",
+    ))?;
+
     let db = SqlxPostgresConnector::from_sqlx_postgres_pool(pool);
-    let router = api::router(db, "yexiyue666".into()).await?;
-    Ok(router.into())
+    // 部署后请求都经过平台的代理转发
+    let behind_proxy = metadata.env == Environment::Deployment;
+    let router = api::router(db, "yexiyue666".into(), staff_encryption_key, behind_proxy).await?;
+    Ok(AxumService(router))
 }